@@ -1,9 +1,10 @@
 use chrono::Utc;
 use cron::Schedule;
 use serenity::all::{
-    CommandDataOptionValue, CreateAttachment, CreateMessage, EditInteractionResponse,
+    Channel, CreateAttachment, CreateMessage, EditInteractionResponse,
 };
 use serenity::prelude::*;
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -14,20 +15,101 @@ use serenity::builder::{CreateInteractionResponse, CreateInteractionResponseMess
 use serenity::model::application::{Command, Interaction};
 use serenity::model::id::{ChannelId, GuildId};
 
+use crate::database::quarantine::{get_unnotified_quarantines, set_quarantines_notified};
+use crate::i18n::{fill, Locale};
 use crate::utils::message::get_exchange_rate_message;
 use crate::{commands, environment};
 
+/// Group the configured channels by guild, so every guild gets its own templates
+async fn get_channels_by_guild(ctx: &Context) -> HashMap<Option<u64>, Vec<ChannelId>> {
+    let mut guilds: HashMap<Option<u64>, Vec<ChannelId>> = HashMap::new();
+
+    for channel in environment::get_channels() {
+        let channel_id = ChannelId::new(channel);
+        let guild_id = match channel_id.to_channel(ctx).await {
+            Ok(Channel::Guild(channel)) => Some(channel.guild_id.get()),
+            Ok(_) => None,
+            Err(why) => {
+                log::warn!("Cannot get guild of channel {}: {:?}", channel, why);
+                None
+            }
+        };
+        guilds.entry(guild_id).or_default().push(channel_id);
+    }
+
+    guilds
+}
+
+/// Preferred locale of the guild, `LOCALE` for channels outside of guilds
+fn get_guild_locale(ctx: &Context, guild_id: Option<u64>) -> Locale {
+    guild_id
+        .and_then(|id| ctx.cache.guild(id).map(|guild| guild.preferred_locale.clone()))
+        .map(|locale| Locale::from_discord(&locale))
+        .unwrap_or_else(|| Locale::from_discord(&environment::get_locale()))
+}
+
 async fn send_exchange_rate_message(ctx: Arc<Context>, from: &str, to: &str) {
-    let msg = get_exchange_rate_message(from, to).await;
+    for (guild_id, channels) in get_channels_by_guild(&ctx).await {
+        let locale = get_guild_locale(&ctx, guild_id);
+        let msg = get_exchange_rate_message(from, to, guild_id, locale, false, None).await;
+
+        let mut message = CreateMessage::new().content(msg.message);
+
+        // Conditionally add the file if `msg.graph` exists
+        if let Some(graph) = msg.graph {
+            let name = environment::get_chart_format().file_name("graph");
+            message = message.add_file(CreateAttachment::bytes(graph, name));
+        }
+
+        // Overview of the market, when MATRIX_CURRENCIES is set
+        if let Some(matrix) = commands::matrix::get_report_matrix(guild_id, locale).await {
+            let name = environment::get_chart_format().file_name("matrix");
+            message = message.add_file(CreateAttachment::bytes(matrix, name));
+        }
+
+        send_message_to(&ctx, &channels, &message).await;
+    }
+}
 
-    let mut message = CreateMessage::new().content(msg.message);
+/// Tell the admin channels about rates quarantined since the last time.
+///
+/// Quarantines stay unnotified until the message reaches an admin channel, so
+/// that the next scheduled run tries again.
+async fn notify_anomalies(ctx: &Context) {
+    let quarantines = get_unnotified_quarantines();
+    if quarantines.is_empty() {
+        return;
+    }
 
-    // Conditionally add the file if `msg.graph` exists
-    if let Some(graph) = msg.graph {
-        message = message.add_file(CreateAttachment::bytes(graph, "graph.png"));
+    // Admin channels are not tied to a guild, they take `LOCALE`
+    let catalog = Locale::from_discord(&environment::get_locale()).catalog();
+    let mut text = catalog.quarantine_title.to_string();
+    for quarantine in &quarantines {
+        text.push('\n');
+        text.push_str(&fill(
+            catalog.quarantine_item,
+            &[
+                ("DATE", &quarantine.date),
+                ("BASE", &quarantine.base),
+                ("PROVIDER", &quarantine.provider),
+                ("REASONS", &quarantine.reasons),
+            ],
+        ));
     }
 
-    send_message(&ctx, &message).await;
+    let channels: Vec<ChannelId> = environment::get_admin_channels()
+        .into_iter()
+        .map(ChannelId::new)
+        .collect();
+    if channels.is_empty() {
+        log::warn!("{}", text);
+    } else if send_message_to(ctx, &channels, &CreateMessage::new().content(text)).await == 0 {
+        log::warn!("Cannot notify any admin channel of {} quarantines", quarantines.len());
+        return;
+    }
+
+    let ids: Vec<i64> = quarantines.iter().map(|quarantine| quarantine.id).collect();
+    set_quarantines_notified(&ids);
 }
 
 struct ExchangeRateBotEventHandler {
@@ -39,18 +121,30 @@ async fn send_ready_message(ctx: &Context, ready: &serenity::model::gateway::Rea
 }
 
 async fn send_message(ctx: &Context, message: &CreateMessage) {
-    let channels = environment::get_channels();
-
-    for channel in channels {
-        // let channel_id = channel.as_u64().unwrap_or(0);
-        log::info!("Channel id: {}", channel);
+    let channels: Vec<ChannelId> = environment::get_channels()
+        .into_iter()
+        .map(ChannelId::new)
+        .collect();
 
-        let channel_id = ChannelId::new(channel);
+    send_message_to(ctx, &channels, message).await;
+}
 
-        if let Err(why) = channel_id.send_message(&ctx.http, message.clone()).await {
-            log::warn!("Error sending message: {:?}", why);
+/// Send `message` to every channel of `channels`, returns how many got it
+async fn send_message_to(
+    ctx: &Context,
+    channels: &[ChannelId],
+    message: &CreateMessage,
+) -> usize {
+    let mut sent = 0;
+    for channel_id in channels {
+        log::info!("Channel id: {}", channel_id);
+
+        match channel_id.send_message(&ctx.http, message.clone()).await {
+            Ok(_) => sent += 1,
+            Err(why) => log::warn!("Error sending message: {:?}", why),
         }
     }
+    sent
 }
 
 async fn send_text(ctx: &Context, text: &str) {
@@ -68,6 +162,16 @@ impl EventHandler for ExchangeRateBotEventHandler {
             vec![
                 commands::check_rate::register(),
                 commands::about::register(),
+                commands::ask::register(),
+                commands::template::register(),
+                commands::calendar::register(),
+                commands::history::register(),
+                commands::compare::register(),
+                commands::theme::register(),
+                commands::matrix::register(),
+                commands::stats::register(),
+                commands::forecast::register(),
+                commands::basket::register(),
             ],
         )
         .await;
@@ -102,9 +206,33 @@ impl EventHandler for ExchangeRateBotEventHandler {
 
             let content: Option<EditInteractionResponse> = match command.data.name.as_str() {
                 commands::check_rate::COMMAND_NAME => {
-                    Some(commands::check_rate::run(&command.data.options()).await)
+                    Some(commands::check_rate::run(&ctx, command).await)
                 }
                 commands::about::COMMAND_NAME => Some(commands::about::run()),
+                commands::ask::COMMAND_NAME => {
+                    // Edits its own response, it also needs the message to start a thread
+                    commands::ask::run(&ctx, command).await;
+                    None
+                }
+                commands::template::COMMAND_NAME => {
+                    Some(commands::template::run(command).await)
+                }
+                commands::calendar::COMMAND_NAME => {
+                    Some(commands::calendar::run(command).await)
+                }
+                commands::history::COMMAND_NAME => {
+                    Some(commands::history::run(command).await)
+                }
+                commands::compare::COMMAND_NAME => {
+                    Some(commands::compare::run(command).await)
+                }
+                commands::theme::COMMAND_NAME => Some(commands::theme::run(command).await),
+                commands::matrix::COMMAND_NAME => Some(commands::matrix::run(command).await),
+                commands::stats::COMMAND_NAME => Some(commands::stats::run(command).await),
+                commands::forecast::COMMAND_NAME => {
+                    Some(commands::forecast::run(command).await)
+                }
+                commands::basket::COMMAND_NAME => Some(commands::basket::run(command).await),
                 _ => Some(EditInteractionResponse::new().content("not implemented :(".to_string())),
             };
 
@@ -117,16 +245,20 @@ impl EventHandler for ExchangeRateBotEventHandler {
         }
 
         if let Interaction::Autocomplete(autocomplete) = &interaction {
-            if let Some(autocomplete_option) = autocomplete.data.options.iter().find_map(|option| {
-                if let CommandDataOptionValue::Autocomplete { value, .. } = &option.value {
-                    Some(value)
-                } else {
-                    None
-                }
-            }) {
+            // Also finds options of subcommands
+            if let Some(autocomplete_option) =
+                autocomplete.data.autocomplete().map(|option| option.value)
+            {
                 let complete_result = match autocomplete.data.name.as_str() {
-                    commands::check_rate::COMMAND_NAME => {
-                        Some(commands::check_rate::autocomplete(&autocomplete_option))
+                    commands::check_rate::COMMAND_NAME
+                    | commands::ask::COMMAND_NAME
+                    | commands::template::COMMAND_NAME
+                    | commands::calendar::COMMAND_NAME
+                    | commands::history::COMMAND_NAME
+                    | commands::compare::COMMAND_NAME
+                    | commands::stats::COMMAND_NAME
+                    | commands::forecast::COMMAND_NAME => {
+                        Some(commands::check_rate::autocomplete(autocomplete_option))
                     }
                     _ => None,
                 };
@@ -177,6 +309,8 @@ impl EventHandler for ExchangeRateBotEventHandler {
 
                         send_exchange_rate_message(Arc::clone(&ctx1), &exchange_from, &exchange_to)
                             .await;
+                        // Also reports the rates quarantined by commands since the last run
+                        notify_anomalies(&ctx1).await;
                     }
                 }
             });
@@ -24,7 +24,9 @@ pub fn run() -> EditInteractionResponse {
     - Exchange Rate API: `{}`\n\
     - Fallback Exchange Rate API: `{}`\n\
     - SearXNG API: `{}`\n\
-    - Ollama Model: `{}`\n\
+    - Economic Calendar: `{}`\n\
+    - LLM Backend: `{}`\n\
+    - LLM Model: `{}`\n\
     ```
     ",
         environment::APP_VERSION,
@@ -40,7 +42,9 @@ pub fn run() -> EditInteractionResponse {
             Some(url) => url,
             None => "N/A".to_string(),
         },
-        environment::get_ollama_model(),
+        environment::get_economic_calendar().unwrap_or("N/A".to_string()),
+        environment::get_llm_backend(),
+        environment::get_llm_model(),
     );
 
     return EditInteractionResponse::new().content(content);
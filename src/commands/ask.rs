@@ -0,0 +1,220 @@
+use chrono::{Duration, Utc};
+use serenity::all::{
+    AutoArchiveDuration, ChannelType, CommandInteraction, CommandOptionType, Context, CreateThread,
+    EditInteractionResponse,
+};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use tokio::sync::watch;
+
+use crate::database::conversation::{get_conversation, save_conversation_message};
+use crate::database::exchange_rate::get_saved_exchange_rates;
+use crate::environment;
+use crate::i18n::{fill, Locale};
+use crate::llm::backend::LlmError;
+use crate::llm::generate::{answer_question, GenerationOptions};
+use crate::llm::guardrails::Guardrails;
+use crate::llm::prompt::get_history_prompt;
+use crate::utils::progress::stream_to_response;
+
+use super::get_string_option;
+
+pub const COMMAND_NAME: &str = "ask";
+
+/// Days of stored rates given to the model
+const HISTORY_DAYS: i64 = 30;
+
+/// Earlier messages of the conversation sent along with a question
+const MAX_CONVERSATION_MESSAGES: usize = 20;
+
+/// Discord limits thread names to 100 characters
+const MAX_THREAD_NAME_CHARS: usize = 100;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Ask a question about exchange rates")
+        .name_localized("de", "frage")
+        .description_localized("de", "Stelle eine Frage zu Wechselkursen")
+        .description_localized("hi", "विनिमय दरों के बारे में प्रश्न पूछें")
+        .name_localized("ja", "質問")
+        .description_localized("ja", "為替レートについて質問します")
+        .name_localized("es-ES", "preguntar")
+        .description_localized("es-ES", "Haz una pregunta sobre tipos de cambio")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "question",
+                "Your question, e.g. \"How did EUR/USD move this month?\"",
+            )
+            .name_localized("de", "frage")
+            .description_localized(
+                "de",
+                "Deine Frage, z. B. \"Wie hat sich EUR/USD diesen Monat bewegt?\"",
+            )
+            .description_localized("hi", "आपका प्रश्न, जैसे \"इस महीने EUR/USD कैसे बदला?\"")
+            .name_localized("ja", "質問")
+            .description_localized("ja", "質問、例: \"今月 EUR/USD はどう動いた？\"")
+            .name_localized("es-ES", "pregunta")
+            .description_localized(
+                "es-ES",
+                "Tu pregunta, p. ej. \"¿Cómo se movió EUR/USD este mes?\"",
+            )
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "from",
+                "Currency to convert from",
+            )
+            .name_localized("de", "von")
+            .description_localized("de", "Die Ausgangswährung für die Konvertierung")
+            .description_localized("hi", "जिस मुद्रा से परिवर्तित करना है")
+            .name_localized("ja", "変換元")
+            .description_localized("ja", "変換する通貨")
+            .name_localized("es-ES", "de")
+            .description_localized("es-ES", "Moneda de origen para la conversión")
+            .required(false)
+            .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "to", "Currency to convert to")
+                .name_localized("de", "zu")
+                .description_localized("de", "Die Zielwährung für die Konvertierung")
+                .description_localized("hi", "जिस मुद्रा में परिवर्तित करना है")
+                .name_localized("ja", "変換先")
+                .description_localized("ja", "変換される通貨")
+                .name_localized("es-ES", "a")
+                .description_localized("es-ES", "Moneda de destino para la conversión")
+                .required(false)
+                .set_autocomplete(true),
+        )
+}
+
+/// Answer the question and keep the conversation going in a thread.
+///
+/// Conversations are remembered per user and channel. Asked outside of a
+/// thread, a thread is started from the answer so that follow-up questions
+/// asked there share the memory.
+pub async fn run(ctx: &Context, command: &CommandInteraction) {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let question = get_string_option(&options, "question").unwrap_or_default();
+    let from = get_string_option(&options, "from")
+        .unwrap_or_else(environment::get_exchange_from)
+        .to_uppercase();
+    let to = get_string_option(&options, "to")
+        .unwrap_or_else(environment::get_exchange_to)
+        .to_uppercase();
+
+    let in_thread = command.channel.as_ref().is_some_and(|channel| {
+        matches!(
+            channel.kind,
+            ChannelType::PublicThread | ChannelType::PrivateThread
+        )
+    });
+    // Direct messages have no threads, the channel itself holds the memory
+    let has_memory = in_thread || command.guild_id.is_none();
+    let user_id = command.user.id.get();
+    let conversation = if has_memory {
+        get_conversation(command.channel_id.get(), user_id, MAX_CONVERSATION_MESSAGES)
+    } else {
+        vec![]
+    };
+
+    let since = (Utc::now() - Duration::days(HISTORY_DAYS)).date_naive();
+    let history = get_saved_exchange_rates(&from, &to, since);
+    let history_prompt = match history.is_empty() {
+        true => fill(catalog.no_stored_rates, &[("FROM", &from), ("TO", &to)]),
+        false => get_history_prompt(&history, &from, &to),
+    };
+    let known_values: Vec<f64> = history
+        .iter()
+        .flat_map(|(_, rate)| [*rate, 1.0 / rate])
+        .filter(|value| value.is_finite())
+        .collect();
+    let guardrails = Guardrails::new(known_values, &[&from, &to]);
+
+    let (tx, rx) = watch::channel(String::new());
+    let editor = tokio::spawn(stream_to_response(ctx.clone(), command.clone(), rx));
+
+    let result = answer_question(
+        &question,
+        (&from, &to),
+        &history_prompt,
+        &conversation,
+        GenerationOptions {
+            guild_id: command.guild_id.map(|id| id.get()),
+            locale,
+            refresh: false,
+            guardrails: &guardrails,
+            progress: Some(tx),
+        },
+    )
+    .await;
+
+    // The sender is gone at this point, wait for the last progressive edit so
+    // it cannot overwrite the final response.
+    if let Err(why) = editor.await {
+        log::warn!("Progressive response task failed: {why}");
+    }
+
+    let answer = match result {
+        Ok(result) => result.content,
+        Err(LlmError::Disabled) => {
+            let content = catalog.llm_disabled;
+            if let Err(why) = command
+                .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+                .await
+            {
+                log::warn!("Cannot send final response to slash command: {why}");
+            }
+            return;
+        }
+        Err(e) => {
+            let content = fill(catalog.generation_error, &[("ERROR", &e.to_string())]);
+            if let Err(why) = command
+                .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+                .await
+            {
+                log::warn!("Cannot send final response to slash command: {why}");
+            }
+            return;
+        }
+    };
+
+    let message = match command
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(&answer))
+        .await
+    {
+        Ok(message) => message,
+        Err(why) => {
+            log::warn!("Cannot send final response to slash command: {why}");
+            return;
+        }
+    };
+
+    let memory_channel_id = if has_memory {
+        command.channel_id
+    } else {
+        let name: String = question.chars().take(MAX_THREAD_NAME_CHARS).collect();
+        match command
+            .channel_id
+            .create_thread_from_message(
+                &ctx.http,
+                message.id,
+                CreateThread::new(name).auto_archive_duration(AutoArchiveDuration::OneDay),
+            )
+            .await
+        {
+            Ok(thread) => thread.id,
+            Err(why) => {
+                log::warn!("Cannot create thread for /ask: {why}");
+                command.channel_id
+            }
+        }
+    };
+
+    save_conversation_message(memory_channel_id.get(), user_id, "user", &question);
+    save_conversation_message(memory_channel_id.get(), user_id, "assistant", &answer);
+}
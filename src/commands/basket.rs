@@ -0,0 +1,276 @@
+use chrono::Utc;
+use serenity::all::{CommandInteraction, CommandOptionType, EditInteractionResponse};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{ResolvedOption, ResolvedValue};
+
+use crate::environment;
+use crate::exchange_rate::ExchangeRateMap;
+use crate::i18n::{fill, Catalog, Locale};
+use crate::utils::basket::{
+    get_basket, get_baskets, parse_shares, remove_basket, set_basket, Basket, BasketError,
+    BasketKind,
+};
+
+use super::get_string_option;
+
+pub const COMMAND_NAME: &str = "basket";
+
+fn name_option(required: bool) -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "name",
+        "Basket name, 4 to 12 letters or digits",
+    )
+    .name_localized("de", "name")
+    .description_localized("de", "Name des Korbs, 4 bis 12 Buchstaben oder Ziffern")
+    .description_localized("hi", "बास्केट का नाम, 4 से 12 अक्षर या अंक")
+    .name_localized("ja", "名前")
+    .description_localized("ja", "バスケット名、4〜12 文字の英数字")
+    .name_localized("es-ES", "nombre")
+    .description_localized("es-ES", "Nombre de la cesta, de 4 a 12 letras o dígitos")
+    .required(required)
+}
+
+pub fn register() -> CreateCommand {
+    let mut kind = CreateCommandOption::new(
+        CommandOptionType::String,
+        "kind",
+        "How the currencies add up, weighted when left out",
+    )
+    .name_localized("de", "art")
+    .description_localized(
+        "de",
+        "Wie sich die Währungen zusammensetzen, ohne Angabe gewichtet",
+    )
+    .description_localized("hi", "मुद्राएँ कैसे जुड़ती हैं, न देने पर भारित")
+    .name_localized("ja", "種類")
+    .description_localized("ja", "通貨の合成方法、省略時は加重")
+    .name_localized("es-ES", "tipo")
+    .description_localized(
+        "es-ES",
+        "Cómo se combinan las monedas, ponderada si se omite",
+    )
+    .required(false);
+    for name in BasketKind::NAMES {
+        kind = kind.add_string_choice(*name, *name);
+    }
+
+    CreateCommand::new(COMMAND_NAME)
+        .description("Manage currency baskets, usable wherever a currency is")
+        .name_localized("de", "korb")
+        .description_localized(
+            "de",
+            "Verwaltet Währungskörbe, nutzbar überall, wo eine Währung angegeben wird",
+        )
+        .description_localized(
+            "hi",
+            "मुद्रा बास्केट प्रबंधित करें, जहाँ भी मुद्रा दी जाती है वहाँ उपयोगी",
+        )
+        .name_localized("ja", "バスケット")
+        .description_localized(
+            "ja",
+            "通貨バスケットを管理します。通貨を指定できる場所ならどこでも使えます",
+        )
+        .name_localized("es-ES", "cesta")
+        .description_localized(
+            "es-ES",
+            "Gestiona cestas de monedas, utilizables donde se indique una moneda",
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "view",
+                "Show a basket, or list all baskets",
+            )
+            .name_localized("de", "anzeigen")
+            .description_localized("de", "Zeigt einen Korb oder listet alle Körbe")
+            .description_localized("hi", "एक बास्केट दिखाएँ, या सभी बास्केट की सूची")
+            .name_localized("ja", "表示")
+            .description_localized("ja", "バスケットを表示、またはすべて一覧します")
+            .name_localized("es-ES", "ver")
+            .description_localized("es-ES", "Muestra una cesta o lista todas las cestas")
+            .add_sub_option(name_option(false)),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "set",
+                "Define a basket, worth 1 EUR today",
+            )
+            .name_localized("de", "festlegen")
+            .description_localized("de", "Legt einen Korb fest, heute 1 EUR wert")
+            .description_localized("hi", "एक बास्केट परिभाषित करें, आज 1 EUR के बराबर")
+            .name_localized("ja", "設定")
+            .description_localized("ja", "今日 1 EUR の価値となるバスケットを定義します")
+            .name_localized("es-ES", "definir")
+            .description_localized("es-ES", "Define una cesta que hoy vale 1 EUR")
+            .add_sub_option(name_option(true))
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "currencies",
+                    "Currencies and their weights, e.g. USD:40,EUR:30,CNY:30",
+                )
+                .name_localized("de", "währungen")
+                .description_localized(
+                    "de",
+                    "Währungen und ihre Gewichte, z. B. USD:40,EUR:30,CNY:30",
+                )
+                .description_localized("hi", "मुद्राएँ और उनके भार, जैसे USD:40,EUR:30,CNY:30")
+                .name_localized("ja", "通貨")
+                .description_localized("ja", "通貨とその重み、例: USD:40,EUR:30,CNY:30")
+                .name_localized("es-ES", "monedas")
+                .description_localized("es-ES", "Monedas y sus pesos, p. ej. USD:40,EUR:30,CNY:30")
+                .required(true),
+            )
+            .add_sub_option(kind),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "delete", "Delete a basket")
+                .name_localized("de", "löschen")
+                .description_localized("de", "Löscht einen Korb")
+                .description_localized("hi", "एक बास्केट हटाएँ")
+                .name_localized("ja", "削除")
+                .description_localized("ja", "バスケットを削除します")
+                .name_localized("es-ES", "eliminar")
+                .description_localized("es-ES", "Elimina una cesta")
+                .add_sub_option(name_option(true)),
+        )
+}
+
+async fn get_latest_rates(catalog: &Catalog) -> Result<ExchangeRateMap, String> {
+    ExchangeRateMap::get_rate(Utc::now().date_naive(), None)
+        .await
+        .map_err(|e| fill(catalog.rates_error, &[("ERROR", &format!("{:?}", e))]))
+}
+
+/// Message of `error` in the language of `catalog`
+fn format_error(error: &BasketError, catalog: &Catalog) -> String {
+    match error {
+        BasketError::InvalidName(name) => fill(catalog.basket_invalid_name, &[("NAME", name)]),
+        BasketError::InvalidComponent(component) => fill(
+            catalog.basket_invalid_component,
+            &[("COMPONENT", component)],
+        ),
+        BasketError::DuplicateCurrency(currency) => {
+            fill(catalog.basket_duplicate, &[("CURRENCY", currency)])
+        }
+        BasketError::Empty => catalog.basket_empty.to_string(),
+        BasketError::NoRate(currency) => fill(catalog.basket_no_rate, &[("CURRENCY", currency)]),
+    }
+}
+
+fn describe(basket: &Basket, rates: &ExchangeRateMap, locale: Locale) -> String {
+    let catalog = locale.catalog();
+    let shares = basket
+        .get_shares(rates)
+        .map(|shares| {
+            shares
+                .iter()
+                .map(|(currency, share)| {
+                    format!("{} {}%", currency, locale.format_number(*share, 2))
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_else(|| catalog.basket_rates_missing.to_string());
+    let value = match basket.get_value(rates, &rates.base) {
+        Some(value) => format!("{} {}", locale.format_number(value, 4), rates.base),
+        None => catalog.basket_value_unknown.to_string(),
+    };
+    fill(
+        catalog.basket_description,
+        &[
+            ("NAME", &basket.name),
+            ("KIND", basket.kind.name()),
+            ("SHARES", &shares),
+            ("VALUE", &value),
+            ("DATE", &locale.format_date(rates.get_date())),
+        ],
+    )
+}
+
+async fn view(name: Option<String>, locale: Locale) -> Result<String, String> {
+    let catalog = locale.catalog();
+    let Some(name) = name else {
+        let baskets = get_baskets();
+        if baskets.is_empty() {
+            return Ok(catalog.basket_none.to_string());
+        }
+        let names: Vec<String> = baskets
+            .iter()
+            .map(|basket| format!("`{}` ({})", basket.name, basket.kind.name()))
+            .collect();
+        return Ok(fill(catalog.basket_list, &[("BASKETS", &names.join(", "))]));
+    };
+
+    let basket =
+        get_basket(&name).ok_or_else(|| fill(catalog.basket_unknown, &[("NAME", &name)]))?;
+    Ok(describe(&basket, &get_latest_rates(catalog).await?, locale))
+}
+
+async fn set(name: &str, locale: Locale, options: &[ResolvedOption<'_>]) -> Result<String, String> {
+    let catalog = locale.catalog();
+    let shares = parse_shares(&get_string_option(options, "currencies").unwrap_or_default())
+        .map_err(|e| format_error(&e, catalog))?;
+    let kind = get_string_option(options, "kind")
+        .and_then(|kind| BasketKind::from_name(&kind))
+        .unwrap_or(BasketKind::Weighted);
+
+    let rates = get_latest_rates(catalog).await?;
+    let basket = Basket::new(name, kind, &shares, &rates).map_err(|e| format_error(&e, catalog))?;
+    let description = describe(&basket, &rates, locale);
+    set_basket(basket);
+    Ok(fill(catalog.basket_saved, &[("BASKET", &description)]))
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let Some((subcommand, options)) = options.first().and_then(|opt| match &opt.value {
+        ResolvedValue::SubCommand(options) => Some((opt.name, options)),
+        _ => None,
+    }) else {
+        return EditInteractionResponse::new().content(catalog.unknown_subcommand);
+    };
+    let name = get_string_option(options, "name");
+
+    // Baskets are shared by every server, changing them takes an admin
+    if subcommand != "view" {
+        let can_manage = command
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.manage_guild());
+        let admin_channels = environment::get_admin_channels();
+        if !can_manage {
+            return EditInteractionResponse::new().content(catalog.basket_permission);
+        }
+        if !admin_channels.is_empty() && !admin_channels.contains(&command.channel_id.get()) {
+            return EditInteractionResponse::new().content(catalog.basket_admin_channel);
+        }
+    }
+
+    let result = match subcommand {
+        "view" => view(name, locale).await,
+        "set" => set(&name.unwrap_or_default(), locale, options).await,
+        "delete" => {
+            let name = name.unwrap_or_default();
+            match remove_basket(&name) {
+                true => Ok(fill(
+                    catalog.basket_deleted,
+                    &[("NAME", &name.to_uppercase())],
+                )),
+                false => Err(fill(catalog.basket_unknown, &[("NAME", &name)])),
+            }
+        }
+        _ => Err(catalog.unknown_subcommand.to_string()),
+    };
+
+    let content = match result {
+        Ok(content) => content,
+        Err(e) => e,
+    };
+    EditInteractionResponse::new().content(content)
+}
@@ -0,0 +1,132 @@
+use chrono::{Duration, Utc};
+use serenity::all::{CommandInteraction, CommandOptionType, EditInteractionResponse};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+
+use crate::environment;
+use crate::i18n::{fill, Locale};
+use crate::utils::calendar::{get_events, get_pair_events};
+
+use super::{get_integer_option, get_string_option};
+
+pub const COMMAND_NAME: &str = "calendar";
+
+/// Days listed when the command does not say
+const DEFAULT_DAYS: i64 = 30;
+
+/// Most events listed, keeps the message below Discord's length limit
+const MAX_LISTED_EVENTS: usize = 20;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("List upcoming rate decisions, releases and holidays for two currencies")
+        .name_localized("de", "kalender")
+        .description_localized(
+            "de",
+            "Listet anstehende Zinsentscheide, Veröffentlichungen und Feiertage zweier Währungen",
+        )
+        .description_localized("hi", "दो मुद्राओं के आगामी ब्याज दर निर्णय, आँकड़े और छुट्टियाँ दिखाएँ")
+        .name_localized("ja", "カレンダー")
+        .description_localized(
+            "ja",
+            "二つの通貨の今後の金利決定、指標発表、祝日を一覧します",
+        )
+        .name_localized("es-ES", "calendario")
+        .description_localized(
+            "es-ES",
+            "Lista las próximas decisiones de tipos, publicaciones y festivos de dos monedas",
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "from",
+                "Currency to convert from",
+            )
+            .name_localized("de", "von")
+            .description_localized("de", "Die Ausgangswährung für die Konvertierung")
+            .description_localized("hi", "जिस मुद्रा से परिवर्तित करना है")
+            .name_localized("ja", "変換元")
+            .description_localized("ja", "変換する通貨")
+            .name_localized("es-ES", "de")
+            .description_localized("es-ES", "Moneda de origen para la conversión")
+            .required(false)
+            .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "to", "Currency to convert to")
+                .name_localized("de", "zu")
+                .description_localized("de", "Die Zielwährung für die Konvertierung")
+                .description_localized("hi", "जिस मुद्रा में परिवर्तित करना है")
+                .name_localized("ja", "変換先")
+                .description_localized("ja", "変換される通貨")
+                .name_localized("es-ES", "a")
+                .description_localized("es-ES", "Moneda de destino para la conversión")
+                .required(false)
+                .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "days",
+                "Number of days to look ahead",
+            )
+            .name_localized("de", "tage")
+            .description_localized("de", "Anzahl der Tage im Voraus")
+            .description_localized("hi", "आगे देखने के दिनों की संख्या")
+            .name_localized("ja", "日数")
+            .description_localized("ja", "先を見る日数")
+            .name_localized("es-ES", "dias")
+            .description_localized("es-ES", "Número de días hacia adelante")
+            .min_int_value(1)
+            .max_int_value(365)
+            .required(false),
+        )
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let from = get_string_option(&options, "from")
+        .unwrap_or_else(environment::get_exchange_from)
+        .to_uppercase();
+    let to = get_string_option(&options, "to")
+        .unwrap_or_else(environment::get_exchange_to)
+        .to_uppercase();
+    let days = get_integer_option(&options, "days").unwrap_or(DEFAULT_DAYS);
+
+    if environment::get_economic_calendar().is_none() {
+        return EditInteractionResponse::new().content(catalog.calendar_disabled);
+    }
+
+    let events = get_events().await;
+    let today = Utc::now().date_naive();
+    let pair_events = get_pair_events(&events, &from, &to, today, today + Duration::days(days));
+
+    let days = days.to_string();
+    let args = [
+        ("FROM", from.as_str()),
+        ("TO", to.as_str()),
+        ("DAYS", &days),
+    ];
+    if pair_events.is_empty() {
+        return EditInteractionResponse::new().content(fill(catalog.calendar_empty, &args));
+    }
+
+    let mut content = fill(catalog.calendar_title, &args);
+    for event in pair_events.iter().take(MAX_LISTED_EVENTS) {
+        content.push_str(&format!("\n- {}", locale.format_date(event.date)));
+        if let Some(time) = event.time {
+            content.push_str(&format!(" {} UTC", time.format("%H:%M")));
+        }
+        content.push_str(&format!(
+            " · {} ({})",
+            event.title,
+            event.currencies.join(", ")
+        ));
+    }
+    if pair_events.len() > MAX_LISTED_EVENTS {
+        content.push_str("\n…");
+    }
+
+    EditInteractionResponse::new().content(content)
+}
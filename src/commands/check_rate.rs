@@ -1,12 +1,16 @@
 use log::debug;
 use serenity::all::{
-    CommandOptionType, CreateAttachment, EditInteractionResponse,
+    CommandInteraction, CommandOptionType, Context, CreateAttachment, EditInteractionResponse,
 };
 use serenity::builder::{CreateAutocompleteResponse, CreateCommand, CreateCommandOption};
-use serenity::model::application::{ResolvedOption, ResolvedValue};
+use serenity::model::application::ResolvedValue;
+use tokio::sync::watch;
 
 use crate::environment::{self};
+use crate::i18n::Locale;
+use crate::utils::basket::get_baskets;
 use crate::utils::message::get_exchange_rate_message;
+use crate::utils::progress::stream_to_response;
 
 pub const COMMAND_NAME: &str = "exchange-check";
 
@@ -57,9 +61,34 @@ pub fn register() -> CreateCommand {
                 .required(false)
                 .set_autocomplete(true),
         )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "refresh",
+                "Generate a new report instead of reusing a recent one",
+            )
+            .name_localized("de", "aktualisieren")
+            .description_localized(
+                "de",
+                "Einen neuen Bericht erstellen, statt einen aktuellen wiederzuverwenden",
+            )
+            .description_localized(
+                "hi",
+                "हाल की रिपोर्ट दोबारा इस्तेमाल करने के बजाय नई रिपोर्ट बनाएँ",
+            )
+            .name_localized("ja", "再生成")
+            .description_localized("ja", "最近のレポートを再利用せずに新しく生成します")
+            .name_localized("es-ES", "regenerar")
+            .description_localized(
+                "es-ES",
+                "Genera un informe nuevo en lugar de reutilizar uno reciente",
+            )
+            .required(false),
+        )
 }
 
-pub async fn run(options: &[ResolvedOption<'_>]) -> EditInteractionResponse {
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> EditInteractionResponse {
+    let options = command.data.options();
     let from = options
         .iter()
         .find(|opt| opt.name == "from")
@@ -78,16 +107,38 @@ pub async fn run(options: &[ResolvedOption<'_>]) -> EditInteractionResponse {
         })
         .map(|s| s.to_string())
         .unwrap_or_else(|| environment::get_exchange_to());
-    debug!("from: {}, to: {}", from, to);
+    let refresh = options
+        .iter()
+        .find(|opt| opt.name == "refresh")
+        .is_some_and(|opt| matches!(opt.value, ResolvedValue::Boolean(true)));
+    debug!("from: {}, to: {}, refresh: {}", from, to, refresh);
+    let (tx, rx) = watch::channel(String::new());
+    let editor = tokio::spawn(stream_to_response(ctx.clone(), command.clone(), rx));
+
     // Generate the exchange rate message
-    let msg = get_exchange_rate_message(from.as_str(), to.as_str()).await;
+    let msg = get_exchange_rate_message(
+        from.as_str(),
+        to.as_str(),
+        command.guild_id.map(|id| id.get()),
+        Locale::from_discord(&command.locale),
+        refresh,
+        Some(tx),
+    )
+    .await;
+
+    // The sender is gone at this point, wait for the last progressive edit so
+    // it cannot overwrite the final response.
+    if let Err(why) = editor.await {
+        log::warn!("Progressive response task failed: {why}");
+    }
 
     // Make `response` mutable to allow modifications
     let mut response = EditInteractionResponse::new().content(msg.message);
 
     // Conditionally add the attachment
     if let Some(graph) = msg.graph {
-        response = response.new_attachment(CreateAttachment::bytes(graph, "graph.png"));
+        let name = environment::get_chart_format().file_name("graph");
+        response = response.new_attachment(CreateAttachment::bytes(graph, name));
     }
 
     response
@@ -95,12 +146,21 @@ pub async fn run(options: &[ResolvedOption<'_>]) -> EditInteractionResponse {
 
 pub fn autocomplete(input: &str) -> CreateAutocompleteResponse {
     let mut response = CreateAutocompleteResponse::new();
-    let filtered_currencies = CURRENCIES
+    let input = input.to_uppercase();
+    // Baskets first, there are few of them
+    let baskets: Vec<String> = get_baskets()
+        .into_iter()
+        .map(|basket| basket.name)
+        .filter(|name| name.starts_with(&input))
+        .collect();
+    let filtered_currencies = baskets
         .iter()
-        .filter(|&&currency| currency.starts_with(&input.to_uppercase()))
+        .map(String::as_str)
+        .chain(CURRENCIES.iter().copied())
+        .filter(|currency| currency.starts_with(&input))
         .take(25); // Limit results to 25
 
-    for &currency in filtered_currencies {
+    for currency in filtered_currencies {
         response = response.add_string_choice(currency, currency);
     }
     response
@@ -0,0 +1,198 @@
+use chrono::{Duration, Utc};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateAttachment, EditInteractionResponse,
+};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+
+use crate::environment;
+use crate::exchange_rate::ExchangeRateMap;
+use crate::i18n::{fill, Locale};
+use crate::utils::plots::{get_comparison_graph, ImageFormat};
+use crate::utils::theme::get_guild_theme;
+
+use super::get_string_option;
+use super::history::format_option;
+
+pub const COMMAND_NAME: &str = "compare";
+
+/// Range charted when the command does not say
+const DEFAULT_RANGE: &str = "90d";
+
+/// More lines than this are hard to tell apart
+const MAX_QUOTES: usize = 8;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Compare how several currencies moved against a base currency")
+        .name_localized("de", "vergleich")
+        .description_localized(
+            "de",
+            "Vergleicht, wie sich mehrere Währungen gegenüber einer Basiswährung bewegt haben",
+        )
+        .description_localized("hi", "तुलना करें कि कई मुद्राएँ एक आधार मुद्रा के मुकाबले कैसे बदलीं")
+        .name_localized("ja", "比較")
+        .description_localized("ja", "複数の通貨が基準通貨に対してどう動いたかを比較します")
+        .name_localized("es-ES", "comparar")
+        .description_localized(
+            "es-ES",
+            "Compara cómo se movieron varias monedas frente a una moneda base",
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "quotes",
+                "Comma separated currencies, e.g. EUR,GBP,JPY",
+            )
+            .name_localized("de", "währungen")
+            .description_localized("de", "Durch Kommas getrennte Währungen, z. B. EUR,GBP,JPY")
+            .description_localized("hi", "अल्पविराम से अलग मुद्राएँ, जैसे EUR,GBP,JPY")
+            .name_localized("ja", "通貨")
+            .description_localized("ja", "カンマ区切りの通貨、例: EUR,GBP,JPY")
+            .name_localized("es-ES", "monedas")
+            .description_localized("es-ES", "Monedas separadas por comas, p. ej. EUR,GBP,JPY")
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "base",
+                "Currency the others are valued in",
+            )
+            .name_localized("de", "basis")
+            .description_localized("de", "Währung, in der die anderen bewertet werden")
+            .description_localized("hi", "वह मुद्रा जिसमें बाकी का मूल्य आँका जाता है")
+            .name_localized("ja", "基準通貨")
+            .description_localized("ja", "他の通貨を評価する通貨")
+            .name_localized("es-ES", "base")
+            .description_localized("es-ES", "Moneda en la que se valoran las demás")
+            .required(false)
+            .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "range",
+                "Days, weeks, months or years to chart, e.g. 90d, 12w, 6m or 1y",
+            )
+            .name_localized("de", "zeitraum")
+            .description_localized(
+                "de",
+                "Tage, Wochen, Monate oder Jahre im Diagramm, z. B. 90d, 12w, 6m oder 1y",
+            )
+            .description_localized(
+                "hi",
+                "चार्ट के दिन, सप्ताह, महीने या साल, जैसे 90d, 12w, 6m या 1y",
+            )
+            .name_localized("ja", "期間")
+            .description_localized("ja", "グラフにする日・週・月・年、例: 90d, 12w, 6m, 1y")
+            .name_localized("es-ES", "periodo")
+            .description_localized(
+                "es-ES",
+                "Días, semanas, meses o años a graficar, p. ej. 90d, 12w, 6m o 1y",
+            )
+            .required(false),
+        )
+        .add_option(format_option())
+}
+
+/// Days of a range such as `90d`, `12w`, `6m` or `1y`, a bare number being days
+fn parse_range(range: &str) -> Option<i64> {
+    let range = range.trim().to_lowercase();
+    let (count, unit) = match range.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => range.split_at(i),
+        None => (range.as_str(), "d"),
+    };
+    let days_per_unit = match unit.trim() {
+        "d" | "day" | "days" => 1,
+        "w" | "week" | "weeks" => 7,
+        "m" | "month" | "months" => 30,
+        "y" | "year" | "years" => 365,
+        _ => return None,
+    };
+    count
+        .parse::<i64>()
+        .ok()
+        .filter(|count| *count > 0)
+        .map(|count| count * days_per_unit)
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let base = get_string_option(&options, "base")
+        .unwrap_or_else(environment::get_exchange_from)
+        .to_uppercase();
+    let mut quotes: Vec<String> = vec![];
+    for quote in get_string_option(&options, "quotes")
+        .unwrap_or_default()
+        .split([',', ' '])
+        .map(|quote| quote.trim().to_uppercase())
+        .filter(|quote| !quote.is_empty() && *quote != base)
+    {
+        if !quotes.contains(&quote) {
+            quotes.push(quote);
+        }
+    }
+    quotes.truncate(MAX_QUOTES);
+
+    let format = get_string_option(&options, "format")
+        .and_then(|name| ImageFormat::from_name(&name))
+        .unwrap_or_else(environment::get_chart_format);
+
+    let range = get_string_option(&options, "range").unwrap_or(DEFAULT_RANGE.to_string());
+    let Some(days) = parse_range(&range) else {
+        return EditInteractionResponse::new()
+            .content(fill(catalog.compare_invalid_range, &[("RANGE", &range)]));
+    };
+
+    let since = (Utc::now() - Duration::days(days)).date_naive();
+    let rates = match ExchangeRateMap::get_rates(since, Some(base.clone())).await {
+        Ok(rates) => rates,
+        Err(e) => {
+            return EditInteractionResponse::new().content(fill(
+                catalog.api_error,
+                &[
+                    ("URL", &environment::get_exchange_rate_api_url()),
+                    ("ERROR", &format!("{:?}", e)),
+                ],
+            ))
+        }
+    };
+
+    let missing: Vec<&str> = quotes
+        .iter()
+        .filter(|quote| {
+            !rates
+                .iter()
+                .any(|rate| rate.get_val(quote, &base).is_some())
+        })
+        .map(String::as_str)
+        .collect();
+
+    let theme = get_guild_theme(command.guild_id.map(|id| id.get()));
+    match get_comparison_graph(&rates, &base, &quotes, since, &theme, locale, format) {
+        Ok(graph) => {
+            let mut content = fill(
+                catalog.compare_title,
+                &[
+                    ("QUOTES", &quotes.join(", ")),
+                    ("BASE", &base),
+                    ("DAYS", &days.to_string()),
+                ],
+            );
+            if !missing.is_empty() {
+                content.push('\n');
+                content.push_str(&fill(
+                    catalog.compare_missing,
+                    &[("CURRENCIES", &missing.join(", "))],
+                ));
+            }
+            EditInteractionResponse::new()
+                .content(content)
+                .new_attachment(CreateAttachment::bytes(graph, format.file_name("compare")))
+        }
+        Err(e) => EditInteractionResponse::new()
+            .content(fill(catalog.graph_error, &[("ERROR", &e.to_string())])),
+    }
+}
@@ -0,0 +1,261 @@
+use chrono::{Duration, Utc};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateAttachment, EditInteractionResponse,
+};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+
+use crate::environment;
+use crate::exchange_rate::ExchangeRateMap;
+use crate::i18n::{fill, Locale};
+use crate::utils::forecast::{
+    get_backtest, get_forecast, get_working_days, ForecastModel, MIN_HISTORY,
+};
+use crate::utils::plots::{get_forecast_graph, ImageFormat};
+use crate::utils::stats::{get_series, get_since};
+use crate::utils::theme::get_guild_theme;
+
+use super::history::format_option;
+use super::{get_integer_option, get_string_option};
+
+pub const COMMAND_NAME: &str = "forecast";
+
+/// Days forecast when the command does not say
+const DEFAULT_DAYS: i64 = 14;
+
+/// Days of rates the models learn from when the command does not say
+const DEFAULT_HISTORY_DAYS: i64 = 365;
+
+/// Days of rates charted before the forecast
+const CHARTED_DAYS: i64 = 90;
+
+pub fn register() -> CreateCommand {
+    let mut model = CreateCommandOption::new(
+        CommandOptionType::String,
+        "model",
+        "Model to chart, the one with the smallest backtest error when left out",
+    )
+    .name_localized("de", "modell")
+    .description_localized(
+        "de",
+        "Darzustellendes Modell, ohne Angabe das mit dem kleinsten Backtest-Fehler",
+    )
+    .description_localized(
+        "hi",
+        "चार्ट किया जाने वाला मॉडल, न देने पर सबसे कम बैकटेस्ट त्रुटि वाला",
+    )
+    .name_localized("ja", "モデル")
+    .description_localized(
+        "ja",
+        "グラフにするモデル、省略時はバックテスト誤差が最小のもの",
+    )
+    .name_localized("es-ES", "modelo")
+    .description_localized(
+        "es-ES",
+        "Modelo a graficar, el de menor error de backtest si se omite",
+    )
+    .required(false);
+    for name in ForecastModel::NAMES {
+        model = model.add_string_choice(*name, *name);
+    }
+
+    CreateCommand::new(COMMAND_NAME)
+        .description("Indicative forecast of the exchange rate of two currencies")
+        .name_localized("de", "prognose")
+        .description_localized(
+            "de",
+            "Unverbindliche Prognose des Wechselkurses zweier Währungen",
+        )
+        .description_localized("hi", "दो मुद्राओं की विनिमय दर का सांकेतिक पूर्वानुमान")
+        .name_localized("ja", "予測")
+        .description_localized("ja", "二つの通貨の為替レートの参考予測")
+        .name_localized("es-ES", "pronostico")
+        .description_localized(
+            "es-ES",
+            "Pronóstico orientativo del tipo de cambio entre dos monedas",
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "from",
+                "Currency to convert from",
+            )
+            .name_localized("de", "von")
+            .description_localized("de", "Die Ausgangswährung für die Konvertierung")
+            .description_localized("hi", "जिस मुद्रा से परिवर्तित करना है")
+            .name_localized("ja", "変換元")
+            .description_localized("ja", "変換する通貨")
+            .name_localized("es-ES", "de")
+            .description_localized("es-ES", "Moneda de origen para la conversión")
+            .required(false)
+            .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "to", "Currency to convert to")
+                .name_localized("de", "zu")
+                .description_localized("de", "Die Zielwährung für die Konvertierung")
+                .description_localized("hi", "जिस मुद्रा में परिवर्तित करना है")
+                .name_localized("ja", "変換先")
+                .description_localized("ja", "変換される通貨")
+                .name_localized("es-ES", "a")
+                .description_localized("es-ES", "Moneda de destino para la conversión")
+                .required(false)
+                .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "days", "Days to forecast")
+                .name_localized("de", "tage")
+                .description_localized("de", "Zu prognostizierende Tage")
+                .description_localized("hi", "पूर्वानुमान के दिन")
+                .name_localized("ja", "日数")
+                .description_localized("ja", "予測する日数")
+                .name_localized("es-ES", "dias")
+                .description_localized("es-ES", "Días a pronosticar")
+                .min_int_value(7)
+                .max_int_value(30)
+                .required(false),
+        )
+        .add_option(model)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "history",
+                "Days of rates the models learn from",
+            )
+            .name_localized("de", "verlauf")
+            .description_localized("de", "Tage mit Kursen, aus denen die Modelle lernen")
+            .description_localized("hi", "दरों के वे दिन जिनसे मॉडल सीखते हैं")
+            .name_localized("ja", "学習期間")
+            .description_localized("ja", "モデルが学習するレートの日数")
+            .name_localized("es-ES", "historial")
+            .description_localized(
+                "es-ES",
+                "Días de tipos de cambio de los que aprenden los modelos",
+            )
+            .min_int_value(30)
+            .max_int_value(3650)
+            .required(false),
+        )
+        .add_option(format_option())
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let from = get_string_option(&options, "from")
+        .unwrap_or_else(environment::get_exchange_from)
+        .to_uppercase();
+    let to = get_string_option(&options, "to")
+        .unwrap_or_else(environment::get_exchange_to)
+        .to_uppercase();
+    let days = get_integer_option(&options, "days").unwrap_or(DEFAULT_DAYS);
+    let history_days = get_integer_option(&options, "history").unwrap_or(DEFAULT_HISTORY_DAYS);
+    let format = get_string_option(&options, "format")
+        .and_then(|name| ImageFormat::from_name(&name))
+        .unwrap_or_else(environment::get_chart_format);
+
+    let since = (Utc::now() - Duration::days(history_days)).date_naive();
+    let rates = match ExchangeRateMap::get_rates(since, Some(from.clone())).await {
+        Ok(rates) => rates,
+        Err(e) => {
+            return EditInteractionResponse::new().content(fill(
+                catalog.api_error,
+                &[
+                    ("URL", &environment::get_exchange_rate_api_url()),
+                    ("ERROR", &format!("{:?}", e)),
+                ],
+            ))
+        }
+    };
+
+    let series = get_series(&rates, &from, &to);
+    let too_few = || {
+        EditInteractionResponse::new().content(fill(
+            catalog.forecast_too_few,
+            &[
+                ("FROM", &from),
+                ("TO", &to),
+                ("COUNT", &MIN_HISTORY.to_string()),
+            ],
+        ))
+    };
+    let Some((last_date, _)) = series.last().copied() else {
+        return too_few();
+    };
+
+    // Every model is backtested over as many rates as the forecast has days
+    let steps = get_working_days(last_date, days).len();
+    let backtests: Vec<_> = ForecastModel::ALL
+        .into_iter()
+        .filter_map(|model| Some((model, get_backtest(model, &series, steps)?)))
+        .collect();
+    let model = get_string_option(&options, "model")
+        .and_then(|name| ForecastModel::from_name(&name))
+        .or(backtests
+            .iter()
+            .min_by(|a, b| a.1.error.total_cmp(&b.1.error))
+            .map(|(model, _)| *model))
+        .unwrap_or(ForecastModel::EwmaDrift);
+
+    let Some(forecast) = get_forecast(model, &series, days) else {
+        return too_few();
+    };
+    let Some(end) = forecast.last() else {
+        return too_few();
+    };
+
+    let number = |value: f64| locale.format_number(value, 4);
+    let mut lines = vec![fill(
+        catalog.forecast_title,
+        &[
+            ("MODEL", model.label()),
+            ("FROM", &from),
+            ("TO", &to),
+            ("DATE", &locale.format_date(end.date)),
+            ("VALUE", &number(end.value)),
+            ("LOW", &number(end.low)),
+            ("HIGH", &number(end.high)),
+        ],
+    )];
+    match backtests.first() {
+        Some((_, backtest)) => {
+            lines.push(fill(
+                catalog.forecast_backtest,
+                &[
+                    ("COUNT", &backtest.forecasts.to_string()),
+                    ("DAYS", &steps.to_string()),
+                ],
+            ));
+            for (model, backtest) in &backtests {
+                lines.push(fill(
+                    catalog.forecast_backtest_model,
+                    &[
+                        ("MODEL", model.label()),
+                        ("ERROR", &locale.format_number(backtest.error, 2)),
+                        ("COVERAGE", &locale.format_number(backtest.coverage, 0)),
+                    ],
+                ));
+            }
+        }
+        None => lines.push(catalog.forecast_no_backtest.to_string()),
+    }
+    lines.push(catalog.forecast_disclaimer.to_string());
+
+    let charted = get_since(&series, last_date - Duration::days(CHARTED_DAYS));
+    let theme = get_guild_theme(command.guild_id.map(|id| id.get()));
+    match get_forecast_graph(
+        charted,
+        &forecast,
+        model,
+        (&from, &to),
+        &theme,
+        locale,
+        format,
+    ) {
+        Ok(graph) => EditInteractionResponse::new()
+            .content(lines.join("\n"))
+            .new_attachment(CreateAttachment::bytes(graph, format.file_name("forecast"))),
+        Err(e) => EditInteractionResponse::new()
+            .content(fill(catalog.graph_error, &[("ERROR", &e.to_string())])),
+    }
+}
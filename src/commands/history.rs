@@ -0,0 +1,232 @@
+use chrono::{Duration, Utc};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateAttachment, EditInteractionResponse,
+};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+
+use crate::environment;
+use crate::exchange_rate::ExchangeRateMap;
+use crate::i18n::{fill, Locale};
+use crate::utils::plots::{get_chart, ChartMode, ChartOptions, ImageFormat, Overlays};
+use crate::utils::theme::get_guild_theme;
+
+use super::{get_integer_option, get_string_option};
+
+pub const COMMAND_NAME: &str = "history";
+
+/// Days charted when the command does not say
+const DEFAULT_DAYS: i64 = 90;
+
+/// Option choosing the file format of the chart
+pub fn format_option() -> CreateCommandOption {
+    let mut option = CreateCommandOption::new(
+        CommandOptionType::String,
+        "format",
+        "File format of the chart, SVG is not shown by Discord",
+    )
+    .name_localized("de", "format")
+    .description_localized(
+        "de",
+        "Dateiformat des Diagramms, SVG zeigt Discord nicht an",
+    )
+    .description_localized("hi", "चार्ट का फ़ाइल फ़ॉर्मेट, Discord SVG नहीं दिखाता")
+    .name_localized("ja", "形式")
+    .description_localized(
+        "ja",
+        "グラフのファイル形式、SVG は Discord に表示されません",
+    )
+    .name_localized("es-ES", "formato")
+    .description_localized(
+        "es-ES",
+        "Formato de archivo del gráfico, Discord no muestra SVG",
+    )
+    .required(false);
+    for name in ImageFormat::NAMES {
+        option = option.add_string_choice(*name, *name);
+    }
+    option
+}
+
+pub fn register() -> CreateCommand {
+    let mut chart = CreateCommandOption::new(
+        CommandOptionType::String,
+        "chart",
+        "Line chart or daily, weekly or monthly candlesticks",
+    )
+    .name_localized("de", "diagramm")
+    .description_localized(
+        "de",
+        "Liniendiagramm oder tägliche, wöchentliche oder monatliche Kerzen",
+    )
+    .description_localized("hi", "लाइन चार्ट या दैनिक, साप्ताहिक या मासिक कैंडलस्टिक")
+    .name_localized("ja", "グラフ")
+    .description_localized("ja", "折れ線グラフ、または日足・週足・月足のローソク足")
+    .name_localized("es-ES", "grafico")
+    .description_localized(
+        "es-ES",
+        "Gráfico de líneas o velas diarias, semanales o mensuales",
+    )
+    .required(false);
+    for name in ChartMode::NAMES {
+        chart = chart.add_string_choice(*name, *name);
+    }
+
+    CreateCommand::new(COMMAND_NAME)
+        .description("Chart the exchange rate history of two currencies")
+        .name_localized("de", "verlauf")
+        .description_localized(
+            "de",
+            "Zeichnet den Verlauf des Wechselkurses zweier Währungen",
+        )
+        .description_localized("hi", "दो मुद्राओं की विनिमय दर के इतिहास का चार्ट बनाएँ")
+        .name_localized("ja", "履歴")
+        .description_localized("ja", "二つの通貨の為替レートの推移をグラフにします")
+        .name_localized("es-ES", "historial")
+        .description_localized(
+            "es-ES",
+            "Grafica el historial del tipo de cambio entre dos monedas",
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "from",
+                "Currency to convert from",
+            )
+            .name_localized("de", "von")
+            .description_localized("de", "Die Ausgangswährung für die Konvertierung")
+            .description_localized("hi", "जिस मुद्रा से परिवर्तित करना है")
+            .name_localized("ja", "変換元")
+            .description_localized("ja", "変換する通貨")
+            .name_localized("es-ES", "de")
+            .description_localized("es-ES", "Moneda de origen para la conversión")
+            .required(false)
+            .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "to", "Currency to convert to")
+                .name_localized("de", "zu")
+                .description_localized("de", "Die Zielwährung für die Konvertierung")
+                .description_localized("hi", "जिस मुद्रा में परिवर्तित करना है")
+                .name_localized("ja", "変換先")
+                .description_localized("ja", "変換される通貨")
+                .name_localized("es-ES", "a")
+                .description_localized("es-ES", "Moneda de destino para la conversión")
+                .required(false)
+                .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "days",
+                "Number of days to chart",
+            )
+            .name_localized("de", "tage")
+            .description_localized("de", "Anzahl der Tage im Diagramm")
+            .description_localized("hi", "चार्ट में दिखाए जाने वाले दिनों की संख्या")
+            .name_localized("ja", "日数")
+            .description_localized("ja", "グラフにする日数")
+            .name_localized("es-ES", "dias")
+            .description_localized("es-ES", "Número de días a graficar")
+            .min_int_value(1)
+            .max_int_value(3650)
+            .required(false),
+        )
+        .add_option(chart)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "overlays",
+                "Overlays of line charts, e.g. sma20,ema50,bollinger,markers",
+            )
+            .name_localized("de", "indikatoren")
+            .description_localized(
+                "de",
+                "Overlays der Liniendiagramme, z. B. sma20,ema50,bollinger,markers",
+            )
+            .description_localized("hi", "लाइन चार्ट के ओवरले, जैसे sma20,ema50,bollinger,markers")
+            .name_localized("ja", "オーバーレイ")
+            .description_localized(
+                "ja",
+                "折れ線グラフのオーバーレイ、例: sma20,ema50,bollinger,markers",
+            )
+            .name_localized("es-ES", "superposiciones")
+            .description_localized(
+                "es-ES",
+                "Superposiciones de los gráficos de líneas, p. ej. sma20,ema50,bollinger,markers",
+            )
+            .required(false),
+        )
+        .add_option(format_option())
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let from = get_string_option(&options, "from")
+        .unwrap_or_else(environment::get_exchange_from)
+        .to_uppercase();
+    let to = get_string_option(&options, "to")
+        .unwrap_or_else(environment::get_exchange_to)
+        .to_uppercase();
+    let mode = get_string_option(&options, "chart")
+        .and_then(|name| ChartMode::from_name(&name))
+        .unwrap_or_else(environment::get_chart_mode);
+    let format = get_string_option(&options, "format")
+        .and_then(|name| ImageFormat::from_name(&name))
+        .unwrap_or_else(environment::get_chart_format);
+    let days = get_integer_option(&options, "days").unwrap_or(DEFAULT_DAYS);
+
+    let overlays = match get_string_option(&options, "overlays") {
+        Some(list) => match Overlays::parse(&list) {
+            Ok(overlays) => overlays,
+            Err(e) => {
+                return EditInteractionResponse::new()
+                    .content(fill(catalog.graph_error, &[("ERROR", &e)]))
+            }
+        },
+        None => environment::get_chart_overlays(),
+    };
+
+    let since = (Utc::now() - Duration::days(days)).date_naive();
+    // Moving averages need the rates before the chart starts
+    let longest_period = overlays
+        .sma
+        .iter()
+        .chain(&overlays.ema)
+        .chain(&overlays.bollinger)
+        .max()
+        .copied()
+        .unwrap_or_default();
+    let rates_since = since - Duration::days(longest_period as i64 * 7 / 5 + 7);
+    let rates = match ExchangeRateMap::get_rates(rates_since, Some(from.clone())).await {
+        Ok(rates) => rates,
+        Err(e) => {
+            return EditInteractionResponse::new().content(fill(
+                catalog.api_error,
+                &[
+                    ("URL", &environment::get_exchange_rate_api_url()),
+                    ("ERROR", &format!("{:?}", e)),
+                ],
+            ))
+        }
+    };
+
+    let chart_options = ChartOptions {
+        mode,
+        overlays,
+        theme: get_guild_theme(command.guild_id.map(|id| id.get())),
+        format,
+        locale,
+    };
+    match get_chart(&rates, &from, &to, since, &chart_options) {
+        Ok(graph) => EditInteractionResponse::new()
+            .content(fill(
+                catalog.history_title,
+                &[("FROM", &from), ("TO", &to), ("DAYS", &days.to_string())],
+            ))
+            .new_attachment(CreateAttachment::bytes(graph, format.file_name("graph"))),
+        Err(e) => EditInteractionResponse::new()
+            .content(fill(catalog.graph_error, &[("ERROR", &e.to_string())])),
+    }
+}
@@ -0,0 +1,193 @@
+use chrono::{Duration, Utc};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateAttachment, EditInteractionResponse,
+};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+
+use crate::environment;
+use crate::exchange_rate::{ExchangeRateMap, FetchExchangeRateError};
+use crate::i18n::{fill, Locale};
+use crate::utils::plots::{get_matrix_graph, ImageFormat};
+use crate::utils::theme::get_guild_theme;
+
+use super::get_string_option;
+use super::history::format_option;
+
+pub const COMMAND_NAME: &str = "matrix";
+
+/// More currencies than this make the cells too small to read
+const MAX_CURRENCIES: usize = 10;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Table of the cross rates of several currencies and their change")
+        .name_localized("de", "matrix")
+        .description_localized(
+            "de",
+            "Tabelle der Kreuzkurse mehrerer Währungen und ihrer Änderung",
+        )
+        .description_localized("hi", "कई मुद्राओं की क्रॉस दरों और उनके बदलाव की तालिका")
+        .name_localized("ja", "クロスレート")
+        .description_localized("ja", "複数の通貨のクロスレートとその変化の表")
+        .name_localized("es-ES", "matriz")
+        .description_localized(
+            "es-ES",
+            "Tabla de los tipos cruzados de varias monedas y su variación",
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "currencies",
+                "Comma separated currencies, e.g. USD,EUR,GBP,JPY,CAD",
+            )
+            .name_localized("de", "währungen")
+            .description_localized(
+                "de",
+                "Durch Kommas getrennte Währungen, z. B. USD,EUR,GBP,JPY,CAD",
+            )
+            .description_localized("hi", "अल्पविराम से अलग मुद्राएँ, जैसे USD,EUR,GBP,JPY,CAD")
+            .name_localized("ja", "通貨")
+            .description_localized("ja", "カンマ区切りの通貨、例: USD,EUR,GBP,JPY,CAD")
+            .name_localized("es-ES", "monedas")
+            .description_localized(
+                "es-ES",
+                "Monedas separadas por comas, p. ej. USD,EUR,GBP,JPY,CAD",
+            )
+            .required(true),
+        )
+        .add_option(format_option())
+}
+
+/// Comma or space separated currencies, without duplicates
+pub fn parse_currencies(list: &str) -> Vec<String> {
+    let mut currencies: Vec<String> = vec![];
+    for currency in list
+        .split([',', ' '])
+        .map(|currency| currency.trim().to_uppercase())
+        .filter(|currency| !currency.is_empty())
+    {
+        if !currencies.contains(&currency) {
+            currencies.push(currency);
+        }
+    }
+    currencies.truncate(MAX_CURRENCIES);
+    currencies
+}
+
+/// Latest rates of `base` and the rates of the working day before
+async fn get_latest_rates(
+    base: &str,
+) -> Result<Option<(ExchangeRateMap, Option<ExchangeRateMap>)>, FetchExchangeRateError> {
+    // A week always holds two working days, even around holidays
+    let since = (Utc::now() - Duration::days(7)).date_naive();
+    let mut rates = ExchangeRateMap::get_rates(since, Some(base.to_string())).await?;
+    let Some(latest) = rates.pop() else {
+        return Ok(None);
+    };
+    let previous = rates
+        .into_iter()
+        .rev()
+        .find(|rates| rates.get_date() < latest.get_date());
+    Ok(Some((latest, previous)))
+}
+
+/// Matrix of `MATRIX_CURRENCIES` for the scheduled report of `guild_id` in `locale`,
+/// `None` when it is not set or fails
+pub async fn get_report_matrix(guild_id: Option<u64>, locale: Locale) -> Option<Vec<u8>> {
+    let currencies = parse_currencies(&environment::get_matrix_currencies());
+    let base = currencies.first()?;
+    let (latest, previous) = match get_latest_rates(base).await {
+        Ok(rates) => rates?,
+        Err(e) => {
+            log::warn!("Cannot get the rates of the matrix: {:?}", e);
+            return None;
+        }
+    };
+    get_matrix_graph(
+        &latest,
+        previous.as_ref(),
+        &currencies,
+        &get_guild_theme(guild_id),
+        locale,
+        environment::get_chart_format(),
+    )
+    .inspect_err(|e| log::warn!("Cannot draw the matrix: {}", e))
+    .ok()
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let currencies =
+        parse_currencies(&get_string_option(&options, "currencies").unwrap_or_default());
+    let format = get_string_option(&options, "format")
+        .and_then(|name| ImageFormat::from_name(&name))
+        .unwrap_or_else(environment::get_chart_format);
+
+    if currencies.len() < 2 {
+        return EditInteractionResponse::new().content(catalog.matrix_too_few);
+    }
+
+    // Cross rates of any base are the same, the first currency saves a conversion
+    let (latest, previous) = match get_latest_rates(&currencies[0]).await {
+        Ok(Some(rates)) => rates,
+        Ok(None) => {
+            return EditInteractionResponse::new().content(fill(
+                catalog.matrix_missing,
+                &[("CURRENCIES", &currencies.join(", "))],
+            ))
+        }
+        Err(e) => {
+            return EditInteractionResponse::new().content(fill(
+                catalog.api_error,
+                &[
+                    ("URL", &environment::get_exchange_rate_api_url()),
+                    ("ERROR", &format!("{:?}", e)),
+                ],
+            ))
+        }
+    };
+
+    let missing: Vec<&str> = currencies
+        .iter()
+        .filter(|currency| latest.get_val(currency, currency).is_none())
+        .map(String::as_str)
+        .collect();
+
+    let theme = get_guild_theme(command.guild_id.map(|id| id.get()));
+    match get_matrix_graph(
+        &latest,
+        previous.as_ref(),
+        &currencies,
+        &theme,
+        locale,
+        format,
+    ) {
+        Ok(graph) => {
+            let date = locale.format_date(latest.get_date());
+            let mut content = match &previous {
+                Some(previous) => fill(
+                    catalog.matrix_title,
+                    &[
+                        ("DATE", &date),
+                        ("PREVIOUS", &locale.format_date(previous.get_date())),
+                    ],
+                ),
+                None => fill(catalog.matrix_title_no_change, &[("DATE", &date)]),
+            };
+            if !missing.is_empty() {
+                content.push('\n');
+                content.push_str(&fill(
+                    catalog.matrix_missing,
+                    &[("CURRENCIES", &missing.join(", "))],
+                ));
+            }
+            EditInteractionResponse::new()
+                .content(content)
+                .new_attachment(CreateAttachment::bytes(graph, format.file_name("matrix")))
+        }
+        Err(e) => EditInteractionResponse::new()
+            .content(fill(catalog.graph_error, &[("ERROR", &e.to_string())])),
+    }
+}
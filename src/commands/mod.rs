@@ -1,2 +1,36 @@
 pub mod about;
+pub mod ask;
+pub mod basket;
+pub mod calendar;
 pub mod check_rate;
+pub mod compare;
+pub mod forecast;
+pub mod history;
+pub mod matrix;
+pub mod stats;
+pub mod template;
+pub mod theme;
+
+use serenity::model::application::{ResolvedOption, ResolvedValue};
+
+/// Value of the string option `name`
+pub fn get_string_option(options: &[ResolvedOption], name: &str) -> Option<String> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| match &opt.value {
+            ResolvedValue::String(s) => Some(s.to_string()),
+            _ => None,
+        })
+}
+
+/// Value of the integer option `name`
+pub fn get_integer_option(options: &[ResolvedOption], name: &str) -> Option<i64> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| match &opt.value {
+            ResolvedValue::Integer(i) => Some(*i),
+            _ => None,
+        })
+}
@@ -0,0 +1,229 @@
+use chrono::{Duration, Utc};
+use serenity::all::{CommandInteraction, CommandOptionType, EditInteractionResponse};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+
+use crate::environment;
+use crate::exchange_rate::ExchangeRateMap;
+use crate::i18n::{fill, Locale};
+use crate::utils::stats::{get_pair_correlation, get_rolling_correlation, get_series, get_stats};
+
+use super::{get_integer_option, get_string_option};
+
+pub const COMMAND_NAME: &str = "stats";
+
+/// Days looked at when the command does not say
+const DEFAULT_DAYS: i64 = 90;
+
+/// Daily changes of the recent correlation
+const CORRELATION_WINDOW: usize = 20;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Statistics of the exchange rate of two currencies")
+        .name_localized("de", "statistik")
+        .description_localized("de", "Statistiken zum Wechselkurs zweier Währungen")
+        .description_localized("hi", "दो मुद्राओं की विनिमय दर के आँकड़े")
+        .name_localized("ja", "統計")
+        .description_localized("ja", "二つの通貨の為替レートの統計")
+        .name_localized("es-ES", "estadisticas")
+        .description_localized("es-ES", "Estadísticas del tipo de cambio entre dos monedas")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "from",
+                "Currency to convert from",
+            )
+            .name_localized("de", "von")
+            .description_localized("de", "Die Ausgangswährung für die Konvertierung")
+            .description_localized("hi", "जिस मुद्रा से परिवर्तित करना है")
+            .name_localized("ja", "変換元")
+            .description_localized("ja", "変換する通貨")
+            .name_localized("es-ES", "de")
+            .description_localized("es-ES", "Moneda de origen para la conversión")
+            .required(false)
+            .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "to", "Currency to convert to")
+                .name_localized("de", "zu")
+                .description_localized("de", "Die Zielwährung für die Konvertierung")
+                .description_localized("hi", "जिस मुद्रा में परिवर्तित करना है")
+                .name_localized("ja", "変換先")
+                .description_localized("ja", "変換される通貨")
+                .name_localized("es-ES", "a")
+                .description_localized("es-ES", "Moneda de destino para la conversión")
+                .required(false)
+                .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "days",
+                "Number of days to look at",
+            )
+            .name_localized("de", "tage")
+            .description_localized("de", "Anzahl der betrachteten Tage")
+            .description_localized("hi", "देखे जाने वाले दिनों की संख्या")
+            .name_localized("ja", "日数")
+            .description_localized("ja", "対象とする日数")
+            .name_localized("es-ES", "dias")
+            .description_localized("es-ES", "Número de días a analizar")
+            .min_int_value(2)
+            .max_int_value(3650)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "versus",
+                "Currency whose rate against the first currency the changes are correlated with",
+            )
+            .name_localized("de", "gegen")
+            .description_localized(
+                "de",
+                "Währung, deren Kurs zur ersten Währung mit den Änderungen korreliert wird",
+            )
+            .description_localized(
+                "hi",
+                "वह मुद्रा जिसकी पहली मुद्रा के मुकाबले दर से बदलावों का सहसंबंध निकाला जाता है",
+            )
+            .name_localized("ja", "比較通貨")
+            .description_localized("ja", "変動の相関をとる、最初の通貨に対するレートの通貨")
+            .name_localized("es-ES", "contra")
+            .description_localized(
+                "es-ES",
+                "Moneda cuyo tipo frente a la primera moneda se correlaciona con los cambios",
+            )
+            .required(false)
+            .set_autocomplete(true),
+        )
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let options = command.data.options();
+    let from = get_string_option(&options, "from")
+        .unwrap_or_else(environment::get_exchange_from)
+        .to_uppercase();
+    let to = get_string_option(&options, "to")
+        .unwrap_or_else(environment::get_exchange_to)
+        .to_uppercase();
+    let versus = get_string_option(&options, "versus").map(|s| s.to_uppercase());
+    let days = get_integer_option(&options, "days").unwrap_or(DEFAULT_DAYS);
+
+    let since = (Utc::now() - Duration::days(days)).date_naive();
+    let rates = match ExchangeRateMap::get_rates(since, Some(from.clone())).await {
+        Ok(rates) => rates,
+        Err(e) => {
+            return EditInteractionResponse::new().content(fill(
+                catalog.api_error,
+                &[
+                    ("URL", &environment::get_exchange_rate_api_url()),
+                    ("ERROR", &format!("{:?}", e)),
+                ],
+            ))
+        }
+    };
+
+    let series = get_series(&rates, &from, &to);
+    let threshold = environment::get_exchange_rate_change_threshold();
+    let Some(stats) = get_stats(&series, threshold) else {
+        return EditInteractionResponse::new().content(fill(
+            catalog.no_stored_rates,
+            &[("FROM", &from), ("TO", &to)],
+        ));
+    };
+
+    let number = |value: f64| locale.format_number(value, 4);
+    let percent = |value: f64| locale.format_number(value, 2);
+    let mut lines = vec![
+        fill(
+            catalog.stats_title,
+            &[
+                ("FROM", &from),
+                ("TO", &to),
+                ("START", &locale.format_date(stats.first.0)),
+                ("END", &locale.format_date(stats.last.0)),
+            ],
+        ),
+        fill(
+            catalog.stats_range,
+            &[
+                ("LATEST", &number(stats.last.1)),
+                ("CHANGE", &locale.format_signed_number(stats.change, 2)),
+                ("HIGH", &number(stats.high)),
+                ("LOW", &number(stats.low)),
+                ("AVERAGE", &number(stats.average)),
+            ],
+        ),
+        fill(
+            catalog.stats_volatility,
+            &[
+                ("DAILY", &percent(stats.volatility)),
+                ("ANNUAL", &percent(stats.annualized_volatility)),
+            ],
+        ),
+    ];
+
+    if let Some(drawdown) = stats.max_drawdown {
+        lines.push(fill(
+            catalog.stats_drawdown,
+            &[
+                ("PERCENT", &percent(drawdown.percent)),
+                ("PEAK", &number(drawdown.peak.1)),
+                ("PEAK_DATE", &locale.format_date(drawdown.peak.0)),
+                ("TROUGH", &number(drawdown.trough.1)),
+                ("TROUGH_DATE", &locale.format_date(drawdown.trough.0)),
+            ],
+        ));
+    }
+    lines.push(fill(
+        catalog.stats_streaks,
+        &[
+            ("GAINS", &stats.longest_gains.to_string()),
+            ("LOSSES", &stats.longest_losses.to_string()),
+        ],
+    ));
+    if let Some(z_score) = stats.z_score {
+        lines.push(fill(
+            catalog.stats_z_score,
+            &[("Z_SCORE", &locale.format_signed_number(z_score, 2))],
+        ));
+    }
+
+    if let Some(versus) = versus {
+        let versus_series = get_series(&rates, &from, &versus);
+        let recent = get_rolling_correlation(&series, &versus_series, CORRELATION_WINDOW)
+            .last()
+            .map(|(_, correlation)| *correlation);
+        let signed = |value: f64| locale.format_signed_number(value, 2);
+        let line = match (get_pair_correlation(&series, &versus_series), recent) {
+            (Some(correlation), Some(recent)) => fill(
+                catalog.stats_correlation_recent,
+                &[
+                    ("FROM", &from),
+                    ("VERSUS", &versus),
+                    ("CORRELATION", &signed(correlation)),
+                    ("RECENT", &signed(recent)),
+                    ("WINDOW", &CORRELATION_WINDOW.to_string()),
+                ],
+            ),
+            (Some(correlation), None) => fill(
+                catalog.stats_correlation,
+                &[
+                    ("FROM", &from),
+                    ("VERSUS", &versus),
+                    ("CORRELATION", &signed(correlation)),
+                ],
+            ),
+            (None, _) => fill(
+                catalog.stats_no_correlation,
+                &[("FROM", &from), ("VERSUS", &versus)],
+            ),
+        };
+        lines.push(line);
+    }
+
+    EditInteractionResponse::new().content(lines.join("\n"))
+}
@@ -0,0 +1,498 @@
+use chrono::{Duration, Utc};
+use serenity::all::{CommandInteraction, CommandOptionType, EditInteractionResponse, Permissions};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{ResolvedOption, ResolvedValue};
+
+use crate::database::prompt_template::{
+    get_prompt_template_version, get_prompt_template_versions, save_prompt_template,
+};
+use crate::environment;
+use crate::exchange_rate::ExchangeRateMap;
+use crate::i18n::{fill, Catalog, Locale};
+use crate::llm::prompt::{
+    get_template, get_template_context, is_prompt_template, parse_template, TEMPLATE_NAMES,
+};
+use crate::utils::calendar::get_events;
+use crate::utils::search::get_news;
+
+use super::{get_integer_option, get_string_option};
+
+pub const COMMAND_NAME: &str = "template";
+
+/// Versions listed by `/template view`
+const MAX_LISTED_VERSIONS: usize = 10;
+
+/// Longest template text shown, leaves room for the rest of the message
+const MAX_SHOWN_CHARS: usize = 1500;
+
+fn name_option() -> CreateCommandOption {
+    let mut option = CreateCommandOption::new(CommandOptionType::String, "name", "Template name")
+        .name_localized("de", "name")
+        .description_localized("de", "Name der Vorlage")
+        .description_localized("hi", "टेम्पलेट का नाम")
+        .name_localized("ja", "名前")
+        .description_localized("ja", "テンプレート名")
+        .name_localized("es-ES", "nombre")
+        .description_localized("es-ES", "Nombre de la plantilla")
+        .required(true);
+    for name in TEMPLATE_NAMES {
+        option = option.add_string_choice(*name, *name);
+    }
+    option
+}
+
+fn template_options(command: CreateCommandOption) -> CreateCommandOption {
+    command
+        .add_sub_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "template",
+                "Template text, on a single line",
+            )
+            .name_localized("de", "vorlage")
+            .description_localized("de", "Text der Vorlage, in einer Zeile")
+            .description_localized("hi", "टेम्पलेट का पाठ, एक ही पंक्ति में")
+            .name_localized("ja", "テンプレート")
+            .description_localized("ja", "テンプレートの本文、1 行で")
+            .name_localized("es-ES", "plantilla")
+            .description_localized("es-ES", "Texto de la plantilla, en una sola línea")
+            .required(false),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(
+                CommandOptionType::Attachment,
+                "file",
+                "Text file with the template, for templates spanning several lines",
+            )
+            .name_localized("de", "datei")
+            .description_localized("de", "Textdatei mit der Vorlage, für mehrzeilige Vorlagen")
+            .description_localized("hi", "टेम्पलेट वाली टेक्स्ट फ़ाइल, कई पंक्तियों वाले टेम्पलेट के लिए")
+            .name_localized("ja", "ファイル")
+            .description_localized(
+                "ja",
+                "テンプレートのテキストファイル、複数行のテンプレート用",
+            )
+            .name_localized("es-ES", "archivo")
+            .description_localized(
+                "es-ES",
+                "Archivo de texto con la plantilla, para plantillas de varias líneas",
+            )
+            .required(false),
+        )
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Manage the prompt templates of this server")
+        .name_localized("de", "vorlage")
+        .description_localized("de", "Verwaltet die Prompt-Vorlagen dieses Servers")
+        .description_localized("hi", "इस सर्वर के प्रॉम्प्ट टेम्पलेट प्रबंधित करें")
+        .name_localized("ja", "テンプレート")
+        .description_localized("ja", "このサーバーのプロンプトテンプレートを管理します")
+        .name_localized("es-ES", "plantilla")
+        .description_localized(
+            "es-ES",
+            "Gestiona las plantillas de prompt de este servidor",
+        )
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "view",
+                "Show a template and its versions",
+            )
+            .name_localized("de", "anzeigen")
+            .description_localized("de", "Zeigt eine Vorlage und ihre Versionen")
+            .description_localized("hi", "एक टेम्पलेट और उसके संस्करण दिखाएँ")
+            .name_localized("ja", "表示")
+            .description_localized("ja", "テンプレートとそのバージョンを表示します")
+            .name_localized("es-ES", "ver")
+            .description_localized("es-ES", "Muestra una plantilla y sus versiones")
+            .add_sub_option(name_option())
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "version",
+                    "Version to show instead of the current one",
+                )
+                .name_localized("de", "version")
+                .description_localized("de", "Anzuzeigende Version statt der aktuellen")
+                .description_localized("hi", "मौजूदा के बजाय दिखाया जाने वाला संस्करण")
+                .name_localized("ja", "バージョン")
+                .description_localized("ja", "現在のものの代わりに表示するバージョン")
+                .name_localized("es-ES", "version")
+                .description_localized("es-ES", "Versión a mostrar en lugar de la actual")
+                .min_int_value(1)
+                .required(false),
+            ),
+        )
+        .add_option(template_options(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "set",
+                "Save a new version of a template",
+            )
+            .name_localized("de", "festlegen")
+            .description_localized("de", "Speichert eine neue Version einer Vorlage")
+            .description_localized("hi", "टेम्पलेट का नया संस्करण सहेजें")
+            .name_localized("ja", "設定")
+            .description_localized("ja", "テンプレートの新しいバージョンを保存します")
+            .name_localized("es-ES", "definir")
+            .description_localized("es-ES", "Guarda una nueva versión de una plantilla")
+            .add_sub_option(name_option()),
+        ))
+        .add_option(
+            template_options(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "preview",
+                    "Render a template with the current exchange rates",
+                )
+                .name_localized("de", "vorschau")
+                .description_localized("de", "Rendert eine Vorlage mit den aktuellen Wechselkursen")
+                .description_localized("hi", "मौजूदा विनिमय दरों के साथ टेम्पलेट दिखाएँ")
+                .name_localized("ja", "プレビュー")
+                .description_localized("ja", "現在の為替レートでテンプレートを表示します")
+                .name_localized("es-ES", "vista-previa")
+                .description_localized(
+                    "es-ES",
+                    "Muestra una plantilla con los tipos de cambio actuales",
+                )
+                .add_sub_option(name_option()),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "from",
+                    "Currency to convert from",
+                )
+                .name_localized("de", "von")
+                .description_localized("de", "Die Ausgangswährung für die Konvertierung")
+                .description_localized("hi", "जिस मुद्रा से परिवर्तित करना है")
+                .name_localized("ja", "変換元")
+                .description_localized("ja", "変換する通貨")
+                .name_localized("es-ES", "de")
+                .description_localized("es-ES", "Moneda de origen para la conversión")
+                .required(false)
+                .set_autocomplete(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "to", "Currency to convert to")
+                    .name_localized("de", "zu")
+                    .description_localized("de", "Die Zielwährung für die Konvertierung")
+                    .description_localized("hi", "जिस मुद्रा में परिवर्तित करना है")
+                    .name_localized("ja", "変換先")
+                    .description_localized("ja", "変換される通貨")
+                    .name_localized("es-ES", "a")
+                    .description_localized("es-ES", "Moneda de destino para la conversión")
+                    .required(false)
+                    .set_autocomplete(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "revert",
+                "Save an earlier version as the newest version",
+            )
+            .name_localized("de", "zurücksetzen")
+            .description_localized("de", "Speichert eine frühere Version als neueste Version")
+            .description_localized("hi", "पिछले संस्करण को नवीनतम संस्करण के रूप में सहेजें")
+            .name_localized("ja", "復元")
+            .description_localized("ja", "以前のバージョンを最新のバージョンとして保存します")
+            .name_localized("es-ES", "revertir")
+            .description_localized("es-ES", "Guarda una versión anterior como la más reciente")
+            .add_sub_option(name_option())
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "version",
+                    "Version to go back to, 0 for the default template",
+                )
+                .name_localized("de", "version")
+                .description_localized(
+                    "de",
+                    "Version, zu der zurückgekehrt wird, 0 für die Standardvorlage",
+                )
+                .description_localized("hi", "जिस संस्करण पर लौटना है, डिफ़ॉल्ट टेम्पलेट के लिए 0")
+                .name_localized("ja", "バージョン")
+                .description_localized("ja", "戻すバージョン、既定のテンプレートは 0")
+                .name_localized("es-ES", "version")
+                .description_localized(
+                    "es-ES",
+                    "Versión a la que volver, 0 para la plantilla predeterminada",
+                )
+                .min_int_value(0)
+                .required(true),
+            ),
+        )
+}
+
+/// Template given by the `file` or `template` option
+async fn get_template_option(
+    options: &[ResolvedOption<'_>],
+    catalog: &Catalog,
+) -> Result<Option<String>, String> {
+    let attachment = options.iter().find_map(|opt| match &opt.value {
+        ResolvedValue::Attachment(attachment) if opt.name == "file" => Some(*attachment),
+        _ => None,
+    });
+
+    match attachment {
+        Some(attachment) => {
+            let bytes = attachment.download().await.map_err(|e| {
+                fill(
+                    catalog.template_download_error,
+                    &[("FILE", &attachment.filename), ("ERROR", &e.to_string())],
+                )
+            })?;
+            String::from_utf8(bytes)
+                .map(Some)
+                .map_err(|_| fill(catalog.template_not_text, &[("FILE", &attachment.filename)]))
+        }
+        None => Ok(get_string_option(options, "template")),
+    }
+}
+
+fn check_template(name: &str, template: &str, catalog: &Catalog) -> Result<(), String> {
+    if !is_prompt_template(name) {
+        return Ok(());
+    }
+    parse_template(template)
+        .map(|_| ())
+        .map_err(|e| fill(catalog.template_invalid, &[("ERROR", &e.to_string())]))
+}
+
+fn format_template(template: &str) -> String {
+    let mut shown: String = template.chars().take(MAX_SHOWN_CHARS).collect();
+    if shown.len() < template.len() {
+        shown.push_str("\n...");
+    }
+    format!("```\n{}\n```", shown.replace("```", "'''"))
+}
+
+/// Shows the template in effect for `locale` when no version is given
+fn view(guild_id: u64, name: &str, version: Option<i64>, locale: Locale) -> String {
+    let catalog = locale.catalog();
+    if let Some(version) = version {
+        let args = [("NAME", name), ("VERSION", &version.to_string())];
+        return match get_prompt_template_version(guild_id, name, version) {
+            Some(v) if v.template.is_empty() => fill(catalog.template_version_default, &args),
+            Some(v) => format!(
+                "{}\n{}",
+                fill(catalog.template_version, &args),
+                format_template(&v.template)
+            ),
+            None => fill(catalog.template_no_version, &args),
+        };
+    }
+
+    let versions = get_prompt_template_versions(guild_id, name, MAX_LISTED_VERSIONS);
+    let source = match versions.first() {
+        Some(latest) if !latest.template.is_empty() => fill(
+            catalog.template_source_server,
+            &[("VERSION", &latest.version.to_string())],
+        ),
+        _ => catalog.template_source_default.to_string(),
+    };
+    let current = match get_template(Some(guild_id), name, locale) {
+        Some(template) => format_template(&template),
+        None => catalog.template_not_set.to_string(),
+    };
+
+    let history: Vec<String> = versions
+        .iter()
+        .map(|v| {
+            let author = v.author_id.map(|id| format!("<@{}>", id));
+            format!(
+                "- {} ({}{}){}",
+                v.version,
+                v.time,
+                author
+                    .map(|author| {
+                        format!(
+                            ", {}",
+                            fill(catalog.template_version_by, &[("AUTHOR", &author)])
+                        )
+                    })
+                    .unwrap_or_default(),
+                if v.template.is_empty() {
+                    format!(" {}", catalog.template_version_reset)
+                } else {
+                    String::new()
+                }
+            )
+        })
+        .collect();
+
+    format!(
+        "`{}` ({}):\n{}{}",
+        name,
+        source,
+        current,
+        match history.len() {
+            0 => String::new(),
+            _ => format!("\n{}\n{}", catalog.template_versions, history.join("\n")),
+        }
+    )
+}
+
+async fn set(
+    guild_id: u64,
+    author_id: u64,
+    name: &str,
+    catalog: &Catalog,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, String> {
+    let template = get_template_option(options, catalog)
+        .await?
+        .ok_or(catalog.template_missing)?;
+    check_template(name, &template, catalog)?;
+
+    let version = save_prompt_template(guild_id, name, &template, Some(author_id));
+    Ok(fill(
+        catalog.template_saved,
+        &[("NAME", name), ("VERSION", &version.to_string())],
+    ))
+}
+
+async fn preview(
+    guild_id: u64,
+    name: &str,
+    locale: Locale,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, String> {
+    let catalog = locale.catalog();
+    let template = match get_template_option(options, catalog).await? {
+        Some(template) => template,
+        None => get_template(Some(guild_id), name, locale)
+            .ok_or_else(|| fill(catalog.template_unset, &[("NAME", name)]))?,
+    };
+    check_template(name, &template, catalog)?;
+
+    if !is_prompt_template(name) {
+        return Ok(format_template(&template));
+    }
+
+    let from = get_string_option(options, "from")
+        .unwrap_or_else(environment::get_exchange_from)
+        .to_uppercase();
+    let to = get_string_option(options, "to")
+        .unwrap_or_else(environment::get_exchange_to)
+        .to_uppercase();
+
+    let from_date = (Utc::now() - Duration::days(90)).date_naive();
+    let rates = ExchangeRateMap::get_rates(from_date, Some(from.clone()))
+        .await
+        .map_err(|e| fill(catalog.rates_error, &[("ERROR", &e.to_string())]))?;
+    let news = get_news(&from, &to, Utc::now(), 5).await;
+
+    let events = get_events().await;
+
+    let context = get_template_context(&rates, &from, &to, &news, &events);
+    let prompt = parse_template(&template)
+        .and_then(|t| t.render(&context))
+        .map_err(|e| fill(catalog.template_render_error, &[("ERROR", &e.to_string())]))?;
+
+    Ok(format!(
+        "{}\n{}",
+        fill(
+            catalog.template_preview,
+            &[("NAME", name), ("FROM", &from), ("TO", &to)]
+        ),
+        format_template(&prompt)
+    ))
+}
+
+fn revert(
+    guild_id: u64,
+    author_id: u64,
+    name: &str,
+    version: i64,
+    catalog: &Catalog,
+) -> Result<String, String> {
+    let template = match version {
+        // An empty version makes the default template apply again
+        0 => String::new(),
+        _ => {
+            get_prompt_template_version(guild_id, name, version)
+                .ok_or_else(|| {
+                    fill(
+                        catalog.template_no_version,
+                        &[("NAME", name), ("VERSION", &version.to_string())],
+                    )
+                })?
+                .template
+        }
+    };
+
+    let new_version = save_prompt_template(guild_id, name, &template, Some(author_id));
+    Ok(match version {
+        0 => fill(
+            catalog.template_reset,
+            &[("NAME", name), ("VERSION", &new_version.to_string())],
+        ),
+        _ => fill(
+            catalog.template_reverted,
+            &[
+                ("NAME", name),
+                ("VERSION", &version.to_string()),
+                ("NEW_VERSION", &new_version.to_string()),
+            ],
+        ),
+    })
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let locale = Locale::from_discord(&command.locale);
+    let catalog = locale.catalog();
+    let Some(guild_id) = command.guild_id.map(|id| id.get()) else {
+        return EditInteractionResponse::new().content(catalog.template_guild_only);
+    };
+
+    // The default permission can be overridden by the server, check it again
+    let can_manage = command
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.manage_guild());
+    if !can_manage {
+        return EditInteractionResponse::new().content(catalog.template_permission);
+    }
+
+    let options = command.data.options();
+    let Some((subcommand, options)) = options.first().and_then(|opt| match &opt.value {
+        ResolvedValue::SubCommand(options) => Some((opt.name, options)),
+        _ => None,
+    }) else {
+        return EditInteractionResponse::new().content(catalog.unknown_subcommand);
+    };
+
+    let name = get_string_option(options, "name").unwrap_or_default();
+    let author_id = command.user.id.get();
+    let result = match subcommand {
+        "view" => Ok(view(
+            guild_id,
+            &name,
+            get_integer_option(options, "version"),
+            locale,
+        )),
+        "set" => set(guild_id, author_id, &name, catalog, options).await,
+        "preview" => preview(guild_id, &name, locale, options).await,
+        "revert" => revert(
+            guild_id,
+            author_id,
+            &name,
+            get_integer_option(options, "version").unwrap_or_default(),
+            catalog,
+        ),
+        _ => Err(catalog.unknown_subcommand.to_string()),
+    };
+
+    let content = match result {
+        Ok(content) => content,
+        Err(e) => e,
+    };
+    EditInteractionResponse::new().content(content)
+}
@@ -0,0 +1,93 @@
+use serenity::all::{CommandInteraction, CommandOptionType, EditInteractionResponse, Permissions};
+use serenity::builder::{CreateCommand, CreateCommandOption};
+
+use crate::database::guild_setting::{get_guild_setting, set_guild_setting};
+use crate::i18n::{fill, Locale};
+use crate::utils::theme::{Theme, THEME_SETTING};
+
+use super::get_string_option;
+
+pub const COMMAND_NAME: &str = "theme";
+
+/// Choice going back to `CHART_THEME`
+const DEFAULT_THEME: &str = "default";
+
+pub fn register() -> CreateCommand {
+    let mut theme = CreateCommandOption::new(
+        CommandOptionType::String,
+        "theme",
+        "Theme to use, the current one is shown when left out",
+    )
+    .name_localized("de", "design")
+    .description_localized(
+        "de",
+        "Zu verwendendes Design, ohne Angabe wird das aktuelle angezeigt",
+    )
+    .description_localized(
+        "hi",
+        "इस्तेमाल की जाने वाली थीम, न देने पर मौजूदा थीम दिखाई जाती है",
+    )
+    .name_localized("ja", "テーマ")
+    .description_localized("ja", "使うテーマ、省略すると現在のテーマを表示します")
+    .name_localized("es-ES", "tema")
+    .description_localized("es-ES", "Tema a usar, si se omite se muestra el actual")
+    .required(false);
+    for name in Theme::NAMES.iter().chain([&DEFAULT_THEME]) {
+        theme = theme.add_string_choice(*name, *name);
+    }
+
+    CreateCommand::new(COMMAND_NAME)
+        .description("Choose the chart theme of this server")
+        .name_localized("de", "design")
+        .description_localized("de", "Wähle das Diagrammdesign dieses Servers")
+        .description_localized("hi", "इस सर्वर की चार्ट थीम चुनें")
+        .name_localized("ja", "テーマ")
+        .description_localized("ja", "このサーバーのグラフのテーマを選びます")
+        .name_localized("es-ES", "tema")
+        .description_localized("es-ES", "Elige el tema de los gráficos de este servidor")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .add_option(theme)
+}
+
+pub async fn run(command: &CommandInteraction) -> EditInteractionResponse {
+    let catalog = Locale::from_discord(&command.locale).catalog();
+    let Some(guild_id) = command.guild_id.map(|id| id.get()) else {
+        return EditInteractionResponse::new().content(catalog.theme_guild_only);
+    };
+
+    let theme = get_string_option(&command.data.options(), "theme").map(|s| s.to_lowercase());
+    let Some(theme) = theme else {
+        let content = match get_guild_setting(guild_id, THEME_SETTING) {
+            Some(theme) => fill(catalog.theme_current, &[("THEME", &theme)]),
+            None => catalog.theme_current_default.to_string(),
+        };
+        return EditInteractionResponse::new().content(content);
+    };
+
+    // The default permission can be overridden by the server, check it again
+    let can_manage = command
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.manage_guild());
+    if !can_manage {
+        return EditInteractionResponse::new().content(catalog.theme_permission);
+    }
+
+    let content = match theme.as_str() {
+        DEFAULT_THEME => {
+            set_guild_setting(guild_id, THEME_SETTING, None);
+            catalog.theme_reset.to_string()
+        }
+        name if Theme::NAMES.contains(&name) => {
+            set_guild_setting(guild_id, THEME_SETTING, Some(name));
+            fill(catalog.theme_set, &[("THEME", name)])
+        }
+        name => fill(
+            catalog.theme_unknown,
+            &[("THEME", name), ("THEMES", &Theme::NAMES.join(", "))],
+        ),
+    };
+    EditInteractionResponse::new().content(content)
+}
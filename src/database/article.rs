@@ -0,0 +1,32 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::environment;
+
+/**
+ * Save the text extracted from the article at `url`, empty when the article
+ * had no readable text
+ */
+pub fn save_article(url: &str, text: &str) {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = "INSERT OR REPLACE INTO article (url, text) VALUES (?, ?)";
+
+    con.execute(query, params![url, text]).unwrap();
+
+    log::debug!("Saved article {url}");
+}
+
+/**
+ * Get the text extracted from the article at `url`, `None` when it was never fetched
+ */
+pub fn get_article(url: &str) -> Option<String> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = "SELECT text FROM article WHERE url = ?";
+
+    con.query_row(query, params![url], |row| row.get(0))
+        .optional()
+        .unwrap()
+}
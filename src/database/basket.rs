@@ -0,0 +1,84 @@
+use rusqlite::{params, Connection};
+
+use crate::environment;
+use crate::utils::basket::{Basket, BasketKind};
+
+/**
+ * All saved baskets, by name
+ */
+pub fn get_saved_baskets() -> Vec<Basket> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT name, kind, components, scale
+        FROM basket
+        ORDER BY name;
+    "#;
+
+    let mut stmt = match con.prepare(query) {
+        Ok(stmt) => stmt,
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            return vec![];
+        }
+    };
+    let rows = stmt.query_map([], |row| {
+        let kind: String = row.get(1)?;
+        let components: String = row.get(2)?;
+        Ok((row.get(0)?, kind, components, row.get(3)?))
+    });
+
+    match rows {
+        Ok(rows) => rows
+            .filter_map(|row| row.ok())
+            .filter_map(|(name, kind, components, scale)| {
+                Some(Basket {
+                    name,
+                    kind: BasketKind::from_name(&kind)?,
+                    components: serde_json::from_str(&components).ok()?,
+                    scale,
+                })
+            })
+            .collect(),
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            vec![]
+        }
+    }
+}
+
+/**
+ * Save `basket`, replacing the basket of the same name
+ */
+pub fn save_basket(basket: &Basket) {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let components = serde_json::to_string(&basket.components).unwrap_or_default();
+    let result = con.execute(
+        r#"
+        INSERT INTO basket (name, kind, components, scale) VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT (name) DO UPDATE
+        SET kind = ?2, components = ?3, scale = ?4, time = CURRENT_TIMESTAMP;
+        "#,
+        params![basket.name, basket.kind.name(), components, basket.scale],
+    );
+
+    match result {
+        Ok(_) => log::debug!("Saved basket {}", basket.name),
+        Err(e) => log::error!("Error while saving to database: {}", e),
+    }
+}
+
+/**
+ * Delete basket `name`
+ */
+pub fn delete_basket(name: &str) {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    if let Err(e) = con.execute("DELETE FROM basket WHERE name = ?;", params![name]) {
+        log::error!("Error while saving to database: {}", e);
+    }
+}
@@ -0,0 +1,49 @@
+use rusqlite::{params, Connection};
+
+use crate::environment;
+
+/**
+ * Save a message of an `/ask` conversation to database
+ */
+pub fn save_conversation_message(channel_id: u64, user_id: u64, role: &str, content: &str) {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = "INSERT INTO conversation (channel_id, user_id, role, content) VALUES (?, ?, ?, ?)";
+
+    con.execute(query, params![channel_id, user_id, role, content])
+        .unwrap();
+
+    log::debug!("Saved {role} message of {user_id} in {channel_id}");
+}
+
+/**
+ * Get the last `limit` messages of a user's conversation in a channel, oldest first
+ */
+pub fn get_conversation(channel_id: u64, user_id: u64, limit: usize) -> Vec<(String, String)> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT role, content
+        FROM conversation
+        WHERE channel_id = ? AND user_id = ?
+        ORDER BY rowid DESC
+        LIMIT ?;
+    "#;
+
+    let mut stmt = con.prepare(query).unwrap();
+    let rows = stmt.query_map(params![channel_id, user_id, limit], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    });
+
+    let mut messages: Vec<(String, String)> = match rows {
+        Ok(rows) => rows.filter_map(|row| row.ok()).collect(),
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            vec![]
+        }
+    };
+    messages.reverse();
+    messages
+}
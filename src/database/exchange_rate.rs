@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use rusqlite::{params, Connection};
 
 use crate::environment;
@@ -18,6 +18,48 @@ pub fn save_exchange_rate(from: &str, to: &str, rate: f64) {
     log::debug!("Saved exchange rate from {} to {} as {}", from, to, rate);
 }
 
+/**
+ * Rates from `from` to `to` saved since `since`, oldest first.
+ * Every report saves one, so a schedule running several times a day gives
+ * intraday rates.
+ */
+pub fn get_saved_exchange_rates(
+    from: &str,
+    to: &str,
+    since: NaiveDate,
+) -> Vec<(NaiveDateTime, f64)> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT time, rate
+        FROM exchange_rate
+        WHERE from_currency = ? AND to_currency = ? AND time >= ? AND rate > 0
+        ORDER BY time;
+    "#;
+
+    let mut stmt = con.prepare(query).unwrap();
+    let rows = stmt.query_map(
+        params![from, to, since.format("%Y-%m-%d").to_string()],
+        |row| Ok((row.get::<_, String>(0)?, row.get(1)?)),
+    );
+
+    match rows {
+        Ok(rows) => rows
+            .filter_map(|row| row.ok())
+            .filter_map(|(time, rate)| {
+                NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .map(|time| (time, rate))
+            })
+            .collect(),
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            vec![]
+        }
+    }
+}
+
 /**
  * Save raw exchange rate to database
  */
@@ -61,7 +103,9 @@ pub fn get_local_exchange_rate_fallback(date: chrono::NaiveDate) -> Option<Strin
         LIMIT 1;
     "#;
 
-    match con.query_row(query, params![date.format("%Y-%m-%d").to_string()], |row| row.get(0)) {
+    match con.query_row(query, params![date.format("%Y-%m-%d").to_string()], |row| {
+        row.get(0)
+    }) {
         Ok(json) => {
             log::debug!("Retrieved fallback exchange rate for {}: {}", date, json);
             Some(json)
@@ -0,0 +1,55 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::environment;
+
+/**
+ * Get setting `name` of `guild_id`, `None` when the guild did not choose one
+ */
+pub fn get_guild_setting(guild_id: u64, name: &str) -> Option<String> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT value
+        FROM guild_setting
+        WHERE guild_id = ? AND name = ?;
+    "#;
+
+    match con
+        .query_row(query, params![guild_id, name], |row| row.get(0))
+        .optional()
+    {
+        Ok(value) => value,
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            None
+        }
+    }
+}
+
+/**
+ * Set setting `name` of `guild_id` to `value`, `None` going back to the default
+ */
+pub fn set_guild_setting(guild_id: u64, name: &str, value: Option<&str>) {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let result = match value {
+        Some(value) => con.execute(
+            r#"
+            INSERT INTO guild_setting (guild_id, name, value) VALUES (?1, ?2, ?3)
+            ON CONFLICT (guild_id, name) DO UPDATE SET value = ?3;
+            "#,
+            params![guild_id, name, value],
+        ),
+        None => con.execute(
+            "DELETE FROM guild_setting WHERE guild_id = ? AND name = ?;",
+            params![guild_id, name],
+        ),
+    };
+
+    match result {
+        Ok(_) => log::debug!("Set {name} of guild {guild_id} to {value:?}"),
+        Err(e) => log::error!("Error while saving to database: {}", e),
+    }
+}
@@ -1,17 +1,87 @@
-use rusqlite::Connection;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension};
 
 use crate::environment;
 
+/// What a result was generated for, stored in the `kind` column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LlmResultKind {
+    /// Exchange rate report, reused by [`get_cached_llm_result`]
+    Report,
+    /// Answer to a question of a conversation, kept but never reused
+    Answer,
+}
+
+impl LlmResultKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LlmResultKind::Report => "report",
+            LlmResultKind::Answer => "answer",
+        }
+    }
+}
+
 /**
  * Save raw LLM result to database
+ *
+ * `result` is the raw output of the model and `content` the final response,
+ * reused by [`get_cached_llm_result`] for reports of the same `model` and
+ * `context_hash`.
  */
-pub fn save_llm_result(prompt: &str, result: &str) {
+pub fn save_llm_result(
+    kind: LlmResultKind,
+    model: &str,
+    prompt: &str,
+    context_hash: &str,
+    result: &str,
+    content: Option<&str>,
+) {
     let db_file = environment::get_db_file();
     let con = Connection::open(db_file).unwrap();
 
-    let query = "INSERT INTO llm_result (prompt, result) VALUES (?, ?)";
+    let query = "INSERT INTO llm_result (kind, model, prompt, context_hash, result, content) VALUES (?, ?, ?, ?, ?, ?)";
 
-    con.execute(query, &[prompt, result]).unwrap();
+    con.execute(
+        query,
+        params![kind.as_str(), model, prompt, context_hash, result, content],
+    )
+    .unwrap();
 
     log::debug!("Saved llm result: {} -> {}", prompt, result);
 }
+
+/**
+ * Get the newest report generated by `model` for the same context, with the
+ * time it was generated at
+ */
+pub fn get_cached_llm_result(model: &str, context_hash: &str) -> Option<(String, NaiveDateTime)> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT content, time
+        FROM llm_result
+        WHERE kind = 'report' AND model = ? AND context_hash = ?
+            AND content IS NOT NULL
+        ORDER BY rowid DESC
+        LIMIT 1;
+    "#;
+
+    match con
+        .query_row(query, params![model, context_hash], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .optional()
+    {
+        // CURRENT_TIMESTAMP is stored as UTC text
+        Ok(result) => result.and_then(|(content, time)| {
+            NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|time| (content, time))
+        }),
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            None
+        }
+    }
+}
@@ -1,3 +1,9 @@
+pub mod article;
+pub mod basket;
+pub mod conversation;
 pub mod exchange_rate;
+pub mod guild_setting;
 pub mod llm_result;
+pub mod prompt_template;
+pub mod quarantine;
 pub mod search_result;
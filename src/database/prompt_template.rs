@@ -0,0 +1,152 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::environment;
+
+/// Guild id of the templates used by every guild without its own version
+pub const DEFAULT_GUILD_ID: u64 = 0;
+
+pub struct PromptTemplateVersion {
+    pub version: i64,
+    /// Empty when this version went back to the default template
+    pub template: String,
+    pub author_id: Option<u64>,
+    pub time: String,
+}
+
+/**
+ * Save a new version of template `name` for `guild_id` and return its version number
+ */
+pub fn save_prompt_template(
+    guild_id: u64,
+    name: &str,
+    template: &str,
+    author_id: Option<u64>,
+) -> i64 {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        INSERT INTO prompt_template (guild_id, name, version, template, author_id)
+        SELECT ?1, ?2, COALESCE(MAX(version), 0) + 1, ?3, ?4
+        FROM prompt_template
+        WHERE guild_id = ?1 AND name = ?2
+        RETURNING version;
+    "#;
+
+    let version: i64 = con
+        .query_row(query, params![guild_id, name, template, author_id], |row| {
+            row.get(0)
+        })
+        .unwrap();
+
+    log::debug!("Saved version {version} of {name} for guild {guild_id}");
+    version
+}
+
+/**
+ * Get version `version` of template `name` for `guild_id`
+ */
+pub fn get_prompt_template_version(
+    guild_id: u64,
+    name: &str,
+    version: i64,
+) -> Option<PromptTemplateVersion> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT version, template, author_id, time
+        FROM prompt_template
+        WHERE guild_id = ? AND name = ? AND version = ?;
+    "#;
+
+    match con
+        .query_row(query, params![guild_id, name, version], |row| {
+            Ok(PromptTemplateVersion {
+                version: row.get(0)?,
+                template: row.get(1)?,
+                author_id: row.get(2)?,
+                time: row.get(3)?,
+            })
+        })
+        .optional()
+    {
+        Ok(version) => version,
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            None
+        }
+    }
+}
+
+/**
+ * Get the last `limit` versions of template `name` for `guild_id`, newest first
+ */
+pub fn get_prompt_template_versions(
+    guild_id: u64,
+    name: &str,
+    limit: usize,
+) -> Vec<PromptTemplateVersion> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT version, template, author_id, time
+        FROM prompt_template
+        WHERE guild_id = ? AND name = ?
+        ORDER BY version DESC
+        LIMIT ?;
+    "#;
+
+    let mut stmt = con.prepare(query).unwrap();
+    let rows = stmt.query_map(params![guild_id, name, limit], |row| {
+        Ok(PromptTemplateVersion {
+            version: row.get(0)?,
+            template: row.get(1)?,
+            author_id: row.get(2)?,
+            time: row.get(3)?,
+        })
+    });
+
+    match rows {
+        Ok(rows) => rows.filter_map(|row| row.ok()).collect(),
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            vec![]
+        }
+    }
+}
+
+/**
+ * Get the latest version of template `name` for `guild_id`, unless that
+ * version went back to the default
+ */
+pub fn get_prompt_template(guild_id: u64, name: &str) -> Option<String> {
+    get_prompt_template_versions(guild_id, name, 1)
+        .pop()
+        .map(|v| v.template)
+        .filter(|t| !t.is_empty())
+}
+
+/**
+ * Store the templates configured by environment variables as the first default
+ * versions. Templates already in the database are kept.
+ */
+pub fn seed_prompt_templates(templates: &[(&str, String)]) {
+    for (name, template) in templates {
+        match get_prompt_template_versions(DEFAULT_GUILD_ID, name, 1).pop() {
+            Some(latest) => {
+                if latest.template != *template {
+                    log::info!(
+                        "{name} is stored in the database (version {}), the environment variable is ignored",
+                        latest.version
+                    );
+                }
+            }
+            None => {
+                save_prompt_template(DEFAULT_GUILD_ID, name, template, None);
+                log::info!("Seeded {name} from the environment");
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use rusqlite::{params, Connection};
+
+use crate::{environment, exchange_rate::ExchangeRateMap};
+
+/// Rates kept out of use, with why
+pub struct QuarantinedRates {
+    pub id: i64,
+    pub date: String,
+    pub base: String,
+    pub provider: String,
+    pub reasons: String,
+}
+
+/**
+ * Quarantine `rates` of `provider` for `reasons`.
+ * The same rates with the same reasons are only quarantined once, so that
+ * fetching them again is not reported again.
+ */
+pub fn quarantine_rates(rates: &ExchangeRateMap, provider: &str, reasons: &str) {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let json = serde_json::to_string(&rates.map).unwrap_or_default();
+    let result = con.execute(
+        r#"
+        INSERT OR IGNORE INTO quarantined_exchange_rate (date, base, provider, rates, reasons)
+        VALUES (?, ?, ?, ?, ?);
+        "#,
+        params![
+            rates.get_date().format("%Y-%m-%d").to_string(),
+            rates.base,
+            provider,
+            json,
+            reasons
+        ],
+    );
+
+    match result {
+        Ok(_) => log::warn!(
+            "Quarantined the {} rates of {} from {}: {}",
+            rates.base,
+            rates.get_date(),
+            provider,
+            reasons
+        ),
+        Err(e) => log::error!("Error while saving to database: {}", e),
+    }
+}
+
+/**
+ * Quarantined rates no admin was told about yet, oldest first
+ */
+pub fn get_unnotified_quarantines() -> Vec<QuarantinedRates> {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    let query = r#"
+        SELECT rowid, date, base, provider, reasons
+        FROM quarantined_exchange_rate
+        WHERE notified = 0
+        ORDER BY rowid;
+    "#;
+
+    let mut stmt = con.prepare(query).unwrap();
+    let rows = stmt.query_map([], |row| {
+        Ok(QuarantinedRates {
+            id: row.get(0)?,
+            date: row.get(1)?,
+            base: row.get(2)?,
+            provider: row.get(3)?,
+            reasons: row.get(4)?,
+        })
+    });
+
+    match rows {
+        Ok(rows) => rows.filter_map(|row| row.ok()).collect(),
+        Err(e) => {
+            log::error!("Error while querying database: {}", e);
+            vec![]
+        }
+    }
+}
+
+/**
+ * Mark quarantined rates `ids` as reported
+ */
+pub fn set_quarantines_notified(ids: &[i64]) {
+    let db_file = environment::get_db_file();
+    let con = Connection::open(db_file).unwrap();
+
+    for id in ids {
+        if let Err(e) = con.execute(
+            "UPDATE quarantined_exchange_rate SET notified = 1 WHERE rowid = ?;",
+            params![id],
+        ) {
+            log::error!("Error while saving to database: {}", e);
+        }
+    }
+}
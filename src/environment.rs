@@ -1,5 +1,9 @@
 use rusqlite::Connection;
 
+use crate::i18n::Locale;
+use crate::utils::plots::{ChartMode, ImageFormat, Overlays};
+use crate::utils::theme::{get_theme, Theme, BUNDLED_FONT_FAMILY};
+
 use std::env;
 
 pub const APP_VERSION: &str = match option_env!("APP_VERSION") {
@@ -55,6 +59,18 @@ CREATE TABLE IF NOT EXISTS llm_result
 );
 "#;
 
+/// Columns added to `llm_result` for the response cache, created by older versions without them
+const LLM_RESULT_CACHE_COLUMNS: &[(&str, &str)] = &[
+    ("model", "TEXT"),        // Backend and model that generated the result
+    ("context_hash", "TEXT"), // Hash of everything sent besides the prompt
+    ("content", "TEXT"),      // Final response, NULL when it cannot be reused
+    ("kind", "TEXT NOT NULL DEFAULT 'report'"), // `report` or `answer` to a question, only reports are reused
+];
+
+const CREATE_LLM_RESULT_CACHE_INDEX_QUERY: &str = r#"
+CREATE INDEX IF NOT EXISTS llm_result_cache ON llm_result (model, context_hash);
+"#;
+
 const CREATE_HISTORICAL_DATA_TABLE_QUERY: &str = r#"
 CREATE TABLE IF NOT EXISTS historical_data (
     json TEXT NOT NULL,                  -- Text field to store JSON data
@@ -71,12 +87,84 @@ CREATE TABLE IF NOT EXISTS search_result
 );
 "#;
 
+const CREATE_CONVERSATION_TABLE_QUERY: &str = r#"
+CREATE TABLE IF NOT EXISTS conversation
+(
+    channel_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
+const CREATE_GUILD_SETTING_TABLE_QUERY: &str = r#"
+CREATE TABLE IF NOT EXISTS guild_setting
+(
+    guild_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (guild_id, name)
+);
+"#;
+
+const CREATE_QUARANTINED_EXCHANGE_RATE_TABLE_QUERY: &str = r#"
+CREATE TABLE IF NOT EXISTS quarantined_exchange_rate
+(
+    date DATE NOT NULL,                  -- Date of the rates
+    base VARCHAR(3) NOT NULL,
+    provider TEXT NOT NULL,              -- main or fallback
+    rates TEXT NOT NULL,                 -- JSON of the rates
+    reasons TEXT NOT NULL,
+    notified BOOLEAN NOT NULL DEFAULT 0, -- Whether the admin channels were told
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (date, base, provider, reasons)
+);
+"#;
+
+const CREATE_BASKET_TABLE_QUERY: &str = r#"
+CREATE TABLE IF NOT EXISTS basket
+(
+    name TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,                  -- weighted or geometric
+    components TEXT NOT NULL,            -- JSON of the currencies and their weights
+    scale REAL NOT NULL,
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
+const CREATE_ARTICLE_TABLE_QUERY: &str = r#"
+CREATE TABLE IF NOT EXISTS article
+(
+    url TEXT PRIMARY KEY,
+    text TEXT NOT NULL,                  -- Empty when nothing could be extracted
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
+const CREATE_PROMPT_TEMPLATE_TABLE_QUERY: &str = r#"
+CREATE TABLE IF NOT EXISTS prompt_template
+(
+    guild_id INTEGER NOT NULL,           -- 0 for the defaults of every guild
+    name TEXT NOT NULL,                  -- Name of the environment variable it replaces
+    version INTEGER NOT NULL,
+    template TEXT NOT NULL,              -- Empty to go back to the default
+    author_id INTEGER,                   -- NULL when seeded from the environment
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
 // pub fn get_interval() -> u64 {
 //     let interval_str = get_and_set_env_var("INTERVAL", "24h");
 //     let interval_int = string_to_time_second(interval_str.as_str());
 //     return interval_int;
 // }
 
+/// Language of scheduled posts in channels outside of guilds, a Discord locale code
+pub fn get_locale() -> String {
+    get_and_set_env_var("LOCALE", "en-US")
+}
+
 pub fn get_cron_expression() -> String {
     get_and_set_env_var("EXCHANGE_RATE_SCHEDULE", "0 0 0 * * *")
 }
@@ -125,6 +213,126 @@ pub fn get_searxng_url() -> Option<String> {
     }
 }
 
+/// Comma separated RSS or Atom feeds searched for news
+pub fn get_search_rss_feeds() -> Vec<String> {
+    env::var("SEARCH_RSS_FEEDS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|feed| feed.trim().to_string())
+        .filter(|feed| !feed.is_empty())
+        .collect()
+}
+
+/// Tokens of a search result passed to the LLM, longer contents are cut
+pub fn get_search_item_tokens() -> usize {
+    get_and_set_env_var("SEARCH_ITEM_TOKENS", "120")
+        .parse()
+        .expect("SEARCH_ITEM_TOKENS must be a number of tokens")
+}
+
+/// Number of top news results whose article is downloaded and summarized, 0 disables it
+pub fn get_article_fetch_count() -> usize {
+    get_and_set_env_var("ARTICLE_FETCH_COUNT", "0")
+        .parse()
+        .expect("ARTICLE_FETCH_COUNT must be a number of articles")
+}
+
+/// Tokens of the summary of a downloaded article
+pub fn get_article_summary_tokens() -> usize {
+    get_and_set_env_var("ARTICLE_SUMMARY_TOKENS", "250")
+        .parse()
+        .expect("ARTICLE_SUMMARY_TOKENS must be a number of tokens")
+}
+
+/// JSON file of search results, used instead of a search engine in tests
+pub fn get_search_local_file() -> Option<String> {
+    env::var("SEARCH_LOCAL_FILE").ok().filter(|path| !path.is_empty())
+}
+
+/// Chart of the scheduled report and `/exchange-check`
+pub fn get_chart_mode() -> ChartMode {
+    ChartMode::from_name(&get_and_set_env_var("CHART_MODE", "line")).unwrap_or_else(|| {
+        panic!(
+            "CHART_MODE must be one of {}",
+            ChartMode::NAMES.join(", ")
+        )
+    })
+}
+
+/// Overlays of the line charts, e.g. `sma20,bollinger,markers`
+pub fn get_chart_overlays() -> Overlays {
+    Overlays::parse(&get_and_set_env_var("CHART_OVERLAYS", ""))
+        .unwrap_or_else(|e| panic!("Invalid CHART_OVERLAYS: {}", e))
+}
+
+/// File format of the charts posted to Discord
+pub fn get_chart_format() -> ImageFormat {
+    ImageFormat::from_name(&get_and_set_env_var("CHART_FORMAT", "png")).unwrap_or_else(|| {
+        panic!(
+            "CHART_FORMAT must be one of {}",
+            ImageFormat::NAMES.join(", ")
+        )
+    })
+}
+
+/// Comma separated currencies of the cross rate matrix posted with the scheduled report
+pub fn get_matrix_currencies() -> String {
+    get_and_set_env_var("MATRIX_CURRENCIES", "")
+}
+
+/// Theme of the charts of guilds without their own, one of `Theme::NAMES`
+pub fn get_chart_theme() -> Theme {
+    get_theme(&get_and_set_env_var("CHART_THEME", "light")).unwrap_or_else(|| {
+        panic!(
+            "CHART_THEME must be one of {}",
+            Theme::NAMES.join(", ")
+        )
+    })
+}
+
+/// Colors of the `custom` theme, e.g. `background=#1e1f22,line=#58a6ff,palette=#ffa657;#7ee787`
+pub fn get_chart_colors() -> String {
+    get_and_set_env_var("CHART_COLORS", "")
+}
+
+/// Width of the charts in pixels at 96 DPI
+pub fn get_chart_width() -> u32 {
+    get_and_set_env_var("CHART_WIDTH", "800")
+        .parse()
+        .expect("CHART_WIDTH must be a number of pixels")
+}
+
+/// Height of the charts in pixels at 96 DPI
+pub fn get_chart_height() -> u32 {
+    get_and_set_env_var("CHART_HEIGHT", "400")
+        .parse()
+        .expect("CHART_HEIGHT must be a number of pixels")
+}
+
+/// Resolution of the charts, scaling their size, text and lines
+pub fn get_chart_dpi() -> u32 {
+    get_and_set_env_var("CHART_DPI", "96")
+        .parse()
+        .ok()
+        .filter(|dpi| *dpi > 0)
+        .expect("CHART_DPI must be a positive number")
+}
+
+/// Font file used by the charts instead of the bundled font
+pub fn get_chart_font() -> Option<String> {
+    env::var("CHART_FONT").ok().filter(|path| !path.is_empty())
+}
+
+/// Family of the font of the charts, the family of `CHART_FONT` when it is set
+pub fn get_chart_font_family() -> String {
+    get_and_set_env_var("CHART_FONT_FAMILY", BUNDLED_FONT_FAMILY)
+}
+
+/// ICS or CSV file or URL of the economic calendar
+pub fn get_economic_calendar() -> Option<String> {
+    env::var("ECONOMIC_CALENDAR").ok().filter(|source| !source.is_empty())
+}
+
 pub fn get_exchange_rate_api_url() -> String {
     return get_and_set_env_var("EXCHANGE_RATE_API_URL", "https://api.frankfurter.dev/v1");
 }
@@ -145,32 +353,29 @@ pub fn get_fallback_exchange_rate_api_key() -> Option<String> {
 pub fn get_increase_prompt_template() -> String {
     return get_and_set_env_var(
         "INCREASE_PROMPT_TEMPLATE",
-        r#"Today is {CURR_DATE}. Provide a brief report for the public about an increase in exchange rates.
-- Since {LAST_DATE}, the exchange rate from {FROM} to {TO} has risen to {CURR}, which is higher than the previous rate of {PREV}.
-- The increase in value is {DIFF}.
-Summarize this information clearly and concisely."#,
+        Locale::En.catalog().increase_prompt_template,
     );
 }
 
 pub fn get_decrease_prompt_template() -> String {
     return get_and_set_env_var(
         "DECREASE_PROMPT_TEMPLATE",
-        r#"Today is {CURR_DATE}. Provide a brief report for the public about a decrease in exchange rates.
-- Since {LAST_DATE}, the exchange rate from {FROM} to {TO} has dropped to {CURR}, which is lower than the previous rate of {PREV}.
-- The decrease in value is {DIFF}.
-Summarize this information clearly and concisely."#,
+        Locale::En.catalog().decrease_prompt_template,
     );
 }
 
 pub fn get_equal_prompt_template() -> String {
     return get_and_set_env_var(
         "EQUAL_PROMPT_TEMPLATE",
-        r#"Today is {CURR_DATE}. Provide a brief report for the public on the current exchange rate.
-- The exchange rate from {FROM} to {TO} is {CURR}, which is approximately the same as the previous rate of {PREV} on {LAST_DATE}.
-Summarize this information clearly and concisely."#,
+        Locale::En.catalog().equal_prompt_template,
     );
 }
 
+/// Single template used for every direction, overrides the three templates above
+pub fn get_prompt_template() -> Option<String> {
+    env::var("PROMPT_TEMPLATE").ok().filter(|t| !t.is_empty())
+}
+
 pub fn get_system_prompt() -> String {
     return get_and_set_env_var(
         "SYSTEM_PROMPT",
@@ -208,6 +413,33 @@ pub fn get_channels() -> Vec<u64> {
     return channels;
 }
 
+/// Channels told about quarantined rates, none when `ADMIN_CHANNELS` is not set
+pub fn get_admin_channels() -> Vec<u64> {
+    env::var("ADMIN_CHANNELS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|channel| !channel.is_empty())
+        .map(|channel| channel.parse().expect("ADMIN_CHANNELS must be channel ids"))
+        .collect()
+}
+
+/// Robust z-score of a daily change above which a fetched rate is quarantined
+pub fn get_anomaly_max_score() -> f64 {
+    get_and_set_env_var("ANOMALY_MAX_SCORE", "10")
+        .parse()
+        .ok()
+        .filter(|score| *score > 0.0)
+        .expect("ANOMALY_MAX_SCORE must be a positive number")
+}
+
+/// Working days the latest rates may be behind before they are stale
+pub fn get_anomaly_max_stale_days() -> usize {
+    get_and_set_env_var("ANOMALY_MAX_STALE_DAYS", "2")
+        .parse()
+        .expect("ANOMALY_MAX_STALE_DAYS must be a number of days")
+}
+
 pub fn get_discord_token() -> String {
     match env::var("DISCORD_TOKEN") {
         Ok(val) => val,
@@ -252,6 +484,115 @@ pub fn get_ollama_model() -> String {
     return get_and_set_env_var("OLLAMA_MODEL", "llama3.1");
 }
 
+/// `ollama`, `openai` (any OpenAI compatible `/v1/chat/completions` server)
+/// or `none` to only use the template narrative
+pub fn get_llm_backend() -> String {
+    get_and_set_env_var("LLM_BACKEND", "ollama")
+}
+
+pub fn get_openai_api_url() -> String {
+    match env::var("OPENAI_API_URL") {
+        Ok(val) => val,
+        Err(_) => {
+            // stop the program
+            panic!("OPENAI_API_URL not found in environment! Please set it in .env file or in the environment");
+        }
+    }
+}
+
+pub fn get_openai_api_key() -> Option<String> {
+    env::var("OPENAI_API_KEY").ok()
+}
+
+pub fn get_openai_model() -> String {
+    get_and_set_env_var("OPENAI_MODEL", "default")
+}
+
+/// Model used by the configured backend
+pub fn get_llm_model() -> String {
+    match get_llm_backend().to_lowercase().as_str() {
+        "none" => "N/A".to_string(),
+        "openai" => get_openai_model(),
+        _ => get_ollama_model(),
+    }
+}
+
+/// Whether the model may call tools (rates, history, statistics, news search)
+pub fn get_llm_tools_enabled() -> bool {
+    get_and_set_env_var("LLM_TOOLS", "false").to_lowercase() == "true"
+}
+
+/// Rounds of tool calls before the model has to answer
+pub fn get_llm_tool_max_steps() -> usize {
+    let steps_str = get_and_set_env_var("LLM_TOOL_MAX_STEPS", "4");
+    steps_str.parse().unwrap()
+}
+
+/// Longest response posted, Discord allows 2000 characters including the footer
+pub fn get_llm_max_length() -> usize {
+    get_and_set_env_var("LLM_MAX_LENGTH", "1500")
+        .parse()
+        .expect("LLM_MAX_LENGTH must be a number of characters")
+}
+
+/// Comma separated terms a response may not contain
+pub fn get_llm_blocklist() -> Vec<String> {
+    env::var("LLM_BLOCKLIST")
+        .unwrap_or_default()
+        .split(',')
+        .map(|term| term.trim().to_string())
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+/// Allowed relative difference between a quoted figure and the actual rate
+pub fn get_llm_number_tolerance() -> f64 {
+    get_and_set_env_var("LLM_NUMBER_TOLERANCE", "0.005")
+        .parse()
+        .expect("LLM_NUMBER_TOLERANCE must be a number")
+}
+
+/// Regenerations after a response failed the guardrails
+pub fn get_llm_guardrail_retries() -> usize {
+    get_and_set_env_var("LLM_GUARDRAIL_RETRIES", "1")
+        .parse()
+        .expect("LLM_GUARDRAIL_RETRIES must be a number")
+}
+
+/// Seconds a generated report is reused for the same rates and templates, 0 disables the cache
+pub fn get_llm_cache_ttl() -> u64 {
+    get_and_set_env_var("LLM_CACHE_TTL", "3600")
+        .parse()
+        .expect("LLM_CACHE_TTL must be a number of seconds")
+}
+
+/// Seconds a single LLM request may take before falling back to the template narrative
+pub fn get_llm_timeout() -> u64 {
+    let timeout_str = get_and_set_env_var("LLM_TIMEOUT", "300");
+    timeout_str.parse().unwrap()
+}
+
+/// Add `column` to `table` of databases created before the column existed
+fn add_column_if_missing(con: &Connection, table: &str, column: &str, definition: &str) {
+    let mut stmt = con
+        .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+        .unwrap();
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .unwrap()
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        log::info!("Adding column {} to {}", column, table);
+        con.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )
+        .unwrap();
+    }
+}
+
 fn ensure_db() {
     log::info!("Ensuring db");
     // get DB_FILE from environment
@@ -264,10 +605,22 @@ fn ensure_db() {
     con.execute(CREATE_EXCHANGE_RATE_API_RAW_TABLE_QUERY, [])
         .unwrap();
     con.execute(CREATE_LLM_RESULT_TABLE_QUERY, []).unwrap();
+    for (column, definition) in LLM_RESULT_CACHE_COLUMNS {
+        add_column_if_missing(&con, "llm_result", column, definition);
+    }
+    con.execute(CREATE_LLM_RESULT_CACHE_INDEX_QUERY, [])
+        .unwrap();
     con.execute(CREATE_HISTORICAL_DATA_TABLE_QUERY, []).unwrap();
     con.execute(CREATE_SEARCH_RESULT_TABLE_QUERY, []).unwrap();
     con.execute(CREATE_EXCHANGE_RATE_FALLBACK_TABLE_QUERY, [])
         .unwrap();
+    con.execute(CREATE_CONVERSATION_TABLE_QUERY, []).unwrap();
+    con.execute(CREATE_PROMPT_TEMPLATE_TABLE_QUERY, []).unwrap();
+    con.execute(CREATE_ARTICLE_TABLE_QUERY, []).unwrap();
+    con.execute(CREATE_GUILD_SETTING_TABLE_QUERY, []).unwrap();
+    con.execute(CREATE_QUARANTINED_EXCHANGE_RATE_TABLE_QUERY, [])
+        .unwrap();
+    con.execute(CREATE_BASKET_TABLE_QUERY, []).unwrap();
 }
 
 /// Ensure environment variables are set
@@ -276,5 +629,31 @@ pub async fn ensure_environment() {
     ensure_db();
     get_discord_token();
     // get_exchange_rate_api_key();
-    get_ollama_url();
+    match get_llm_backend().to_lowercase().as_str() {
+        "ollama" => {
+            get_ollama_url();
+        }
+        "openai" => {
+            get_openai_api_url();
+        }
+        "none" => {
+            log::info!("LLM_BACKEND is 'none', only the template narrative will be used");
+        }
+        backend => {
+            // stop the program
+            panic!("Unknown LLM_BACKEND '{backend}'! Expected 'ollama', 'openai' or 'none'");
+        }
+    }
+    crate::llm::prompt::ensure_templates();
+    get_chart_theme();
+    get_chart_mode();
+    get_chart_overlays();
+    get_chart_format();
+}
+
+/// Prepare what the evaluation needs, without the Discord settings
+pub fn ensure_eval_environment() {
+    log::info!("Ensuring evaluation environment");
+    ensure_db();
+    crate::llm::prompt::ensure_templates();
 }
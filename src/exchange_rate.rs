@@ -1,4 +1,4 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
 use serde_json::Value;
 use thiserror::Error;
 
@@ -8,13 +8,32 @@ use crate::{
         get_local_exchange_rate_fallback, save_exchange_rate_fallback,
         save_raw_exchange_rate_result,
     },
+    database::quarantine::quarantine_rates,
     environment::{self},
+    utils::{
+        anomaly::{get_missing, get_outliers, get_staleness, Anomaly},
+        basket::get_basket,
+        calendar::{get_events, CalendarEvent},
+        holidays::{get_closing_day, is_closing_day},
+    },
 };
 use std::{
     collections::HashMap,
     fmt::{self},
 };
 
+/// Provider of `EXCHANGE_RATE_API_URL`, as recorded with quarantined rates
+const MAIN_PROVIDER: &str = "main";
+
+/// Provider of `FALLBACK_EXCHANGE_RATE_API_URL`, as recorded with quarantined rates
+const FALLBACK_PROVIDER: &str = "fallback";
+
+/// Days of rates fetched at least, so that new rates have history to be checked against
+const ANOMALY_HISTORY_DAYS: i64 = 45;
+
+/// Most recent rates new rates are checked against
+const ANOMALY_HISTORY_RATES: usize = 30;
+
 #[derive(Debug, Clone)]
 pub struct ExchangeRateMap {
     pub datetime: DateTime<Utc>,
@@ -87,6 +106,9 @@ pub enum FetchExchangeRateError {
     // ShapeError(String),
     #[error("Missing API Key")]
     MissingKeyError,
+
+    #[error("The rates of {0} are quarantined: {1}")]
+    QuarantinedError(NaiveDate, String),
 }
 
 impl ExchangeRateMap {
@@ -95,6 +117,91 @@ impl ExchangeRateMap {
     }
 }
 
+/// Anomalies of `rates` fetched for `expected`, compared with the rates before them
+fn check_rates(
+    rates: &ExchangeRateMap,
+    history: &[ExchangeRateMap],
+    expected: NaiveDate,
+    events: &[CalendarEvent],
+) -> Vec<Anomaly> {
+    let history = &history[history.len().saturating_sub(ANOMALY_HISTORY_RATES)..];
+    let mut anomalies = get_outliers(rates, history, environment::get_anomaly_max_score());
+    anomalies.extend(get_missing(rates, history));
+    anomalies.extend(get_staleness(
+        rates.get_date(),
+        expected,
+        events,
+        environment::get_anomaly_max_stale_days(),
+    ));
+    anomalies
+}
+
+fn format_anomalies(anomalies: &[Anomaly]) -> String {
+    anomalies
+        .iter()
+        .map(|anomaly| anomaly.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// `rates` of the main provider fetched for `expected`, checked against `history`.
+///
+/// Rates with anomalies are quarantined and replaced by the rates of the fallback
+/// provider, when it has some without anomalies. Otherwise the currencies with
+/// wrong rates are left out, and rates dated in the future are not used at all.
+async fn screen_rates(
+    mut rates: ExchangeRateMap,
+    history: &[ExchangeRateMap],
+    expected: NaiveDate,
+    events: &[CalendarEvent],
+) -> Result<ExchangeRateMap, FetchExchangeRateError> {
+    let anomalies = check_rates(&rates, history, expected, events);
+    if anomalies.is_empty() {
+        return Ok(rates);
+    }
+    let reasons = format_anomalies(&anomalies);
+    quarantine_rates(&rates, MAIN_PROVIDER, &reasons);
+
+    match ExchangeRateMap::get_rate_fallback(expected).await {
+        Ok(fallback) => {
+            let fallback_anomalies = check_rates(&fallback, history, expected, events);
+            if fallback_anomalies.is_empty() {
+                log::info!("Using the fallback rates of {} instead", expected);
+                return Ok(fallback);
+            }
+            quarantine_rates(
+                &fallback,
+                FALLBACK_PROVIDER,
+                &format_anomalies(&fallback_anomalies),
+            );
+        }
+        Err(e) => log::warn!("No fallback rates of {}: {}", expected, e),
+    }
+
+    if anomalies
+        .iter()
+        .any(|anomaly| matches!(anomaly, Anomaly::Future(_)))
+    {
+        return Err(FetchExchangeRateError::QuarantinedError(
+            rates.get_date(),
+            reasons,
+        ));
+    }
+    for currency in anomalies.iter().filter_map(Anomaly::currency) {
+        rates.map.remove(currency);
+    }
+    Ok(rates)
+}
+
+/// Base currency asked from the APIs, EUR for baskets, which are computed from its rates
+fn get_api_base(base: Option<String>) -> String {
+    let base = base.unwrap_or("EUR".to_string()).to_uppercase();
+    match get_basket(&base) {
+        Some(_) => "EUR".to_string(),
+        None => base,
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ExchangeRateParserError {
     #[error("Failed to parse JSON: {0}")]
@@ -110,23 +217,59 @@ impl ExchangeRateMap {
         let from = from.to_uppercase();
         let to = to.to_uppercase();
 
-        // Set `from_value` or `to_value` to 1 if they match the base currency
-        let from_value = if from == self.base {
-            1.0
-        } else {
-            *self.map.get(&from)?
-        };
-
-        let to_value = if to == self.base {
-            1.0
-        } else {
-            *self.map.get(&to)?
-        };
+        let from_value = self.get_units(&from)?;
+        let to_value = self.get_units(&to)?;
 
         // Return the conversion rate
         Some(to_value / from_value)
     }
 
+    /// Units of `currency` one base currency buys, `currency` may be a basket
+    fn get_units(&self, currency: &str) -> Option<f64> {
+        // 1 if it is the base currency
+        if currency == self.base {
+            return Some(1.0);
+        }
+        match self.map.get(currency) {
+            Some(value) => Some(*value),
+            None => Some(1.0 / get_basket(currency)?.get_value(self, &self.base)?),
+        }
+    }
+
+    /// Rates of `base` on `date` (`YYYY-MM-DD`) from the `rates` object of the
+    /// main API, shared by its single day and time series answers
+    pub fn parse_day(
+        date: &str,
+        rates: &Value,
+        base: &str,
+    ) -> Result<ExchangeRateMap, ExchangeRateParserError> {
+        let date_with_time = format!("{}T00:00:00+00:00", date);
+        let datetime = DateTime::parse_from_rfc3339(&date_with_time).map_err(|e| {
+            ExchangeRateParserError::ShapeError(format!(
+                "Invalid date ({}): {:?}",
+                date_with_time, e
+            ))
+        })?;
+
+        let map: HashMap<String, f64> = rates
+            .as_object()
+            .ok_or_else(|| ExchangeRateParserError::ShapeError("rates in wrong shape".to_string()))?
+            .iter()
+            .map(|(k, v)| {
+                let value = v.as_f64().ok_or_else(|| {
+                    ExchangeRateParserError::ShapeError(format!("Invalid value for key: {}", k))
+                })?;
+                Ok((k.clone(), value))
+            })
+            .collect::<Result<HashMap<_, _>, ExchangeRateParserError>>()?;
+
+        Ok(ExchangeRateMap {
+            datetime: datetime.into(),
+            base: base.to_string(),
+            map,
+        })
+    }
+
     pub fn parse_fallback_json(json: &str) -> Result<ExchangeRateMap, ExchangeRateParserError> {
         let v: Value = match serde_json::from_str(&json) {
             Ok(v) => v,
@@ -240,17 +383,75 @@ impl ExchangeRateMap {
         Ok(m)
     }
 
+    /// Get the rates of a single date from the main API.
+    ///
+    /// The API answers with the closest earlier working day when `date` has no data.
+    pub async fn get_rate(
+        date: NaiveDate,
+        base: Option<String>,
+    ) -> Result<ExchangeRateMap, FetchExchangeRateError> {
+        let base: String = get_api_base(base);
+
+        let api_base = environment::get_exchange_rate_api_url();
+        let fetch_url = format!("{}/{}?base={}", api_base, date.format("%Y-%m-%d"), &base);
+
+        log::debug!("Fetching URL: {fetch_url}");
+
+        let response = reqwest::get(&fetch_url)
+            .await
+            .map_err(|e| FetchExchangeRateError::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(FetchExchangeRateError::RequestError(format!(
+                "Request failed with status: {}, URL: {}",
+                response.status(),
+                &fetch_url
+            )));
+        }
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| FetchExchangeRateError::ResponseBodyError(e.to_string()))?;
+
+        log::debug!("text: {}", text);
+
+        let parsed: Value = serde_json::from_str(&text).map_err(ExchangeRateParserError::from)?;
+        let date_text = parsed.get("date").and_then(|v| v.as_str()).ok_or_else(|| {
+            ExchangeRateParserError::ShapeError("date in wrong shape".to_string())
+        })?;
+        let rates = ExchangeRateMap::parse_day(date_text, &parsed["rates"], &base)?;
+
+        // Checked against the rates before it, which are checked when they are fetched
+        let since = date - Duration::days(ANOMALY_HISTORY_DAYS);
+        let history: Vec<ExchangeRateMap> =
+            match ExchangeRateMap::get_rates(since, Some(base)).await {
+                Ok(history) => history
+                    .into_iter()
+                    .filter(|history| history.get_date() < rates.get_date())
+                    .collect(),
+                Err(e) => {
+                    log::warn!("Cannot check the rates of {} against history: {}", date, e);
+                    vec![]
+                }
+            };
+        screen_rates(rates, &history, date, &get_events().await).await
+    }
+
     pub async fn get_rates(
         from_date: NaiveDate,
         base: Option<String>,
     ) -> Result<Vec<ExchangeRateMap>, FetchExchangeRateError> {
-        let base: String = base.unwrap_or("EUR".to_string()).to_uppercase();
+        let base: String = get_api_base(base);
+        let today = Utc::now().date_naive();
+        // Earlier rates are only fetched to check the first rates against
+        let fetch_date = from_date.min(today - Duration::days(ANOMALY_HISTORY_DAYS));
 
         let api_base = environment::get_exchange_rate_api_url();
         let fetch_url = format!(
             "{}/{}..?base={}",
             api_base,
-            from_date.format("%Y-%m-%d"),
+            fetch_date.format("%Y-%m-%d"),
             &base
         );
 
@@ -284,77 +485,76 @@ impl ExchangeRateMap {
         // Save raw results for backward compatibility reason.
         save_raw_exchange_rate_result(&text);
 
-        let parsed: Value = match serde_json::from_str(&text) {
-            Ok(v) => v,
-            Err(e) => {
-                return Err(FetchExchangeRateError::ParseError(
-                    ExchangeRateParserError::SerdeError(e),
-                ))
-            }
-        };
+        let parsed: Value = serde_json::from_str(&text).map_err(ExchangeRateParserError::from)?;
 
         let rates_raw = parsed
             .get("rates")
             .and_then(|v| v.as_object())
             .ok_or_else(|| {
-                FetchExchangeRateError::ParseError(ExchangeRateParserError::ShapeError(
-                    "rates in wrong shape".to_string(),
-                ))
+                ExchangeRateParserError::ShapeError("rates in wrong shape".to_string())
             })?;
 
         let mut rates: HashMap<NaiveDate, ExchangeRateMap> = HashMap::new();
-
         for (date, rate_map) in rates_raw {
-            let date_with_time = format!("{}T00:00:00+00:00", date);
-            let datetime = DateTime::parse_from_rfc3339(&date_with_time).map_err(|e| {
-                FetchExchangeRateError::ParseError(ExchangeRateParserError::ShapeError(format!(
-                    "Invalid date ({}): {:?}",
-                    date_with_time, e
-                )))
-            })?;
-
-            let rate_map = rate_map.as_object().ok_or_else(|| {
-                FetchExchangeRateError::ParseError(ExchangeRateParserError::ShapeError(
-                    "rate_map in wrong shape".to_string(),
-                ))
-            })?;
-
-            let map: HashMap<String, f64> = rate_map
-                .iter()
-                .map(|(k, v)| {
-                    let value = v.as_f64().ok_or_else(|| {
-                        FetchExchangeRateError::ParseError(ExchangeRateParserError::ShapeError(
-                            format!("Invalid value for key: {}", k),
-                        ))
-                    })?;
-                    Ok((k.clone(), value))
-                })
-                .collect::<Result<HashMap<_, _>, FetchExchangeRateError>>()?;
-            log::debug!("Map: {:?}", map);
-
-            rates.insert(
-                datetime.date_naive(),
-                ExchangeRateMap {
-                    datetime: datetime.into(),
-                    base: base.clone(),
-                    map,
-                },
-            );
+            let rate_map = ExchangeRateMap::parse_day(date, rate_map, &base)?;
+            log::debug!("Map: {:?}", rate_map.map);
+            rates.insert(rate_map.get_date(), rate_map);
         }
 
-        // Sort rates by datetime
-        // rates.sort_by(|a, b| a.datetime.cmp(&b.datetime));
+        // Every day is checked against the days before it, the latest one against today
+        let events = get_events().await;
+        let mut fetched: Vec<ExchangeRateMap> = rates.into_values().collect();
+        fetched.sort_by_key(|rates| rates.datetime);
+        let latest_date = fetched.last().map(|rates| rates.get_date());
+        let mut checked: Vec<ExchangeRateMap> = vec![];
+        for rates in fetched {
+            let date = rates.get_date();
+            let expected = match Some(date) == latest_date {
+                true => today,
+                false => date,
+            };
+            match screen_rates(rates, &checked, expected, &events).await {
+                Ok(rates) => checked.push(rates),
+                Err(e) => log::warn!("Leaving out the rates of {}: {}", date, e),
+            }
+        }
+        let mut rates: HashMap<NaiveDate, ExchangeRateMap> = checked
+            .into_iter()
+            .map(|rates| (rates.get_date(), rates))
+            .collect();
 
-        // The EU bank API doesn't have data on weekend.
+        // The EU bank API doesn't have data on weekends and holidays.
         // Fill in empty
-        let mut current_date = Utc::now().date_naive();
+        let mut current_date = today;
 
         while current_date >= from_date {
             if rates.get(&current_date).is_none() {
                 // Date is missing
                 match ExchangeRateMap::get_rate_fallback(current_date).await {
                     Ok(map) => {
-                        rates.insert(current_date, map);
+                        let mut history: Vec<ExchangeRateMap> = rates
+                            .values()
+                            .filter(|rates| rates.get_date() < current_date)
+                            .cloned()
+                            .collect();
+                        history.sort_by_key(|rates| rates.datetime);
+                        let anomalies = check_rates(&map, &history, current_date, &events);
+                        match anomalies.is_empty() {
+                            true => {
+                                rates.insert(current_date, map);
+                            }
+                            false => quarantine_rates(
+                                &map,
+                                FALLBACK_PROVIDER,
+                                &format_anomalies(&anomalies),
+                            ),
+                        }
+                    }
+                    Err(e) if is_closing_day(current_date, &events) => {
+                        log::debug!(
+                            "No rates on {current_date} ({}): {e}",
+                            get_closing_day(current_date, &events).unwrap_or("weekend".to_string())
+                        )
                     }
                     Err(e) => {
                         log::warn!("Error: {e}")
@@ -371,11 +571,80 @@ impl ExchangeRateMap {
             }
         }
 
+        // Closing days the fallback could not fill keep the last published
+        // rate, so that weekends and holidays leave no holes between rates
+        let last_date = rates.keys().max().copied().unwrap_or(from_date);
+        let mut previous: Option<ExchangeRateMap> = None;
+        let mut current_date = from_date;
+        while current_date < last_date {
+            match rates.get(&current_date) {
+                Some(map) => previous = Some(map.clone()),
+                None if is_closing_day(current_date, &events) => {
+                    if let Some(previous) = &previous {
+                        rates.insert(
+                            current_date,
+                            ExchangeRateMap {
+                                datetime: current_date.and_time(NaiveTime::MIN).and_utc(),
+                                ..previous.clone()
+                            },
+                        );
+                    }
+                }
+                None => {}
+            }
+            current_date = match current_date.succ_opt() {
+                Some(date) => date,
+                None => break,
+            };
+        }
+
         // Convert HashMap to Vec, sorting by the date (NaiveDate)
         let mut result_vec: Vec<ExchangeRateMap> = rates.into_iter().map(|(_, v)| v).collect();
 
         result_vec.sort_by(|a, b| a.datetime.cmp(&b.datetime));
 
+        // From the last rates on `from_date` or before, as if only they were fetched
+        let start_date = result_vec
+            .iter()
+            .map(|rates| rates.get_date())
+            .filter(|date| *date <= from_date)
+            .max()
+            .unwrap_or(from_date);
+        result_vec.retain(|rates| rates.get_date() >= start_date);
+
         Ok(result_vec)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_day() {
+        let single: Value =
+            serde_json::from_str(r#"{"date":"2024-11-11","rates":{"USD":1.07,"JPY":164.2}}"#)
+                .unwrap();
+        let series: Value =
+            serde_json::from_str(r#"{"rates":{"2024-11-11":{"USD":1.07,"JPY":164.2}}}"#).unwrap();
+
+        // Both answers of the main API give the same rates
+        let from_single =
+            ExchangeRateMap::parse_day(single["date"].as_str().unwrap(), &single["rates"], "EUR")
+                .unwrap();
+        let from_series =
+            ExchangeRateMap::parse_day("2024-11-11", &series["rates"]["2024-11-11"], "EUR")
+                .unwrap();
+        assert_eq!(from_single.datetime, from_series.datetime);
+        assert_eq!(from_single.map, from_series.map);
+        assert_eq!(
+            from_single.get_date(),
+            NaiveDate::from_ymd_opt(2024, 11, 11).unwrap()
+        );
+        assert_eq!(from_single.get_val("USD", "EUR"), Some(1.0 / 1.07));
+
+        let invalid: Value = serde_json::from_str(r#"{"USD":"1.07"}"#).unwrap();
+        assert!(ExchangeRateMap::parse_day("2024-11-11", &invalid, "EUR").is_err());
+        assert!(ExchangeRateMap::parse_day("11/11/2024", &single["rates"], "EUR").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use super::Catalog;
+
+pub const CATALOG: Catalog = Catalog {
+    language: "German",
+    date_format: "%d.%m.%Y",
+    decimal_separator: ',',
+    weekdays: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+
+    increase_prompt_template: r#"Heute ist der {CURR_DATE}. Verfasse einen kurzen Bericht für die Öffentlichkeit über einen gestiegenen Wechselkurs.
+- Seit dem {LAST_DATE} ist der Wechselkurs von {FROM} zu {TO} auf {CURR} gestiegen und liegt damit über dem vorherigen Kurs von {PREV}.
+- Der Anstieg beträgt {DIFF}.
+Fasse diese Informationen klar und knapp auf Deutsch zusammen."#,
+    decrease_prompt_template: r#"Heute ist der {CURR_DATE}. Verfasse einen kurzen Bericht für die Öffentlichkeit über einen gesunkenen Wechselkurs.
+- Seit dem {LAST_DATE} ist der Wechselkurs von {FROM} zu {TO} auf {CURR} gefallen und liegt damit unter dem vorherigen Kurs von {PREV}.
+- Der Rückgang beträgt {DIFF}.
+Fasse diese Informationen klar und knapp auf Deutsch zusammen."#,
+    equal_prompt_template: r#"Heute ist der {CURR_DATE}. Verfasse einen kurzen Bericht für die Öffentlichkeit über den aktuellen Wechselkurs.
+- Der Wechselkurs von {FROM} zu {TO} beträgt {CURR} und ist damit fast gleich dem vorherigen Kurs von {PREV} am {LAST_DATE}.
+Fasse diese Informationen klar und knapp auf Deutsch zusammen."#,
+
+    searched_in: "Gesucht in {SECONDS} Sekunden",
+    first_token_in: "Erstes Token nach {SECONDS} Sekunden",
+    evaluated_in: "Generiert in {SECONDS} Sekunden",
+    prompt_and_generated_tokens: "{PROMPT} Prompt- + {GENERATED} generierte Tokens",
+    generated_tokens: "{GENERATED} generierte Tokens",
+    tokens_per_second: "{SPEED} Tokens/s",
+    called_tools: "{COUNT} Werkzeuge aufgerufen",
+    cached_response: "Zwischengespeicherte Antwort vom {DATE} {TIME} UTC, mit refresh neu erstellen",
+    narrative_footer: "Text aus Vorlage erstellt",
+    narrative_llm_unavailable_footer: "Text aus Vorlage erstellt (LLM nicht verfügbar: {ERROR})",
+    narrative_rejected_footer: "Text aus Vorlage erstellt (LLM-Antwort abgelehnt: {ERROR})",
+    graph_generated_in: "Diagramm erstellt in {SECONDS} Sekunden",
+    graph_error: "Fehler beim Erstellen des Diagramms: {ERROR}",
+    generated_in: "Erstellt in {SECONDS} Sekunden",
+    api_error: "Fehler beim Abrufen der API. Bitte prüfe die API-URL und die Internetverbindung. Verwendete URL: `{URL}`\n`Fehler: {ERROR}`",
+
+    narrative_no_data: "Für {FROM} zu {TO} sind keine Wechselkursdaten verfügbar.",
+    narrative_unchanged: "Am {WEEKDAY}, {DATE}, ist 1 {FROM} {CURR} {TO} wert, praktisch unverändert gegenüber {PREV} am {LAST_DATE}.",
+    narrative_rose: "Am {WEEKDAY}, {DATE}, stieg {FROM} gegenüber {TO} {MAGNITUDE} um {DIFF} ({PCT} %), von {PREV} am {LAST_DATE} auf {CURR}.",
+    narrative_fell: "Am {WEEKDAY}, {DATE}, fiel {FROM} gegenüber {TO} {MAGNITUDE} um {DIFF} ({PCT} %), von {PREV} am {LAST_DATE} auf {CURR}.",
+    narrative_single: "Am {WEEKDAY}, {DATE}, ist 1 {FROM} {CURR} {TO} wert.",
+    narrative_magnitudes: ["minimal", "leicht", "spürbar", "kräftig"],
+    narrative_gains_streak: "Das ist der {COUNT}. Anstieg in Folge.",
+    narrative_losses_streak: "Das ist der {COUNT}. Rückgang in Folge.",
+    narrative_highest: "Das ist der höchste Stand seit {DAYS} Tagen.",
+    narrative_lowest: "Das ist der tiefste Stand seit {DAYS} Tagen.",
+    narrative_range: "Der Kurs liegt {BELOW} % unter seinem {DAYS}-Tage-Hoch von {HIGH} ({HIGH_DATE}) und {ABOVE} % über seinem Tief von {LOW} ({LOW_DATE}).",
+    narrative_weekend: "Es ist Wochenende, die Märkte sind geschlossen und der Kurs entspricht dem letzten Handelstag.",
+    narrative_week_start: "Eine neue Handelswoche hat begonnen.",
+    narrative_week_end: "Die Märkte gehen ins Wochenende.",
+    narrative_midweek: "Es ist {WEEKDAY}, mitten in der Handelswoche.",
+
+    llm_disabled: "Für Fragen wird ein Sprachmodell benötigt, das LLM ist aber deaktiviert.",
+    generation_error: "Fehler beim Erstellen der Antwort: {ERROR}",
+    no_stored_rates: "Es sind keine gespeicherten Wechselkurse von {FROM} zu {TO} verfügbar.",
+
+    calendar_title: "Anstehende Termine für {FROM}/{TO} in den nächsten {DAYS} Tagen:",
+    calendar_empty: "Keine Termine für {FROM}/{TO} in den nächsten {DAYS} Tagen.",
+    calendar_disabled: "Es ist kein Wirtschaftskalender eingerichtet.",
+
+    history_title: "{FROM} zu {TO} in den letzten {DAYS} Tagen",
+
+    compare_title: "{QUOTES} gegenüber {BASE} in den letzten {DAYS} Tagen, als prozentuale Veränderung seit dem ersten Tag",
+    compare_missing: "Keine Kurse für {CURRENCIES}.",
+    compare_invalid_range: "`{RANGE}` ist kein Zeitraum, verwenden Sie z. B. 90d, 12w, 6m oder 1y.",
+
+    matrix_title: "Kreuzkurse am {DATE}: eine Einheit jeder Zeile in der Währung jeder Spalte, gefärbt nach der Veränderung seit dem {PREVIOUS}",
+    matrix_title_no_change: "Kreuzkurse am {DATE}: eine Einheit jeder Zeile in der Währung jeder Spalte",
+    matrix_too_few: "Geben Sie mindestens zwei Währungen an, z. B. USD,EUR,GBP,JPY,CAD.",
+    matrix_missing: "Keine Kurse für {CURRENCIES}.",
+
+    stats_title: "**{FROM} zu {TO}** vom {START} bis {END}",
+    stats_range: "Aktuell {LATEST} ({CHANGE} % im Zeitraum), Hoch {HIGH}, Tief {LOW}, Durchschnitt {AVERAGE}",
+    stats_volatility: "Volatilität: {DAILY} % pro Tag, {ANNUAL} % pro Jahr",
+    stats_drawdown: "Größter Rückgang: {PERCENT} % von {PEAK} am {PEAK_DATE} auf {TROUGH} am {TROUGH_DATE}",
+    stats_streaks: "Längste Serien: {GAINS} Gewinne und {LOSSES} Verluste in Folge",
+    stats_z_score: "Letzte Veränderung: {Z_SCORE} Standardabweichungen vom durchschnittlichen Tag",
+    stats_correlation: "Korrelation der täglichen Veränderungen mit {FROM}/{VERSUS}: {CORRELATION}",
+    stats_correlation_recent: "Korrelation der täglichen Veränderungen mit {FROM}/{VERSUS}: {CORRELATION}, {RECENT} über die letzten {WINDOW} Veränderungen",
+    stats_no_correlation: "Nicht genug Kurse von {FROM}/{VERSUS} für eine Korrelation.",
+
+    forecast_title: "{MODEL}-Prognose für {FROM} zu {TO}: {VALUE} am {DATE}, mit 95 % Konfidenz zwischen {LOW} und {HIGH}",
+    forecast_backtest: "Backtest von {COUNT} Prognosen über {DAYS} Arbeitstage aus früheren Kursen:",
+    forecast_backtest_model: "- {MODEL}: {ERROR} % durchschnittlicher Fehler, {COVERAGE} % der Kurse im Intervall",
+    forecast_no_backtest: "Nicht genug Kurse für einen Backtest der Modelle.",
+    forecast_too_few: "Nicht genug Kurse von {FROM} zu {TO} für eine Prognose, mindestens {COUNT} sind nötig.",
+    forecast_disclaimer: "*Nur zur Orientierung, keine Finanzberatung.*",
+
+    unknown_subcommand: "Unbekannter Unterbefehl.",
+    rates_error: "Wechselkurse können nicht abgerufen werden: {ERROR}",
+    template_guild_only: "Vorlagen können nur in einem Server verwaltet werden.",
+    template_permission: "Du brauchst die Berechtigung „Server verwalten“, um Vorlagen zu verwalten.",
+    template_download_error: "`{FILE}` kann nicht heruntergeladen werden: {ERROR}",
+    template_not_text: "`{FILE}` ist keine UTF-8-Textdatei",
+    template_invalid: "Ungültige Vorlage: {ERROR}",
+    template_missing: "Gib die Vorlage mit der Option `template` oder `file` an.",
+    template_version: "Version {VERSION} von `{NAME}`:",
+    template_version_default: "Version {VERSION} von `{NAME}` verwendet die Standardvorlage.",
+    template_no_version: "`{NAME}` hat auf diesem Server keine Version {VERSION}.",
+    template_source_server: "Version {VERSION} dieses Servers",
+    template_source_default: "Standard",
+    template_not_set: "Nicht festgelegt.",
+    template_versions: "Versionen:",
+    template_version_by: "von {AUTHOR}",
+    template_version_reset: "zurück zur Standardvorlage",
+    template_saved: "Version {VERSION} von `{NAME}` gespeichert.",
+    template_unset: "`{NAME}` ist auf diesem Server nicht festgelegt.",
+    template_render_error: "Vorlage kann nicht gerendert werden: {ERROR}",
+    template_preview: "`{NAME}` mit {FROM} zu {TO}:",
+    template_reset: "`{NAME}` verwendet wieder die Standardvorlage (Version {VERSION}).",
+    template_reverted: "`{NAME}` auf Version {VERSION} zurückgesetzt (als Version {NEW_VERSION} gespeichert).",
+
+    preview_hidden: "Die Antwort wird vor dem Senden geprüft…",
+
+    theme_guild_only: "Designs können nur in einem Server gewählt werden.",
+    theme_permission: "Du brauchst die Berechtigung „Server verwalten“, um das Design zu wählen.",
+    theme_current: "Diagramme dieses Servers verwenden das Design `{THEME}`.",
+    theme_current_default: "Diagramme dieses Servers verwenden das Standarddesign.",
+    theme_set: "Diagramme dieses Servers verwenden jetzt das Design `{THEME}`.",
+    theme_reset: "Diagramme dieses Servers verwenden wieder das Standarddesign.",
+    theme_unknown: "`{THEME}` ist kein Design, wähle eines von {THEMES}.",
+
+    basket_permission: "Du brauchst die Berechtigung „Server verwalten“, um Körbe zu ändern.",
+    basket_admin_channel: "Körbe können nur in den Admin-Kanälen geändert werden.",
+    basket_none: "Es gibt noch keine Körbe, lege einen mit `/basket set` an.",
+    basket_list: "Körbe: {BASKETS}.",
+    basket_unknown: "Es gibt keinen Korb `{NAME}`.",
+    basket_description: "`{NAME}` ({KIND}): {SHARES}, Wert {VALUE} am {DATE}.",
+    basket_rates_missing: "Kurse fehlen",
+    basket_value_unknown: "unbekannt",
+    basket_saved: "Gespeichert: {BASKET}",
+    basket_deleted: "`{NAME}` gelöscht.",
+    basket_invalid_name: "`{NAME}` ist kein Korbname, verwende 4 bis 12 Buchstaben oder Ziffern.",
+    basket_invalid_component: "`{COMPONENT}` ist keine Währung mit Gewicht wie `USD:40`.",
+    basket_duplicate: "{CURRENCY} ist mehr als einmal im Korb.",
+    basket_empty: "Der Korb enthält keine Währungen.",
+    basket_no_rate: "Es gibt keinen Kurs für {CURRENCY}.",
+
+    chart_trend: "Wechselkursverlauf: {FROM} zu {TO}",
+    chart_candles_day: "Wechselkurs täglich: {FROM} zu {TO}",
+    chart_candles_week: "Wechselkurs wöchentlich: {FROM} zu {TO}",
+    chart_candles_month: "Wechselkurs monatlich: {FROM} zu {TO}",
+    chart_forecast: "Prognose: {FROM} zu {TO} ({MODEL})",
+    chart_comparison: "Änderung gegenüber {BASE} seit {DATE}",
+    chart_cross_rates: "Kreuzkurse am {DATE}",
+    chart_cross_rates_change: "Kreuzkurse am {DATE}, Änderung seit {PREVIOUS}",
+    chart_rate: "Wechselkurs",
+    chart_change: "Änderung (%)",
+    chart_date: "Datum",
+    chart_pair: "{FROM} zu {TO}",
+    chart_interval: "95%-Intervall",
+    chart_forecast_line: "Prognose",
+    chart_disclaimer: "Unverbindliche Prognose, keine Finanzberatung",
+    chart_min: "Min.",
+    chart_max: "Max.",
+    chart_last: "zuletzt",
+
+    quarantine_title: "Zurückgehaltene Wechselkurse:",
+    quarantine_item: "- {DATE} {BASE} von {PROVIDER}: {REASONS}",
+};
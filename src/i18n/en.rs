@@ -0,0 +1,168 @@
+use super::Catalog;
+
+pub const CATALOG: Catalog = Catalog {
+    language: "English",
+    date_format: "%Y-%m-%d",
+    decimal_separator: '.',
+    weekdays: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+
+    increase_prompt_template: r#"Today is {CURR_DATE}. Provide a brief report for the public about an increase in exchange rates.
+- Since {LAST_DATE}, the exchange rate from {FROM} to {TO} has risen to {CURR}, which is higher than the previous rate of {PREV}.
+- The increase in value is {DIFF}.
+Summarize this information clearly and concisely."#,
+    decrease_prompt_template: r#"Today is {CURR_DATE}. Provide a brief report for the public about a decrease in exchange rates.
+- Since {LAST_DATE}, the exchange rate from {FROM} to {TO} has dropped to {CURR}, which is lower than the previous rate of {PREV}.
+- The decrease in value is {DIFF}.
+Summarize this information clearly and concisely."#,
+    equal_prompt_template: r#"Today is {CURR_DATE}. Provide a brief report for the public on the current exchange rate.
+- The exchange rate from {FROM} to {TO} is {CURR}, which is approximately the same as the previous rate of {PREV} on {LAST_DATE}.
+Summarize this information clearly and concisely."#,
+
+    searched_in: "Searched in {SECONDS} seconds",
+    first_token_in: "First token in {SECONDS} seconds",
+    evaluated_in: "Evaluated in {SECONDS} seconds",
+    prompt_and_generated_tokens: "{PROMPT} prompt + {GENERATED} generated tokens",
+    generated_tokens: "{GENERATED} generated tokens",
+    tokens_per_second: "{SPEED} tokens/s",
+    called_tools: "Called {COUNT} tools",
+    cached_response: "Cached response from {DATE} {TIME} UTC, use refresh to regenerate",
+    narrative_footer: "Narrative generated from template",
+    narrative_llm_unavailable_footer: "Narrative generated from template (LLM unavailable: {ERROR})",
+    narrative_rejected_footer: "Narrative generated from template (LLM response rejected: {ERROR})",
+    graph_generated_in: "Graph generated in {SECONDS} seconds",
+    graph_error: "Graph generation error: {ERROR}",
+    generated_in: "Generated in {SECONDS} seconds",
+    api_error: "Error fetching API. Please verify the API URL and Internet connection. URL used: `{URL}`\n`Error: {ERROR}`",
+
+    narrative_no_data: "No exchange rate data is available for {FROM} to {TO}.",
+    narrative_unchanged: "On {WEEKDAY} {DATE}, 1 {FROM} is worth {CURR} {TO}, practically unchanged from {PREV} on {LAST_DATE}.",
+    narrative_rose: "On {WEEKDAY} {DATE}, {FROM} rose {MAGNITUDE} against {TO} by {DIFF} ({PCT}%), moving from {PREV} on {LAST_DATE} to {CURR}.",
+    narrative_fell: "On {WEEKDAY} {DATE}, {FROM} fell {MAGNITUDE} against {TO} by {DIFF} ({PCT}%), moving from {PREV} on {LAST_DATE} to {CURR}.",
+    narrative_single: "On {WEEKDAY} {DATE}, 1 {FROM} is worth {CURR} {TO}.",
+    narrative_magnitudes: ["marginally", "slightly", "noticeably", "sharply"],
+    narrative_gains_streak: "That makes {COUNT} gains in a row.",
+    narrative_losses_streak: "That makes {COUNT} losses in a row.",
+    narrative_highest: "This is the highest level in {DAYS} days.",
+    narrative_lowest: "This is the lowest level in {DAYS} days.",
+    narrative_range: "The rate sits {BELOW}% below its {DAYS}-day high of {HIGH} ({HIGH_DATE}) and {ABOVE}% above its low of {LOW} ({LOW_DATE}).",
+    narrative_weekend: "It is the weekend, so markets are closed and the rate reflects the last trading session.",
+    narrative_week_start: "A fresh trading week is underway.",
+    narrative_week_end: "Markets are heading into the weekend.",
+    narrative_midweek: "It is a {WEEKDAY} in the middle of the trading week.",
+
+    llm_disabled: "Questions need a language model, but the LLM is disabled.",
+    generation_error: "Error generating response: {ERROR}",
+    no_stored_rates: "No stored exchange rates from {FROM} to {TO} are available.",
+
+    calendar_title: "Upcoming events for {FROM}/{TO} in the next {DAYS} days:",
+    calendar_empty: "No events for {FROM}/{TO} in the next {DAYS} days.",
+    calendar_disabled: "No economic calendar is configured.",
+
+    history_title: "{FROM} to {TO} over the last {DAYS} days",
+
+    compare_title: "{QUOTES} against {BASE} over the last {DAYS} days, in percent change since the first day",
+    compare_missing: "No rates for {CURRENCIES}.",
+    compare_invalid_range: "`{RANGE}` is not a range, use e.g. 90d, 12w, 6m or 1y.",
+
+    matrix_title: "Cross rates on {DATE}: one unit of each row in the currency of each column, colored by the change since {PREVIOUS}",
+    matrix_title_no_change: "Cross rates on {DATE}: one unit of each row in the currency of each column",
+    matrix_too_few: "Give at least two currencies, e.g. USD,EUR,GBP,JPY,CAD.",
+    matrix_missing: "No rates for {CURRENCIES}.",
+
+    stats_title: "**{FROM} to {TO}** from {START} to {END}",
+    stats_range: "Latest {LATEST} ({CHANGE}% over the range), high {HIGH}, low {LOW}, average {AVERAGE}",
+    stats_volatility: "Volatility: {DAILY}% a day, {ANNUAL}% a year",
+    stats_drawdown: "Largest fall: {PERCENT}% from {PEAK} on {PEAK_DATE} to {TROUGH} on {TROUGH_DATE}",
+    stats_streaks: "Longest streaks: {GAINS} gains and {LOSSES} losses in a row",
+    stats_z_score: "Latest change: {Z_SCORE} standard deviations from the average day",
+    stats_correlation: "Correlation of the daily changes with {FROM}/{VERSUS}: {CORRELATION}",
+    stats_correlation_recent: "Correlation of the daily changes with {FROM}/{VERSUS}: {CORRELATION}, {RECENT} over the last {WINDOW} changes",
+    stats_no_correlation: "Not enough rates of {FROM}/{VERSUS} for a correlation.",
+
+    forecast_title: "{MODEL} forecast of {FROM} to {TO}: {VALUE} on {DATE}, between {LOW} and {HIGH} with 95% confidence",
+    forecast_backtest: "Backtest of {COUNT} forecasts of {DAYS} working days made from earlier rates:",
+    forecast_backtest_model: "- {MODEL}: {ERROR}% average error, {COVERAGE}% of the rates within the interval",
+    forecast_no_backtest: "Not enough rates to backtest the models.",
+    forecast_too_few: "Not enough rates of {FROM} to {TO} for a forecast, at least {COUNT} are needed.",
+    forecast_disclaimer: "*Indicative only, not financial advice.*",
+
+    unknown_subcommand: "Unknown subcommand.",
+    rates_error: "Cannot get exchange rates: {ERROR}",
+    template_guild_only: "Templates can only be managed in a server.",
+    template_permission: "You need the Manage Server permission to manage templates.",
+    template_download_error: "Cannot download `{FILE}`: {ERROR}",
+    template_not_text: "`{FILE}` is not a UTF-8 text file",
+    template_invalid: "Invalid template: {ERROR}",
+    template_missing: "Give the template with the `template` or `file` option.",
+    template_version: "Version {VERSION} of `{NAME}`:",
+    template_version_default: "Version {VERSION} of `{NAME}` uses the default template.",
+    template_no_version: "`{NAME}` has no version {VERSION} on this server.",
+    template_source_server: "version {VERSION} of this server",
+    template_source_default: "default",
+    template_not_set: "Not set.",
+    template_versions: "Versions:",
+    template_version_by: "by {AUTHOR}",
+    template_version_reset: "back to the default",
+    template_saved: "Saved version {VERSION} of `{NAME}`.",
+    template_unset: "`{NAME}` is not set on this server.",
+    template_render_error: "Cannot render template: {ERROR}",
+    template_preview: "`{NAME}` with {FROM} to {TO}:",
+    template_reset: "`{NAME}` uses the default template again (version {VERSION}).",
+    template_reverted: "Reverted `{NAME}` to version {VERSION} (saved as version {NEW_VERSION}).",
+
+    preview_hidden: "Checking the response before it is posted…",
+
+    theme_guild_only: "Themes can only be chosen in a server.",
+    theme_permission: "You need the Manage Server permission to choose the theme.",
+    theme_current: "Charts of this server use the `{THEME}` theme.",
+    theme_current_default: "Charts of this server use the default theme.",
+    theme_set: "Charts of this server now use the `{THEME}` theme.",
+    theme_reset: "Charts of this server use the default theme again.",
+    theme_unknown: "`{THEME}` is not a theme, choose one of {THEMES}.",
+
+    basket_permission: "You need the Manage Server permission to change baskets.",
+    basket_admin_channel: "Baskets can only be changed in the admin channels.",
+    basket_none: "There are no baskets yet, define one with `/basket set`.",
+    basket_list: "Baskets: {BASKETS}.",
+    basket_unknown: "There is no basket `{NAME}`.",
+    basket_description: "`{NAME}` ({KIND}): {SHARES}, worth {VALUE} on {DATE}.",
+    basket_rates_missing: "rates missing",
+    basket_value_unknown: "unknown",
+    basket_saved: "Saved {BASKET}",
+    basket_deleted: "Deleted `{NAME}`.",
+    basket_invalid_name: "`{NAME}` is not a basket name, use 4 to 12 letters or digits.",
+    basket_invalid_component: "`{COMPONENT}` is not a currency and a weight such as `USD:40`.",
+    basket_duplicate: "{CURRENCY} is in the basket more than once.",
+    basket_empty: "The basket has no currencies.",
+    basket_no_rate: "There is no rate of {CURRENCY}.",
+
+    chart_trend: "Exchange Rate Trend: {FROM} to {TO}",
+    chart_candles_day: "Exchange Rate Daily: {FROM} to {TO}",
+    chart_candles_week: "Exchange Rate Weekly: {FROM} to {TO}",
+    chart_candles_month: "Exchange Rate Monthly: {FROM} to {TO}",
+    chart_forecast: "Forecast: {FROM} to {TO} ({MODEL})",
+    chart_comparison: "Change against {BASE} since {DATE}",
+    chart_cross_rates: "Cross Rates on {DATE}",
+    chart_cross_rates_change: "Cross Rates on {DATE}, change since {PREVIOUS}",
+    chart_rate: "Exchange Rate",
+    chart_change: "Change (%)",
+    chart_date: "Date",
+    chart_pair: "{FROM} to {TO}",
+    chart_interval: "95% interval",
+    chart_forecast_line: "Forecast",
+    chart_disclaimer: "Indicative forecast, not financial advice",
+    chart_min: "min",
+    chart_max: "max",
+    chart_last: "last",
+
+    quarantine_title: "Quarantined exchange rates:",
+    quarantine_item: "- {DATE} {BASE} from {PROVIDER}: {REASONS}",
+};
@@ -0,0 +1,168 @@
+use super::Catalog;
+
+pub const CATALOG: Catalog = Catalog {
+    language: "Spanish",
+    date_format: "%d/%m/%Y",
+    decimal_separator: ',',
+    weekdays: [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+
+    increase_prompt_template: r#"Hoy es {CURR_DATE}. Redacta un breve informe para el público sobre una subida del tipo de cambio.
+- Desde el {LAST_DATE}, el tipo de cambio de {FROM} a {TO} ha subido a {CURR}, por encima del tipo anterior de {PREV}.
+- La subida es de {DIFF}.
+Resume esta información de forma clara y concisa en español."#,
+    decrease_prompt_template: r#"Hoy es {CURR_DATE}. Redacta un breve informe para el público sobre una bajada del tipo de cambio.
+- Desde el {LAST_DATE}, el tipo de cambio de {FROM} a {TO} ha bajado a {CURR}, por debajo del tipo anterior de {PREV}.
+- La bajada es de {DIFF}.
+Resume esta información de forma clara y concisa en español."#,
+    equal_prompt_template: r#"Hoy es {CURR_DATE}. Redacta un breve informe para el público sobre el tipo de cambio actual.
+- El tipo de cambio de {FROM} a {TO} es {CURR}, prácticamente igual al tipo anterior de {PREV} del {LAST_DATE}.
+Resume esta información de forma clara y concisa en español."#,
+
+    searched_in: "Búsqueda en {SECONDS} segundos",
+    first_token_in: "Primer token en {SECONDS} segundos",
+    evaluated_in: "Generado en {SECONDS} segundos",
+    prompt_and_generated_tokens: "{PROMPT} tokens de prompt + {GENERATED} generados",
+    generated_tokens: "{GENERATED} tokens generados",
+    tokens_per_second: "{SPEED} tokens/s",
+    called_tools: "{COUNT} herramientas usadas",
+    cached_response: "Respuesta guardada del {DATE} {TIME} UTC, usa refresh para regenerarla",
+    narrative_footer: "Texto generado a partir de una plantilla",
+    narrative_llm_unavailable_footer: "Texto generado a partir de una plantilla (LLM no disponible: {ERROR})",
+    narrative_rejected_footer: "Texto generado a partir de una plantilla (respuesta del LLM rechazada: {ERROR})",
+    graph_generated_in: "Gráfico generado en {SECONDS} segundos",
+    graph_error: "Error al generar el gráfico: {ERROR}",
+    generated_in: "Generado en {SECONDS} segundos",
+    api_error: "Error al consultar la API. Comprueba la URL de la API y la conexión a Internet. URL usada: `{URL}`\n`Error: {ERROR}`",
+
+    narrative_no_data: "No hay datos del tipo de cambio de {FROM} a {TO}.",
+    narrative_unchanged: "El {WEEKDAY} {DATE}, 1 {FROM} vale {CURR} {TO}, prácticamente igual que {PREV} el {LAST_DATE}.",
+    narrative_rose: "El {WEEKDAY} {DATE}, el {FROM} subió {MAGNITUDE} frente al {TO} en {DIFF} ({PCT} %), pasando de {PREV} el {LAST_DATE} a {CURR}.",
+    narrative_fell: "El {WEEKDAY} {DATE}, el {FROM} bajó {MAGNITUDE} frente al {TO} en {DIFF} ({PCT} %), pasando de {PREV} el {LAST_DATE} a {CURR}.",
+    narrative_single: "El {WEEKDAY} {DATE}, 1 {FROM} vale {CURR} {TO}.",
+    narrative_magnitudes: ["mínimamente", "ligeramente", "notablemente", "con fuerza"],
+    narrative_gains_streak: "Acumula {COUNT} subidas seguidas.",
+    narrative_losses_streak: "Acumula {COUNT} bajadas seguidas.",
+    narrative_highest: "Es el nivel más alto en {DAYS} días.",
+    narrative_lowest: "Es el nivel más bajo en {DAYS} días.",
+    narrative_range: "El tipo está un {BELOW} % por debajo de su máximo de {DAYS} días de {HIGH} ({HIGH_DATE}) y un {ABOVE} % por encima de su mínimo de {LOW} ({LOW_DATE}).",
+    narrative_weekend: "Es fin de semana, los mercados están cerrados y el tipo refleja la última sesión.",
+    narrative_week_start: "Comienza una nueva semana de negociación.",
+    narrative_week_end: "Los mercados se acercan al fin de semana.",
+    narrative_midweek: "Es {WEEKDAY}, en plena semana de negociación.",
+
+    llm_disabled: "Las preguntas necesitan un modelo de lenguaje, pero el LLM está desactivado.",
+    generation_error: "Error al generar la respuesta: {ERROR}",
+    no_stored_rates: "No hay tipos de cambio guardados de {FROM} a {TO}.",
+
+    calendar_title: "Próximos eventos de {FROM}/{TO} en los próximos {DAYS} días:",
+    calendar_empty: "No hay eventos de {FROM}/{TO} en los próximos {DAYS} días.",
+    calendar_disabled: "No hay ningún calendario económico configurado.",
+
+    history_title: "{FROM} a {TO} en los últimos {DAYS} días",
+
+    compare_title: "{QUOTES} frente a {BASE} en los últimos {DAYS} días, en variación porcentual desde el primer día",
+    compare_missing: "No hay tipos para {CURRENCIES}.",
+    compare_invalid_range: "`{RANGE}` no es un periodo, use p. ej. 90d, 12w, 6m o 1y.",
+
+    matrix_title: "Tipos cruzados del {DATE}: una unidad de cada fila en la moneda de cada columna, coloreados según la variación desde el {PREVIOUS}",
+    matrix_title_no_change: "Tipos cruzados del {DATE}: una unidad de cada fila en la moneda de cada columna",
+    matrix_too_few: "Indique al menos dos monedas, p. ej. USD,EUR,GBP,JPY,CAD.",
+    matrix_missing: "No hay tipos para {CURRENCIES}.",
+
+    stats_title: "**{FROM} a {TO}** del {START} al {END}",
+    stats_range: "Último {LATEST} ({CHANGE} % en el periodo), máximo {HIGH}, mínimo {LOW}, media {AVERAGE}",
+    stats_volatility: "Volatilidad: {DAILY} % al día, {ANNUAL} % al año",
+    stats_drawdown: "Mayor caída: {PERCENT} % de {PEAK} el {PEAK_DATE} a {TROUGH} el {TROUGH_DATE}",
+    stats_streaks: "Rachas más largas: {GAINS} subidas y {LOSSES} bajadas seguidas",
+    stats_z_score: "Última variación: {Z_SCORE} desviaciones típicas del día medio",
+    stats_correlation: "Correlación de las variaciones diarias con {FROM}/{VERSUS}: {CORRELATION}",
+    stats_correlation_recent: "Correlación de las variaciones diarias con {FROM}/{VERSUS}: {CORRELATION}, {RECENT} en las últimas {WINDOW} variaciones",
+    stats_no_correlation: "No hay suficientes tipos de {FROM}/{VERSUS} para una correlación.",
+
+    forecast_title: "Previsión {MODEL} de {FROM} a {TO}: {VALUE} el {DATE}, entre {LOW} y {HIGH} con un 95 % de confianza",
+    forecast_backtest: "Backtest de {COUNT} previsiones de {DAYS} días hábiles hechas con tipos anteriores:",
+    forecast_backtest_model: "- {MODEL}: {ERROR} % de error medio, {COVERAGE} % de los tipos dentro del intervalo",
+    forecast_no_backtest: "No hay suficientes tipos para un backtest de los modelos.",
+    forecast_too_few: "No hay suficientes tipos de {FROM} a {TO} para una previsión, se necesitan al menos {COUNT}.",
+    forecast_disclaimer: "*Solo orientativo, no es asesoramiento financiero.*",
+
+    unknown_subcommand: "Subcomando desconocido.",
+    rates_error: "No se pueden obtener los tipos de cambio: {ERROR}",
+    template_guild_only: "Las plantillas solo se pueden gestionar en un servidor.",
+    template_permission: "Necesitas el permiso Gestionar servidor para gestionar plantillas.",
+    template_download_error: "No se puede descargar `{FILE}`: {ERROR}",
+    template_not_text: "`{FILE}` no es un archivo de texto UTF-8",
+    template_invalid: "Plantilla no válida: {ERROR}",
+    template_missing: "Indica la plantilla con la opción `template` o `file`.",
+    template_version: "Versión {VERSION} de `{NAME}`:",
+    template_version_default: "La versión {VERSION} de `{NAME}` usa la plantilla predeterminada.",
+    template_no_version: "`{NAME}` no tiene la versión {VERSION} en este servidor.",
+    template_source_server: "versión {VERSION} de este servidor",
+    template_source_default: "predeterminada",
+    template_not_set: "Sin definir.",
+    template_versions: "Versiones:",
+    template_version_by: "por {AUTHOR}",
+    template_version_reset: "de vuelta a la predeterminada",
+    template_saved: "Se guardó la versión {VERSION} de `{NAME}`.",
+    template_unset: "`{NAME}` no está definida en este servidor.",
+    template_render_error: "No se puede generar la plantilla: {ERROR}",
+    template_preview: "`{NAME}` con {FROM} a {TO}:",
+    template_reset: "`{NAME}` vuelve a usar la plantilla predeterminada (versión {VERSION}).",
+    template_reverted: "`{NAME}` se restauró a la versión {VERSION} (guardada como versión {NEW_VERSION}).",
+
+    preview_hidden: "Revisando la respuesta antes de publicarla…",
+
+    theme_guild_only: "Los temas solo se pueden elegir en un servidor.",
+    theme_permission: "Necesitas el permiso Gestionar servidor para elegir el tema.",
+    theme_current: "Los gráficos de este servidor usan el tema `{THEME}`.",
+    theme_current_default: "Los gráficos de este servidor usan el tema predeterminado.",
+    theme_set: "Los gráficos de este servidor ahora usan el tema `{THEME}`.",
+    theme_reset: "Los gráficos de este servidor vuelven a usar el tema predeterminado.",
+    theme_unknown: "`{THEME}` no es un tema, elige uno de {THEMES}.",
+
+    basket_permission: "Necesitas el permiso Gestionar servidor para cambiar cestas.",
+    basket_admin_channel: "Las cestas solo se pueden cambiar en los canales de administración.",
+    basket_none: "Todavía no hay cestas, define una con `/basket set`.",
+    basket_list: "Cestas: {BASKETS}.",
+    basket_unknown: "No existe la cesta `{NAME}`.",
+    basket_description: "`{NAME}` ({KIND}): {SHARES}, vale {VALUE} el {DATE}.",
+    basket_rates_missing: "faltan tipos de cambio",
+    basket_value_unknown: "desconocido",
+    basket_saved: "Guardada: {BASKET}",
+    basket_deleted: "Se eliminó `{NAME}`.",
+    basket_invalid_name: "`{NAME}` no es un nombre de cesta, usa de 4 a 12 letras o dígitos.",
+    basket_invalid_component: "`{COMPONENT}` no es una moneda con un peso como `USD:40`.",
+    basket_duplicate: "{CURRENCY} está más de una vez en la cesta.",
+    basket_empty: "La cesta no tiene monedas.",
+    basket_no_rate: "No hay tipo de cambio de {CURRENCY}.",
+
+    chart_trend: "Tendencia del tipo de cambio: {FROM} a {TO}",
+    chart_candles_day: "Tipo de cambio diario: {FROM} a {TO}",
+    chart_candles_week: "Tipo de cambio semanal: {FROM} a {TO}",
+    chart_candles_month: "Tipo de cambio mensual: {FROM} a {TO}",
+    chart_forecast: "Pronóstico: {FROM} a {TO} ({MODEL})",
+    chart_comparison: "Variación frente a {BASE} desde el {DATE}",
+    chart_cross_rates: "Tipos cruzados del {DATE}",
+    chart_cross_rates_change: "Tipos cruzados del {DATE}, variación desde el {PREVIOUS}",
+    chart_rate: "Tipo de cambio",
+    chart_change: "Variación (%)",
+    chart_date: "Fecha",
+    chart_pair: "{FROM} a {TO}",
+    chart_interval: "Intervalo del 95%",
+    chart_forecast_line: "Pronóstico",
+    chart_disclaimer: "Pronóstico orientativo, no es asesoramiento financiero",
+    chart_min: "mín.",
+    chart_max: "máx.",
+    chart_last: "último",
+
+    quarantine_title: "Tipos de cambio en cuarentena:",
+    quarantine_item: "- {DATE} {BASE} de {PROVIDER}: {REASONS}",
+};
@@ -0,0 +1,168 @@
+use super::Catalog;
+
+pub const CATALOG: Catalog = Catalog {
+    language: "Hindi",
+    date_format: "%d/%m/%Y",
+    decimal_separator: '.',
+    weekdays: [
+        "सोमवार",
+        "मंगलवार",
+        "बुधवार",
+        "गुरुवार",
+        "शुक्रवार",
+        "शनिवार",
+        "रविवार",
+    ],
+
+    increase_prompt_template: r#"आज {CURR_DATE} है। विनिमय दर में वृद्धि के बारे में जनता के लिए एक संक्षिप्त रिपोर्ट लिखें।
+- {LAST_DATE} से {FROM} से {TO} की विनिमय दर बढ़कर {CURR} हो गई है, जो पिछली दर {PREV} से अधिक है।
+- वृद्धि {DIFF} है।
+इस जानकारी को हिंदी में स्पष्ट और संक्षेप में प्रस्तुत करें।"#,
+    decrease_prompt_template: r#"आज {CURR_DATE} है। विनिमय दर में गिरावट के बारे में जनता के लिए एक संक्षिप्त रिपोर्ट लिखें।
+- {LAST_DATE} से {FROM} से {TO} की विनिमय दर घटकर {CURR} हो गई है, जो पिछली दर {PREV} से कम है।
+- गिरावट {DIFF} है।
+इस जानकारी को हिंदी में स्पष्ट और संक्षेप में प्रस्तुत करें।"#,
+    equal_prompt_template: r#"आज {CURR_DATE} है। वर्तमान विनिमय दर के बारे में जनता के लिए एक संक्षिप्त रिपोर्ट लिखें।
+- {FROM} से {TO} की विनिमय दर {CURR} है, जो {LAST_DATE} की पिछली दर {PREV} के लगभग बराबर है।
+इस जानकारी को हिंदी में स्पष्ट और संक्षेप में प्रस्तुत करें।"#,
+
+    searched_in: "{SECONDS} सेकंड में खोजा गया",
+    first_token_in: "पहला टोकन {SECONDS} सेकंड में",
+    evaluated_in: "{SECONDS} सेकंड में तैयार",
+    prompt_and_generated_tokens: "{PROMPT} प्रॉम्प्ट + {GENERATED} उत्पन्न टोकन",
+    generated_tokens: "{GENERATED} उत्पन्न टोकन",
+    tokens_per_second: "{SPEED} टोकन/सेकंड",
+    called_tools: "{COUNT} टूल इस्तेमाल किए गए",
+    cached_response: "{DATE} {TIME} UTC का सहेजा गया उत्तर, दोबारा बनाने के लिए refresh का उपयोग करें",
+    narrative_footer: "टेम्पलेट से बनाया गया विवरण",
+    narrative_llm_unavailable_footer: "टेम्पलेट से बनाया गया विवरण (LLM उपलब्ध नहीं: {ERROR})",
+    narrative_rejected_footer: "टेम्पलेट से बनाया गया विवरण (LLM का उत्तर अस्वीकार: {ERROR})",
+    graph_generated_in: "ग्राफ़ {SECONDS} सेकंड में बना",
+    graph_error: "ग्राफ़ बनाने में त्रुटि: {ERROR}",
+    generated_in: "कुल {SECONDS} सेकंड",
+    api_error: "API से डेटा लाने में त्रुटि। कृपया API URL और इंटरनेट कनेक्शन जाँचें। इस्तेमाल किया गया URL: `{URL}`\n`त्रुटि: {ERROR}`",
+
+    narrative_no_data: "{FROM} से {TO} के लिए विनिमय दर का कोई डेटा उपलब्ध नहीं है।",
+    narrative_unchanged: "{WEEKDAY} {DATE} को 1 {FROM} का मूल्य {CURR} {TO} है, जो {LAST_DATE} के {PREV} से लगभग अपरिवर्तित है।",
+    narrative_rose: "{WEEKDAY} {DATE} को {FROM}, {TO} के मुकाबले {MAGNITUDE} {DIFF} ({PCT}%) बढ़ा, {LAST_DATE} के {PREV} से {CURR} पर पहुँचा।",
+    narrative_fell: "{WEEKDAY} {DATE} को {FROM}, {TO} के मुकाबले {MAGNITUDE} {DIFF} ({PCT}%) गिरा, {LAST_DATE} के {PREV} से {CURR} पर पहुँचा।",
+    narrative_single: "{WEEKDAY} {DATE} को 1 {FROM} का मूल्य {CURR} {TO} है।",
+    narrative_magnitudes: ["मामूली रूप से", "थोड़ा", "उल्लेखनीय रूप से", "तेज़ी से"],
+    narrative_gains_streak: "यह लगातार {COUNT}वीं बढ़त है।",
+    narrative_losses_streak: "यह लगातार {COUNT}वीं गिरावट है।",
+    narrative_highest: "यह {DAYS} दिनों का उच्चतम स्तर है।",
+    narrative_lowest: "यह {DAYS} दिनों का निम्नतम स्तर है।",
+    narrative_range: "दर अपने {DAYS} दिनों के उच्चतम स्तर {HIGH} ({HIGH_DATE}) से {BELOW}% नीचे और निम्नतम स्तर {LOW} ({LOW_DATE}) से {ABOVE}% ऊपर है।",
+    narrative_weekend: "सप्ताहांत है, बाज़ार बंद हैं और दर पिछले कारोबारी सत्र की है।",
+    narrative_week_start: "नया कारोबारी सप्ताह शुरू हो गया है।",
+    narrative_week_end: "बाज़ार सप्ताहांत की ओर बढ़ रहे हैं।",
+    narrative_midweek: "आज {WEEKDAY} है, कारोबारी सप्ताह का मध्य।",
+
+    llm_disabled: "प्रश्नों के लिए भाषा मॉडल की आवश्यकता है, लेकिन LLM बंद है।",
+    generation_error: "उत्तर बनाने में त्रुटि: {ERROR}",
+    no_stored_rates: "{FROM} से {TO} की कोई संग्रहीत विनिमय दर उपलब्ध नहीं है।",
+
+    calendar_title: "अगले {DAYS} दिनों में {FROM}/{TO} की आगामी घटनाएँ:",
+    calendar_empty: "अगले {DAYS} दिनों में {FROM}/{TO} की कोई घटना नहीं है।",
+    calendar_disabled: "कोई आर्थिक कैलेंडर कॉन्फ़िगर नहीं है।",
+
+    history_title: "पिछले {DAYS} दिनों में {FROM} से {TO}",
+
+    compare_title: "पिछले {DAYS} दिनों में {BASE} के मुकाबले {QUOTES}, पहले दिन से प्रतिशत बदलाव",
+    compare_missing: "{CURRENCIES} की कोई दर नहीं है।",
+    compare_invalid_range: "`{RANGE}` कोई अवधि नहीं है, जैसे 90d, 12w, 6m या 1y लिखें।",
+
+    matrix_title: "{DATE} की क्रॉस दरें: हर पंक्ति की एक इकाई हर कॉलम की मुद्रा में, {PREVIOUS} से बदलाव के अनुसार रंगी हुई",
+    matrix_title_no_change: "{DATE} की क्रॉस दरें: हर पंक्ति की एक इकाई हर कॉलम की मुद्रा में",
+    matrix_too_few: "कम से कम दो मुद्राएँ दें, जैसे USD,EUR,GBP,JPY,CAD।",
+    matrix_missing: "{CURRENCIES} की कोई दर नहीं है।",
+
+    stats_title: "**{FROM} से {TO}** {START} से {END} तक",
+    stats_range: "नवीनतम {LATEST} (अवधि में {CHANGE}%), उच्चतम {HIGH}, न्यूनतम {LOW}, औसत {AVERAGE}",
+    stats_volatility: "अस्थिरता: प्रति दिन {DAILY}%, प्रति वर्ष {ANNUAL}%",
+    stats_drawdown: "सबसे बड़ी गिरावट: {PEAK_DATE} के {PEAK} से {TROUGH_DATE} के {TROUGH} तक {PERCENT}%",
+    stats_streaks: "सबसे लंबे सिलसिले: लगातार {GAINS} बढ़त और {LOSSES} गिरावट",
+    stats_z_score: "नवीनतम बदलाव: औसत दिन से {Z_SCORE} मानक विचलन",
+    stats_correlation: "{FROM}/{VERSUS} के साथ दैनिक बदलावों का सहसंबंध: {CORRELATION}",
+    stats_correlation_recent: "{FROM}/{VERSUS} के साथ दैनिक बदलावों का सहसंबंध: {CORRELATION}, पिछले {WINDOW} बदलावों में {RECENT}",
+    stats_no_correlation: "सहसंबंध के लिए {FROM}/{VERSUS} की पर्याप्त दरें नहीं हैं।",
+
+    forecast_title: "{FROM} से {TO} का {MODEL} पूर्वानुमान: {DATE} को {VALUE}, 95% विश्वास के साथ {LOW} और {HIGH} के बीच",
+    forecast_backtest: "पिछली दरों से बनाए गए {DAYS} कार्य दिवसों के {COUNT} पूर्वानुमानों का बैकटेस्ट:",
+    forecast_backtest_model: "- {MODEL}: औसत त्रुटि {ERROR}%, अंतराल के भीतर {COVERAGE}% दरें",
+    forecast_no_backtest: "मॉडलों के बैकटेस्ट के लिए पर्याप्त दरें नहीं हैं।",
+    forecast_too_few: "पूर्वानुमान के लिए {FROM} से {TO} की पर्याप्त दरें नहीं हैं, कम से कम {COUNT} चाहिए।",
+    forecast_disclaimer: "*केवल सांकेतिक, वित्तीय सलाह नहीं।*",
+
+    unknown_subcommand: "अज्ञात उप-कमांड।",
+    rates_error: "विनिमय दरें प्राप्त नहीं की जा सकीं: {ERROR}",
+    template_guild_only: "टेम्पलेट केवल किसी सर्वर में प्रबंधित किए जा सकते हैं।",
+    template_permission: "टेम्पलेट प्रबंधित करने के लिए आपको सर्वर प्रबंधित करने की अनुमति चाहिए।",
+    template_download_error: "`{FILE}` डाउनलोड नहीं किया जा सका: {ERROR}",
+    template_not_text: "`{FILE}` UTF-8 टेक्स्ट फ़ाइल नहीं है",
+    template_invalid: "अमान्य टेम्पलेट: {ERROR}",
+    template_missing: "टेम्पलेट `template` या `file` विकल्प के साथ दें।",
+    template_version: "`{NAME}` का संस्करण {VERSION}:",
+    template_version_default: "`{NAME}` का संस्करण {VERSION} डिफ़ॉल्ट टेम्पलेट का उपयोग करता है।",
+    template_no_version: "इस सर्वर पर `{NAME}` का संस्करण {VERSION} नहीं है।",
+    template_source_server: "इस सर्वर का संस्करण {VERSION}",
+    template_source_default: "डिफ़ॉल्ट",
+    template_not_set: "सेट नहीं है।",
+    template_versions: "संस्करण:",
+    template_version_by: "{AUTHOR} द्वारा",
+    template_version_reset: "डिफ़ॉल्ट पर वापस",
+    template_saved: "`{NAME}` का संस्करण {VERSION} सहेजा गया।",
+    template_unset: "इस सर्वर पर `{NAME}` सेट नहीं है।",
+    template_render_error: "टेम्पलेट रेंडर नहीं किया जा सका: {ERROR}",
+    template_preview: "{FROM} से {TO} के साथ `{NAME}`:",
+    template_reset: "`{NAME}` फिर से डिफ़ॉल्ट टेम्पलेट का उपयोग करता है (संस्करण {VERSION})।",
+    template_reverted: "`{NAME}` को संस्करण {VERSION} पर लौटाया गया (संस्करण {NEW_VERSION} के रूप में सहेजा गया)।",
+
+    preview_hidden: "पोस्ट करने से पहले उत्तर की जाँच हो रही है…",
+
+    theme_guild_only: "थीम केवल किसी सर्वर में चुनी जा सकती है।",
+    theme_permission: "थीम चुनने के लिए आपको सर्वर प्रबंधित करने की अनुमति चाहिए।",
+    theme_current: "इस सर्वर के चार्ट `{THEME}` थीम का उपयोग करते हैं।",
+    theme_current_default: "इस सर्वर के चार्ट डिफ़ॉल्ट थीम का उपयोग करते हैं।",
+    theme_set: "इस सर्वर के चार्ट अब `{THEME}` थीम का उपयोग करते हैं।",
+    theme_reset: "इस सर्वर के चार्ट फिर से डिफ़ॉल्ट थीम का उपयोग करते हैं।",
+    theme_unknown: "`{THEME}` कोई थीम नहीं है, {THEMES} में से एक चुनें।",
+
+    basket_permission: "बास्केट बदलने के लिए आपको सर्वर प्रबंधित करने की अनुमति चाहिए।",
+    basket_admin_channel: "बास्केट केवल एडमिन चैनलों में बदले जा सकते हैं।",
+    basket_none: "अभी कोई बास्केट नहीं है, `/basket set` से एक बनाएँ।",
+    basket_list: "बास्केट: {BASKETS}।",
+    basket_unknown: "`{NAME}` नाम का कोई बास्केट नहीं है।",
+    basket_description: "`{NAME}` ({KIND}): {SHARES}, {DATE} को मूल्य {VALUE}।",
+    basket_rates_missing: "दरें उपलब्ध नहीं",
+    basket_value_unknown: "अज्ञात",
+    basket_saved: "सहेजा गया: {BASKET}",
+    basket_deleted: "`{NAME}` हटाया गया।",
+    basket_invalid_name: "`{NAME}` बास्केट का नाम नहीं हो सकता, 4 से 12 अक्षर या अंक इस्तेमाल करें।",
+    basket_invalid_component: "`{COMPONENT}` `USD:40` जैसी मुद्रा और भार नहीं है।",
+    basket_duplicate: "{CURRENCY} बास्केट में एक से अधिक बार है।",
+    basket_empty: "बास्केट में कोई मुद्रा नहीं है।",
+    basket_no_rate: "{CURRENCY} की कोई दर नहीं है।",
+
+    chart_trend: "विनिमय दर का रुझान: {FROM} से {TO}",
+    chart_candles_day: "दैनिक विनिमय दर: {FROM} से {TO}",
+    chart_candles_week: "साप्ताहिक विनिमय दर: {FROM} से {TO}",
+    chart_candles_month: "मासिक विनिमय दर: {FROM} से {TO}",
+    chart_forecast: "पूर्वानुमान: {FROM} से {TO} ({MODEL})",
+    chart_comparison: "{DATE} से {BASE} के मुकाबले बदलाव",
+    chart_cross_rates: "{DATE} की क्रॉस दरें",
+    chart_cross_rates_change: "{DATE} की क्रॉस दरें, {PREVIOUS} से बदलाव",
+    chart_rate: "विनिमय दर",
+    chart_change: "बदलाव (%)",
+    chart_date: "तारीख",
+    chart_pair: "{FROM} से {TO}",
+    chart_interval: "95% अंतराल",
+    chart_forecast_line: "पूर्वानुमान",
+    chart_disclaimer: "सांकेतिक पूर्वानुमान, वित्तीय सलाह नहीं",
+    chart_min: "न्यूनतम",
+    chart_max: "अधिकतम",
+    chart_last: "अंतिम",
+
+    quarantine_title: "क्वारंटीन की गई विनिमय दरें:",
+    quarantine_item: "- {DATE} {BASE}, {PROVIDER} से: {REASONS}",
+};
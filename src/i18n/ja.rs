@@ -0,0 +1,168 @@
+use super::Catalog;
+
+pub const CATALOG: Catalog = Catalog {
+    language: "Japanese",
+    date_format: "%Y年%m月%d日",
+    decimal_separator: '.',
+    weekdays: [
+        "月曜日",
+        "火曜日",
+        "水曜日",
+        "木曜日",
+        "金曜日",
+        "土曜日",
+        "日曜日",
+    ],
+
+    increase_prompt_template: r#"今日は{CURR_DATE}です。為替レートの上昇について、一般向けの短いレポートを書いてください。
+- {LAST_DATE}以降、{FROM}から{TO}への為替レートは{CURR}に上昇し、前回の{PREV}を上回りました。
+- 上昇幅は{DIFF}です。
+この情報を日本語で明確かつ簡潔にまとめてください。"#,
+    decrease_prompt_template: r#"今日は{CURR_DATE}です。為替レートの下落について、一般向けの短いレポートを書いてください。
+- {LAST_DATE}以降、{FROM}から{TO}への為替レートは{CURR}に下落し、前回の{PREV}を下回りました。
+- 下落幅は{DIFF}です。
+この情報を日本語で明確かつ簡潔にまとめてください。"#,
+    equal_prompt_template: r#"今日は{CURR_DATE}です。現在の為替レートについて、一般向けの短いレポートを書いてください。
+- {FROM}から{TO}への為替レートは{CURR}で、{LAST_DATE}の前回レート{PREV}とほぼ同じです。
+この情報を日本語で明確かつ簡潔にまとめてください。"#,
+
+    searched_in: "検索時間 {SECONDS} 秒",
+    first_token_in: "最初のトークンまで {SECONDS} 秒",
+    evaluated_in: "生成時間 {SECONDS} 秒",
+    prompt_and_generated_tokens: "プロンプト {PROMPT} + 生成 {GENERATED} トークン",
+    generated_tokens: "生成 {GENERATED} トークン",
+    tokens_per_second: "{SPEED} トークン/秒",
+    called_tools: "ツール呼び出し {COUNT} 回",
+    cached_response: "{DATE} {TIME} UTC のキャッシュされた回答です。refresh で再生成できます",
+    narrative_footer: "テンプレートから生成した文章",
+    narrative_llm_unavailable_footer: "テンプレートから生成した文章（LLM 利用不可: {ERROR}）",
+    narrative_rejected_footer: "テンプレートから生成した文章（LLM の回答を却下: {ERROR}）",
+    graph_generated_in: "グラフ生成時間 {SECONDS} 秒",
+    graph_error: "グラフ生成エラー: {ERROR}",
+    generated_in: "合計 {SECONDS} 秒",
+    api_error: "API の取得に失敗しました。API の URL とインターネット接続を確認してください。使用した URL: `{URL}`\n`エラー: {ERROR}`",
+
+    narrative_no_data: "{FROM}から{TO}への為替レートのデータがありません。",
+    narrative_unchanged: "{DATE}（{WEEKDAY}）、1 {FROM} は {CURR} {TO} で、{LAST_DATE}の {PREV} からほぼ横ばいです。",
+    narrative_rose: "{DATE}（{WEEKDAY}）、{FROM}は{TO}に対して{MAGNITUDE}上昇し、{DIFF}（{PCT}%）高の{CURR}となりました（{LAST_DATE}は{PREV}）。",
+    narrative_fell: "{DATE}（{WEEKDAY}）、{FROM}は{TO}に対して{MAGNITUDE}下落し、{DIFF}（{PCT}%）安の{CURR}となりました（{LAST_DATE}は{PREV}）。",
+    narrative_single: "{DATE}（{WEEKDAY}）、1 {FROM} は {CURR} {TO} です。",
+    narrative_magnitudes: ["わずかに", "小幅に", "目立って", "大幅に"],
+    narrative_gains_streak: "これで{COUNT}回連続の上昇です。",
+    narrative_losses_streak: "これで{COUNT}回連続の下落です。",
+    narrative_highest: "過去{DAYS}日間で最も高い水準です。",
+    narrative_lowest: "過去{DAYS}日間で最も低い水準です。",
+    narrative_range: "レートは{DAYS}日間の高値 {HIGH}（{HIGH_DATE}）を{BELOW}%下回り、安値 {LOW}（{LOW_DATE}）を{ABOVE}%上回っています。",
+    narrative_weekend: "週末のため市場は休場で、レートは直近の取引日のものです。",
+    narrative_week_start: "新しい取引週が始まりました。",
+    narrative_week_end: "市場は週末を迎えようとしています。",
+    narrative_midweek: "今日は{WEEKDAY}、取引週の半ばです。",
+
+    llm_disabled: "質問には言語モデルが必要ですが、LLM は無効になっています。",
+    generation_error: "回答の生成中にエラーが発生しました: {ERROR}",
+    no_stored_rates: "{FROM}から{TO}への保存された為替レートがありません。",
+
+    calendar_title: "今後{DAYS}日間の{FROM}/{TO}の予定:",
+    calendar_empty: "今後{DAYS}日間に{FROM}/{TO}の予定はありません。",
+    calendar_disabled: "経済カレンダーが設定されていません。",
+
+    history_title: "過去{DAYS}日間の{FROM}から{TO}",
+
+    compare_title: "過去{DAYS}日間の{BASE}に対する{QUOTES}（初日からの変化率）",
+    compare_missing: "{CURRENCIES}のレートがありません。",
+    compare_invalid_range: "`{RANGE}`は期間ではありません。90d、12w、6m、1y などを指定してください。",
+
+    matrix_title: "{DATE}のクロスレート：各行の通貨1単位を各列の通貨で表示（{PREVIOUS}からの変化で色分け）",
+    matrix_title_no_change: "{DATE}のクロスレート：各行の通貨1単位を各列の通貨で表示",
+    matrix_too_few: "通貨を2つ以上指定してください（例：USD,EUR,GBP,JPY,CAD）。",
+    matrix_missing: "{CURRENCIES}のレートがありません。",
+
+    stats_title: "**{FROM}から{TO}**（{START}〜{END}）",
+    stats_range: "最新 {LATEST}（期間中 {CHANGE}%）、高値 {HIGH}、安値 {LOW}、平均 {AVERAGE}",
+    stats_volatility: "ボラティリティ：1日 {DAILY}%、年率 {ANNUAL}%",
+    stats_drawdown: "最大下落：{PEAK_DATE}の{PEAK}から{TROUGH_DATE}の{TROUGH}まで {PERCENT}%",
+    stats_streaks: "最長連続：{GAINS}日連続の上昇、{LOSSES}日連続の下落",
+    stats_z_score: "直近の変化：平均的な日から標準偏差 {Z_SCORE} 個分",
+    stats_correlation: "{FROM}/{VERSUS}との日次変化の相関：{CORRELATION}",
+    stats_correlation_recent: "{FROM}/{VERSUS}との日次変化の相関：{CORRELATION}（直近{WINDOW}回の変化では{RECENT}）",
+    stats_no_correlation: "{FROM}/{VERSUS}のレートが不足しているため相関を計算できません。",
+
+    forecast_title: "{MODEL}による{FROM}から{TO}の予測：{DATE}に{VALUE}（95%信頼区間 {LOW}〜{HIGH}）",
+    forecast_backtest: "過去のレートから行った{DAYS}営業日先の予測{COUNT}回のバックテスト：",
+    forecast_backtest_model: "- {MODEL}：平均誤差 {ERROR}%、区間内のレート {COVERAGE}%",
+    forecast_no_backtest: "モデルをバックテストするにはレートが不足しています。",
+    forecast_too_few: "{FROM}から{TO}のレートが予測には不足しています。少なくとも{COUNT}件必要です。",
+    forecast_disclaimer: "*参考情報であり、投資助言ではありません。*",
+
+    unknown_subcommand: "不明なサブコマンドです。",
+    rates_error: "為替レートを取得できません: {ERROR}",
+    template_guild_only: "テンプレートはサーバー内でのみ管理できます。",
+    template_permission: "テンプレートを管理するには「サーバー管理」権限が必要です。",
+    template_download_error: "`{FILE}` をダウンロードできません: {ERROR}",
+    template_not_text: "`{FILE}` は UTF-8 のテキストファイルではありません",
+    template_invalid: "無効なテンプレート: {ERROR}",
+    template_missing: "`template` または `file` オプションでテンプレートを指定してください。",
+    template_version: "`{NAME}` のバージョン {VERSION}:",
+    template_version_default: "`{NAME}` のバージョン {VERSION} はデフォルトのテンプレートを使用しています。",
+    template_no_version: "このサーバーには `{NAME}` のバージョン {VERSION} がありません。",
+    template_source_server: "このサーバーのバージョン {VERSION}",
+    template_source_default: "デフォルト",
+    template_not_set: "未設定です。",
+    template_versions: "バージョン:",
+    template_version_by: "{AUTHOR} による",
+    template_version_reset: "デフォルトに戻す",
+    template_saved: "`{NAME}` のバージョン {VERSION} を保存しました。",
+    template_unset: "このサーバーでは `{NAME}` が設定されていません。",
+    template_render_error: "テンプレートを展開できません: {ERROR}",
+    template_preview: "{FROM} から {TO} での `{NAME}`:",
+    template_reset: "`{NAME}` は再びデフォルトのテンプレートを使用します(バージョン {VERSION})。",
+    template_reverted: "`{NAME}` をバージョン {VERSION} に戻しました(バージョン {NEW_VERSION} として保存)。",
+
+    preview_hidden: "投稿前に回答を確認しています…",
+
+    theme_guild_only: "テーマはサーバー内でのみ選択できます。",
+    theme_permission: "テーマを選択するには「サーバー管理」権限が必要です。",
+    theme_current: "このサーバーのチャートは `{THEME}` テーマを使用しています。",
+    theme_current_default: "このサーバーのチャートはデフォルトのテーマを使用しています。",
+    theme_set: "このサーバーのチャートは今後 `{THEME}` テーマを使用します。",
+    theme_reset: "このサーバーのチャートは再びデフォルトのテーマを使用します。",
+    theme_unknown: "`{THEME}` はテーマではありません。{THEMES} から選んでください。",
+
+    basket_permission: "バスケットを変更するには「サーバー管理」権限が必要です。",
+    basket_admin_channel: "バスケットは管理チャンネルでのみ変更できます。",
+    basket_none: "バスケットはまだありません。`/basket set` で作成してください。",
+    basket_list: "バスケット: {BASKETS}。",
+    basket_unknown: "バスケット `{NAME}` はありません。",
+    basket_description: "`{NAME}` ({KIND}): {SHARES}、{DATE} の価値は {VALUE}。",
+    basket_rates_missing: "レートがありません",
+    basket_value_unknown: "不明",
+    basket_saved: "保存しました: {BASKET}",
+    basket_deleted: "`{NAME}` を削除しました。",
+    basket_invalid_name: "`{NAME}` はバスケット名に使えません。4〜12 文字の英数字を使ってください。",
+    basket_invalid_component: "`{COMPONENT}` は `USD:40` のような通貨と重みではありません。",
+    basket_duplicate: "{CURRENCY} がバスケットに複数回含まれています。",
+    basket_empty: "バスケットに通貨がありません。",
+    basket_no_rate: "{CURRENCY} のレートがありません。",
+
+    chart_trend: "為替レートの推移: {FROM} → {TO}",
+    chart_candles_day: "為替レート日足: {FROM} → {TO}",
+    chart_candles_week: "為替レート週足: {FROM} → {TO}",
+    chart_candles_month: "為替レート月足: {FROM} → {TO}",
+    chart_forecast: "予測: {FROM} → {TO} ({MODEL})",
+    chart_comparison: "{DATE} 以降の {BASE} に対する変化",
+    chart_cross_rates: "{DATE} のクロスレート",
+    chart_cross_rates_change: "{DATE} のクロスレート、{PREVIOUS} からの変化",
+    chart_rate: "為替レート",
+    chart_change: "変化 (%)",
+    chart_date: "日付",
+    chart_pair: "{FROM} → {TO}",
+    chart_interval: "95% 区間",
+    chart_forecast_line: "予測",
+    chart_disclaimer: "参考予測であり、投資助言ではありません",
+    chart_min: "最安",
+    chart_max: "最高",
+    chart_last: "最新",
+
+    quarantine_title: "隔離された為替レート:",
+    quarantine_item: "- {DATE} {BASE}（{PROVIDER}）: {REASONS}",
+};
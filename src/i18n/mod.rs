@@ -0,0 +1,235 @@
+use chrono::{NaiveDate, Weekday};
+
+mod de;
+mod en;
+mod es;
+mod hi;
+mod ja;
+
+/// Languages the bot replies in, the same ones the slash commands are localized into
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Ja,
+    Es,
+    Hi,
+}
+
+/// Translations of everything the bot says.
+///
+/// Messages contain `{NAME}` placeholders replaced by [`fill`].
+pub struct Catalog {
+    /// English name of the language, used to tell the LLM which language to answer in
+    pub language: &'static str,
+    pub date_format: &'static str,
+    pub decimal_separator: char,
+    /// Monday first
+    pub weekdays: [&'static str; 7],
+
+    pub increase_prompt_template: &'static str,
+    pub decrease_prompt_template: &'static str,
+    pub equal_prompt_template: &'static str,
+
+    pub searched_in: &'static str,
+    pub first_token_in: &'static str,
+    pub evaluated_in: &'static str,
+    pub prompt_and_generated_tokens: &'static str,
+    pub generated_tokens: &'static str,
+    pub tokens_per_second: &'static str,
+    pub called_tools: &'static str,
+    pub cached_response: &'static str,
+    pub narrative_footer: &'static str,
+    pub narrative_llm_unavailable_footer: &'static str,
+    pub narrative_rejected_footer: &'static str,
+    pub graph_generated_in: &'static str,
+    pub graph_error: &'static str,
+    pub generated_in: &'static str,
+    pub api_error: &'static str,
+
+    pub narrative_no_data: &'static str,
+    pub narrative_unchanged: &'static str,
+    pub narrative_rose: &'static str,
+    pub narrative_fell: &'static str,
+    pub narrative_single: &'static str,
+    /// Marginally, slightly, noticeably and sharply
+    pub narrative_magnitudes: [&'static str; 4],
+    pub narrative_gains_streak: &'static str,
+    pub narrative_losses_streak: &'static str,
+    pub narrative_highest: &'static str,
+    pub narrative_lowest: &'static str,
+    pub narrative_range: &'static str,
+    pub narrative_weekend: &'static str,
+    pub narrative_week_start: &'static str,
+    pub narrative_week_end: &'static str,
+    pub narrative_midweek: &'static str,
+
+    pub llm_disabled: &'static str,
+    pub generation_error: &'static str,
+    pub no_stored_rates: &'static str,
+
+    pub calendar_title: &'static str,
+    pub calendar_empty: &'static str,
+    pub calendar_disabled: &'static str,
+
+    pub history_title: &'static str,
+
+    pub compare_title: &'static str,
+    pub compare_missing: &'static str,
+    pub compare_invalid_range: &'static str,
+
+    pub matrix_title: &'static str,
+    pub matrix_title_no_change: &'static str,
+    pub matrix_too_few: &'static str,
+    pub matrix_missing: &'static str,
+
+    pub stats_title: &'static str,
+    pub stats_range: &'static str,
+    pub stats_volatility: &'static str,
+    pub stats_drawdown: &'static str,
+    pub stats_streaks: &'static str,
+    pub stats_z_score: &'static str,
+    pub stats_correlation: &'static str,
+    pub stats_correlation_recent: &'static str,
+    pub stats_no_correlation: &'static str,
+
+    pub forecast_title: &'static str,
+    pub forecast_backtest: &'static str,
+    pub forecast_backtest_model: &'static str,
+    pub forecast_no_backtest: &'static str,
+    pub forecast_too_few: &'static str,
+    pub forecast_disclaimer: &'static str,
+
+    pub unknown_subcommand: &'static str,
+    pub rates_error: &'static str,
+    pub template_guild_only: &'static str,
+    pub template_permission: &'static str,
+    pub template_download_error: &'static str,
+    pub template_not_text: &'static str,
+    pub template_invalid: &'static str,
+    pub template_missing: &'static str,
+    pub template_version: &'static str,
+    pub template_version_default: &'static str,
+    pub template_no_version: &'static str,
+    pub template_source_server: &'static str,
+    pub template_source_default: &'static str,
+    pub template_not_set: &'static str,
+    pub template_versions: &'static str,
+    pub template_version_by: &'static str,
+    pub template_version_reset: &'static str,
+    pub template_saved: &'static str,
+    pub template_unset: &'static str,
+    pub template_render_error: &'static str,
+    pub template_preview: &'static str,
+    pub template_reset: &'static str,
+    pub template_reverted: &'static str,
+
+    pub preview_hidden: &'static str,
+
+    pub theme_guild_only: &'static str,
+    pub theme_permission: &'static str,
+    pub theme_current: &'static str,
+    pub theme_current_default: &'static str,
+    pub theme_set: &'static str,
+    pub theme_reset: &'static str,
+    pub theme_unknown: &'static str,
+
+    pub basket_permission: &'static str,
+    pub basket_admin_channel: &'static str,
+    pub basket_none: &'static str,
+    pub basket_list: &'static str,
+    pub basket_unknown: &'static str,
+    pub basket_description: &'static str,
+    pub basket_rates_missing: &'static str,
+    pub basket_value_unknown: &'static str,
+    pub basket_saved: &'static str,
+    pub basket_deleted: &'static str,
+    pub basket_invalid_name: &'static str,
+    pub basket_invalid_component: &'static str,
+    pub basket_duplicate: &'static str,
+    pub basket_empty: &'static str,
+    pub basket_no_rate: &'static str,
+
+    pub chart_trend: &'static str,
+    pub chart_candles_day: &'static str,
+    pub chart_candles_week: &'static str,
+    pub chart_candles_month: &'static str,
+    pub chart_forecast: &'static str,
+    pub chart_comparison: &'static str,
+    pub chart_cross_rates: &'static str,
+    pub chart_cross_rates_change: &'static str,
+    pub chart_rate: &'static str,
+    pub chart_change: &'static str,
+    pub chart_date: &'static str,
+    pub chart_pair: &'static str,
+    pub chart_interval: &'static str,
+    pub chart_forecast_line: &'static str,
+    pub chart_disclaimer: &'static str,
+    pub chart_min: &'static str,
+    pub chart_max: &'static str,
+    pub chart_last: &'static str,
+
+    pub quarantine_title: &'static str,
+    pub quarantine_item: &'static str,
+}
+
+impl Locale {
+    /// Locale of a Discord locale code such as `de`, `es-ES` or `en-US`.
+    ///
+    /// Languages without a translation fall back to English.
+    pub fn from_discord(code: &str) -> Locale {
+        match code.split('-').next().unwrap_or_default() {
+            "de" => Locale::De,
+            "ja" => Locale::Ja,
+            "es" => Locale::Es,
+            "hi" => Locale::Hi,
+            _ => Locale::En,
+        }
+    }
+
+    pub fn catalog(&self) -> &'static Catalog {
+        match self {
+            Locale::En => &en::CATALOG,
+            Locale::De => &de::CATALOG,
+            Locale::Ja => &ja::CATALOG,
+            Locale::Es => &es::CATALOG,
+            Locale::Hi => &hi::CATALOG,
+        }
+    }
+
+    /// Replace the decimal point of a formatted number with the locale's separator
+    pub fn format_decimal(&self, number: &str) -> String {
+        number.replace('.', &self.catalog().decimal_separator.to_string())
+    }
+
+    pub fn format_number(&self, value: f64, precision: usize) -> String {
+        self.format_decimal(&format!("{:.*}", precision, value))
+    }
+
+    /// Like [`Locale::format_number`], with a `+` in front of positive values
+    pub fn format_signed_number(&self, value: f64, precision: usize) -> String {
+        self.format_decimal(&format!("{:+.*}", precision, value))
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(self.catalog().date_format).to_string()
+    }
+
+    pub fn format_weekday(&self, weekday: Weekday) -> &'static str {
+        self.catalog().weekdays[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Seconds with millisecond precision
+    pub fn format_seconds(&self, duration: std::time::Duration) -> String {
+        self.format_number(duration.as_secs_f64(), 3)
+    }
+}
+
+/// Replace the `{NAME}` placeholders of a catalog message
+pub fn fill(message: &str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(message.to_string(), |message, (name, value)| {
+            message.replace(&format!("{{{}}}", name), value)
+        })
+}
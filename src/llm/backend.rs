@@ -0,0 +1,298 @@
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use serenity::async_trait;
+use thiserror::Error;
+use tokio::sync::watch;
+
+use crate::environment;
+use crate::llm::guardrails::GuardrailError;
+use crate::llm::ollama::OllamaBackend;
+use crate::llm::openai::OpenAiBackend;
+
+#[derive(Debug, Error)]
+pub enum LlmError {
+    #[error("Failed to create a client ({0})")]
+    Client(String),
+
+    #[error("Failed to send request ({0})")]
+    Request(String),
+
+    #[error("Request failed with status {0} ({1})")]
+    Status(reqwest::StatusCode, String),
+
+    #[error("Failed to retrieve response text ({0})")]
+    ResponseBody(String),
+
+    #[error("Failed to parse JSON ({0})")]
+    Parse(String),
+
+    #[error("Backend returned an error ({0})")]
+    Backend(String),
+
+    #[error("Stream ended before completion")]
+    Incomplete,
+
+    #[error("LLM is disabled")]
+    Disabled,
+
+    #[error("Response rejected, {0}")]
+    Rejected(#[from] GuardrailError),
+}
+
+/// A function call requested by the model
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    /// Call id, only sent by OpenAI compatible backends
+    pub id: Option<String>,
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// Backend independent result of a chat request.
+///
+/// Timings are measured on our side so that every backend reports the same thing.
+pub struct ChatCompletion {
+    pub content: String,
+    /// Raw response body, kept for the database
+    pub raw: String,
+    /// Tools the model wants to call before answering
+    pub tool_calls: Vec<ToolCall>,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    /// Time from sending the request to receiving the first token
+    pub first_token_duration: Duration,
+    /// Time from sending the request to receiving the last token
+    pub total_duration: Duration,
+}
+
+#[async_trait]
+pub trait LlmBackend: Send + Sync {
+    /// Name of the backend, shown in `/about`
+    fn name(&self) -> &'static str;
+
+    /// Model used for the requests
+    fn model(&self) -> &str;
+
+    /// Send `messages` (`{"role", "content"}` objects) and stream the answer.
+    ///
+    /// `tools` are function definitions in the OpenAI format, shared by both
+    /// backends. When `progress` is given, the text generated so far is
+    /// published to it after every received chunk.
+    async fn chat(
+        &self,
+        messages: &[Value],
+        tools: &[Value],
+        progress: Option<&watch::Sender<String>>,
+    ) -> Result<ChatCompletion, LlmError>;
+
+    /// The assistant message that requested `completion.tool_calls`, in the
+    /// shape this backend expects it back in the history.
+    fn assistant_message(&self, completion: &ChatCompletion) -> Value;
+
+    /// The message carrying the result of `call` back to the model.
+    fn tool_message(&self, call: &ToolCall, content: &str) -> Value;
+}
+
+/// Get the backend selected by `LLM_BACKEND`, `None` in no-LLM mode
+pub fn get_backend() -> Option<Box<dyn LlmBackend>> {
+    match environment::get_llm_backend().to_lowercase().as_str() {
+        "none" => None,
+        "openai" => Some(Box::new(OpenAiBackend::new(
+            environment::get_openai_api_url(),
+            environment::get_openai_api_key(),
+            environment::get_openai_model(),
+        ))),
+        _ => Some(Box::new(OllamaBackend::new(
+            environment::get_ollama_url(),
+            environment::get_ollama_model(),
+        ))),
+    }
+}
+
+pub(crate) fn build_client() -> Result<reqwest::Client, LlmError> {
+    reqwest::ClientBuilder::new()
+        .timeout(Duration::from_secs(environment::get_llm_timeout()))
+        .build()
+        .map_err(|e| LlmError::Client(e.to_string()))
+}
+
+/// Fail with the response body when the status is not a success.
+pub(crate) async fn check_status(res: reqwest::Response) -> Result<reqwest::Response, LlmError> {
+    if res.status().is_success() {
+        return Ok(res);
+    }
+
+    let status = res.status();
+    let body = res.text().await.unwrap_or_default();
+    log::error!("Request failed with status {}: {}", status, body);
+    Err(LlmError::Status(status, body))
+}
+
+/// Splits streamed bytes into lines, whichever way they are chunked.
+#[derive(Default)]
+pub(crate) struct LineBuffer {
+    buffer: Vec<u8>,
+}
+
+impl LineBuffer {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Next complete non-empty line, `None` until a newline is received.
+    pub fn next_line(&mut self) -> Option<String> {
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line).trim().to_string();
+            if !line.is_empty() {
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    /// What is left once the body ended, the last line may not be terminated
+    /// by a newline.
+    pub fn take_rest(&mut self) -> Option<String> {
+        let line = String::from_utf8_lossy(&self.buffer).trim().to_string();
+        self.buffer.clear();
+        if line.is_empty() {
+            None
+        } else {
+            Some(line)
+        }
+    }
+}
+
+/// Splits a streamed response body into lines.
+pub(crate) struct LineReader {
+    res: reqwest::Response,
+    lines: LineBuffer,
+    finished: bool,
+    /// Everything received so far
+    pub raw: String,
+}
+
+impl LineReader {
+    pub fn new(res: reqwest::Response) -> LineReader {
+        LineReader {
+            res,
+            lines: LineBuffer::default(),
+            finished: false,
+            raw: String::new(),
+        }
+    }
+
+    /// Next non-empty line, `None` once the body is exhausted.
+    pub async fn next_line(&mut self) -> Result<Option<String>, LlmError> {
+        loop {
+            if let Some(line) = self.lines.next_line() {
+                return Ok(Some(line));
+            }
+
+            if self.finished {
+                return Ok(self.lines.take_rest());
+            }
+
+            match self.res.chunk().await {
+                Ok(Some(bytes)) => {
+                    self.raw.push_str(&String::from_utf8_lossy(&bytes));
+                    self.lines.push(&bytes);
+                }
+                Ok(None) => self.finished = true,
+                Err(e) => {
+                    log::error!("Failed to get response chunk: {}", e);
+                    return Err(LlmError::ResponseBody(e.to_string()));
+                }
+            }
+        }
+    }
+}
+
+/// Accumulates streamed text and the timings shared by all backends.
+pub(crate) struct StreamState<'a> {
+    start: Instant,
+    first_token: Option<Duration>,
+    progress: Option<&'a watch::Sender<String>>,
+    pub content: String,
+}
+
+impl<'a> StreamState<'a> {
+    pub fn new(start: Instant, progress: Option<&'a watch::Sender<String>>) -> StreamState<'a> {
+        StreamState {
+            start,
+            first_token: None,
+            progress,
+            content: String::new(),
+        }
+    }
+
+    pub fn push(&mut self, delta: &str) {
+        if delta.is_empty() {
+            return;
+        }
+        if self.first_token.is_none() {
+            self.first_token = Some(self.start.elapsed());
+        }
+        self.content.push_str(delta);
+
+        if let Some(progress) = self.progress {
+            // Nobody listening anymore is not an error for the generation
+            let _ = progress.send(self.content.clone());
+        }
+    }
+
+    pub fn finish(
+        self,
+        raw: String,
+        tool_calls: Vec<ToolCall>,
+        prompt_tokens: Option<u64>,
+        completion_tokens: Option<u64>,
+    ) -> ChatCompletion {
+        let total_duration = self.start.elapsed();
+        ChatCompletion {
+            content: self.content,
+            raw,
+            tool_calls,
+            prompt_tokens,
+            completion_tokens,
+            first_token_duration: self.first_token.unwrap_or(total_duration),
+            total_duration,
+        }
+    }
+}
+
+/// Lines of `chunks` as the readers split them, for the tests of the backends
+#[cfg(test)]
+pub(crate) fn split_lines(chunks: &[&[u8]]) -> Vec<String> {
+    let mut buffer = LineBuffer::default();
+    let mut lines = vec![];
+    for chunk in chunks {
+        buffer.push(chunk);
+        while let Some(line) = buffer.next_line() {
+            lines.push(line);
+        }
+    }
+    lines.extend(buffer.take_rest());
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_lines() {
+        // A line split over chunks, blank lines and a last line without a newline
+        let lines = split_lines(&[b"{\"a\":", b"1}\n\n{\"b\"", b":2}\r\n", b"{\"c\":3}"]);
+        assert_eq!(lines, ["{\"a\":1}", "{\"b\":2}", "{\"c\":3}"]);
+
+        // A character split over chunks
+        let yen = "¥".as_bytes();
+        let lines = split_lines(&[&yen[..1], &yen[1..], b"\n"]);
+        assert_eq!(lines, ["¥"]);
+
+        assert!(split_lines(&[b"\n\n"]).is_empty());
+    }
+}
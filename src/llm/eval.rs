@@ -0,0 +1,504 @@
+use std::{env, fs, time::Duration};
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    exchange_rate::ExchangeRateMap,
+    i18n::Locale,
+    llm::{
+        generate::{generate_sentence, GenerationOptions, GenerationResult},
+        guardrails::{strip_reasoning, GuardrailError, Guardrails},
+        prompt::{get_known_values, get_prompt, get_template_context, parse_template},
+        template::TemplateError,
+    },
+    utils::search::{parse_published, SearchResult},
+};
+
+const DEFAULT_REPORT_FILE: &str = "eval-report.json";
+
+const USAGE: &str = "Usage: exchange-rate-bot eval <fixtures.json> \
+[--model ollama:MODEL|openai:MODEL]... [--template FILE]... [--output FILE]";
+
+/// Words common enough to tell the languages written in Latin script apart
+const STOPWORDS: &[(Locale, &[&str])] = &[
+    (
+        Locale::En,
+        &["the", "is", "and", "of", "to", "has", "from", "against"],
+    ),
+    (
+        Locale::De,
+        &["der", "die", "das", "und", "ist", "gegenüber", "von", "zum"],
+    ),
+    (
+        Locale::Es,
+        &["el", "la", "los", "y", "es", "del", "frente", "con"],
+    ),
+];
+
+#[derive(Debug, Error)]
+pub enum EvalError {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("Cannot access {0}: {1}")]
+    Io(String, std::io::Error),
+
+    #[error("Invalid fixtures: {0}")]
+    Fixture(String),
+
+    #[error("Invalid template {0}: {1}")]
+    Template(String, TemplateError),
+}
+
+/// A historical rate scenario replayed against every model and template
+#[derive(Debug)]
+pub struct Scenario {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    pub locale: Locale,
+    /// Daily rates, oldest first
+    pub rates: Vec<ExchangeRateMap>,
+    pub news: Vec<SearchResult>,
+}
+
+/// Backend and model set through the environment before a run
+#[derive(Debug)]
+struct ModelSpec {
+    backend: String,
+    model: String,
+}
+
+impl ModelSpec {
+    fn parse(spec: &str) -> Result<ModelSpec, EvalError> {
+        match spec.split_once(':') {
+            Some((backend @ ("ollama" | "openai"), model)) if !model.is_empty() => Ok(ModelSpec {
+                backend: backend.to_string(),
+                model: model.to_string(),
+            }),
+            _ => Err(EvalError::Usage(format!(
+                "Invalid model '{}', expected ollama:MODEL or openai:MODEL",
+                spec
+            ))),
+        }
+    }
+
+    /// The configured backend and model
+    fn configured() -> ModelSpec {
+        let backend = crate::environment::get_llm_backend().to_lowercase();
+        let model = match backend.as_str() {
+            "openai" => crate::environment::get_openai_model(),
+            _ => crate::environment::get_ollama_model(),
+        };
+        ModelSpec { backend, model }
+    }
+
+    /// Make `generate_sentence` use this model
+    fn apply(&self) {
+        env::set_var("LLM_BACKEND", &self.backend);
+        match self.backend.as_str() {
+            "openai" => env::set_var("OPENAI_MODEL", &self.model),
+            _ => env::set_var("OLLAMA_MODEL", &self.model),
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("{}:{}", self.backend, self.model)
+    }
+}
+
+struct Options {
+    fixtures: String,
+    models: Vec<ModelSpec>,
+    /// `(file, template)`, the configured templates when empty
+    templates: Vec<(String, String)>,
+    output: String,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, EvalError> {
+    let mut fixtures = None;
+    let mut models = vec![];
+    let mut templates = vec![];
+    let mut output = DEFAULT_REPORT_FILE.to_string();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| EvalError::Usage(format!("{} needs a value\n{}", arg, USAGE)))
+        };
+        match arg.as_str() {
+            "--model" => models.push(ModelSpec::parse(&value()?)?),
+            "--template" => {
+                let file = value()?;
+                let template =
+                    fs::read_to_string(&file).map_err(|e| EvalError::Io(file.clone(), e))?;
+                parse_template(&template).map_err(|e| EvalError::Template(file.clone(), e))?;
+                templates.push((file, template));
+            }
+            "--output" => output = value()?,
+            _ if fixtures.is_none() && !arg.starts_with("--") => fixtures = Some(arg.clone()),
+            _ => {
+                return Err(EvalError::Usage(format!(
+                    "Unexpected argument '{}'\n{}",
+                    arg, USAGE
+                )))
+            }
+        }
+    }
+
+    if models.is_empty() {
+        models.push(ModelSpec::configured());
+    }
+
+    Ok(Options {
+        fixtures: fixtures.ok_or_else(|| EvalError::Usage(USAGE.to_string()))?,
+        models,
+        templates,
+        output,
+    })
+}
+
+/// Parse the fixture file, a JSON array of scenarios like
+///
+/// ```json
+/// {"name": "...", "from": "EUR", "to": "USD", "locale": "de",
+///  "rates": [["2024-01-02", 1.0945], ...], "news": [{"title": "...", "content": "...", "url": "..."}]}
+/// ```
+pub fn parse_scenarios(json: &str) -> Result<Vec<Scenario>, EvalError> {
+    let value: Value = serde_json::from_str(json).map_err(|e| EvalError::Fixture(e.to_string()))?;
+    let scenarios = value
+        .as_array()
+        .ok_or_else(|| EvalError::Fixture("expected an array of scenarios".to_string()))?;
+
+    scenarios
+        .iter()
+        .enumerate()
+        .map(|(i, scenario)| {
+            let field = |name: &str| {
+                scenario[name]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| EvalError::Fixture(format!("scenario {} has no '{}'", i, name)))
+            };
+            let name = field("name")?;
+            let from = field("from")?.to_uppercase();
+            let to = field("to")?.to_uppercase();
+            let locale = Locale::from_discord(scenario["locale"].as_str().unwrap_or("en-US"));
+
+            let rates = scenario["rates"]
+                .as_array()
+                .ok_or_else(|| EvalError::Fixture(format!("scenario '{}' has no rates", name)))?
+                .iter()
+                .map(|rate| {
+                    let date = rate[0]
+                        .as_str()
+                        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+                    match (date, rate[1].as_f64()) {
+                        (Some(date), Some(value)) => Ok(ExchangeRateMap {
+                            datetime: Utc.from_utc_datetime(&date.and_time(Default::default())),
+                            base: from.clone(),
+                            map: [(to.clone(), value)].into(),
+                        }),
+                        _ => Err(EvalError::Fixture(format!(
+                            "scenario '{}' has an invalid rate {}, expected [\"YYYY-MM-DD\", value]",
+                            name, rate
+                        ))),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let news = scenario["news"]
+                .as_array()
+                .map(|news| {
+                    news.iter()
+                        .map(|n| SearchResult {
+                            url: n["url"].as_str().map(str::to_string),
+                            title: n["title"].as_str().map(str::to_string),
+                            content: n["content"].as_str().map(str::to_string),
+                            published: n["published"].as_str().and_then(parse_published),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            Ok(Scenario {
+                name,
+                from,
+                to,
+                locale,
+                rates,
+                news,
+            })
+        })
+        .collect()
+}
+
+/// Guess the language of `text`, `None` when there is nothing to go by
+pub fn detect_language(text: &str) -> Option<Locale> {
+    let chars = text.chars().filter(|c| c.is_alphabetic()).count();
+    let count = |range: &[std::ops::RangeInclusive<char>]| {
+        text.chars()
+            .filter(|c| range.iter().any(|r| r.contains(c)))
+            .count()
+    };
+    if chars == 0 {
+        return None;
+    }
+    // Hiragana, katakana and kanji
+    if count(&['\u{3040}'..='\u{30ff}', '\u{4e00}'..='\u{9fff}']) * 3 > chars {
+        return Some(Locale::Ja);
+    }
+    if count(&['\u{0900}'..='\u{097f}']) * 3 > chars {
+        return Some(Locale::Hi);
+    }
+
+    let lowercase = text.to_lowercase();
+    let words: Vec<&str> = lowercase
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    STOPWORDS
+        .iter()
+        .map(|(locale, stopwords)| {
+            let hits = words.iter().filter(|w| stopwords.contains(w)).count();
+            (*locale, hits)
+        })
+        .filter(|(_, hits)| *hits > 0)
+        .max_by_key(|(_, hits)| *hits)
+        .map(|(locale, _)| locale)
+}
+
+fn check_result(result: Result<(), GuardrailError>) -> Value {
+    match result {
+        Ok(()) => Value::Null,
+        Err(e) => Value::from(e.to_string()),
+    }
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs_f64()
+}
+
+/// Report entry of one generation with its automatic checks
+fn get_entry(
+    scenario: &Scenario,
+    model: &str,
+    template: &str,
+    prompt: &str,
+    result: &Result<GenerationResult, String>,
+) -> Value {
+    let mut entry = serde_json::json!({
+        "scenario": scenario.name,
+        "model": model,
+        "template": template,
+        "prompt": prompt,
+    });
+
+    match result {
+        Ok(result) => {
+            let checks = Guardrails::new(
+                get_known_values(&scenario.rates, &scenario.from, &scenario.to),
+                &[&scenario.from, &scenario.to],
+            );
+            let output = strip_reasoning(&result.content).trim().to_string();
+            let language = detect_language(&output);
+
+            entry["output"] = Value::from(output.as_str());
+            entry["latency"] = serde_json::json!({
+                "search": seconds(result.search_duration),
+                "first_token": seconds(result.first_token_duration),
+                "eval": seconds(result.eval_duration()),
+                "total": seconds(result.total_duration),
+            });
+            entry["prompt_tokens"] = serde_json::json!(result.prompt_tokens);
+            entry["completion_tokens"] = serde_json::json!(result.completion_tokens);
+            entry["tokens_per_second"] = serde_json::json!(result.tokens_per_second());
+            entry["checks"] = serde_json::json!({
+                "length": check_result(checks.check_length(&output)),
+                "figures": check_result(checks.check_figures(&output)),
+                "terms": check_result(checks.check_terms(&output)),
+                "language": {
+                    "expected": scenario.locale.catalog().language,
+                    "detected": language.map(|l| l.catalog().language),
+                    "passed": language == Some(scenario.locale),
+                },
+            });
+            let passed = ["length", "figures", "terms"]
+                .iter()
+                .all(|check| entry["checks"][check].is_null())
+                && language == Some(scenario.locale);
+            entry["passed"] = Value::from(passed);
+        }
+        Err(e) => {
+            entry["error"] = Value::from(e.as_str());
+            entry["passed"] = Value::from(false);
+        }
+    }
+
+    entry
+}
+
+/// Replay every scenario of the fixtures through the prompt templates and
+/// models given in `args` and write the outputs, latencies and checks to a
+/// JSON report.
+pub async fn run(args: &[String]) -> Result<(), EvalError> {
+    let options = parse_options(args)?;
+    let json = fs::read_to_string(&options.fixtures)
+        .map_err(|e| EvalError::Io(options.fixtures.clone(), e))?;
+    let scenarios = parse_scenarios(&json)?;
+
+    // Outputs are checked by the evaluation instead of being rejected, only
+    // mass mentions are still refused by the guardrails.
+    let guardrails = Guardrails {
+        max_length: usize::MAX,
+        blocklist: vec![],
+        known_values: vec![],
+        currencies: vec![],
+        tolerance: 0.0,
+    };
+
+    let mut entries = vec![];
+    for model in &options.models {
+        model.apply();
+
+        let templates = match options.templates.is_empty() {
+            true => vec![None],
+            false => options.templates.iter().map(Some).collect(),
+        };
+        for template in templates {
+            let template_name = template.map_or("configured", |(file, _)| file.as_str());
+
+            for scenario in &scenarios {
+                let prompt = match template {
+                    Some((file, template)) => {
+                        let context = get_template_context(
+                            &scenario.rates,
+                            &scenario.from,
+                            &scenario.to,
+                            &scenario.news,
+                            &[],
+                        );
+                        parse_template(template)
+                            .and_then(|t| t.render(&context))
+                            .map_err(|e| EvalError::Template(file.clone(), e))?
+                    }
+                    None => get_prompt(
+                        &scenario.rates,
+                        &scenario.from,
+                        &scenario.to,
+                        &scenario.news,
+                        &[],
+                        None,
+                        scenario.locale,
+                    ),
+                };
+
+                log::info!(
+                    "Evaluating '{}' with {} and {}",
+                    scenario.name,
+                    model.name(),
+                    template_name
+                );
+                let result = generate_sentence(
+                    &prompt,
+                    (&scenario.from, &scenario.to),
+                    async { (prompt.clone(), scenario.news.clone()) },
+                    GenerationOptions {
+                        guild_id: None,
+                        locale: scenario.locale,
+                        refresh: true,
+                        guardrails: &guardrails,
+                        progress: None,
+                    },
+                )
+                .await
+                .map_err(|e| e.to_string());
+
+                let entry = get_entry(scenario, &model.name(), template_name, &prompt, &result);
+                println!(
+                    "{:<24} {:<32} {:<24} {:>7.2}s {}",
+                    scenario.name,
+                    model.name(),
+                    template_name,
+                    entry["latency"]["total"].as_f64().unwrap_or_default(),
+                    match entry["passed"].as_bool() {
+                        Some(true) => "passed",
+                        _ => "failed",
+                    }
+                );
+                entries.push(entry);
+            }
+        }
+    }
+
+    let passed = entries.iter().filter(|e| e["passed"] == true).count();
+    let report = serde_json::json!({
+        "time": Utc::now().to_rfc3339(),
+        "fixtures": options.fixtures,
+        "passed": passed,
+        "total": entries.len(),
+        "results": entries,
+    });
+    let report = serde_json::to_string_pretty(&report).unwrap_or_default();
+    fs::write(&options.output, report).map_err(|e| EvalError::Io(options.output.clone(), e))?;
+    println!(
+        "{}/{} passed, report written to {}",
+        passed,
+        entries.len(),
+        options.output
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("The euro rose against the dollar and is at 1.08."),
+            Some(Locale::En)
+        );
+        assert_eq!(
+            detect_language("Der Euro ist gegenüber dem Dollar gestiegen."),
+            Some(Locale::De)
+        );
+        assert_eq!(
+            detect_language("El euro subió frente al dólar y la tasa es 1,08."),
+            Some(Locale::Es)
+        );
+        assert_eq!(
+            detect_language("ユーロはドルに対して上昇しました。"),
+            Some(Locale::Ja)
+        );
+        assert_eq!(detect_language("यूरो डॉलर के मुकाबले बढ़ा।"), Some(Locale::Hi));
+        assert_eq!(detect_language("1.08"), None);
+    }
+
+    #[test]
+    fn test_parse_scenarios() {
+        let scenarios = parse_scenarios(
+            r#"[{"name": "rise", "from": "eur", "to": "usd", "locale": "de",
+                 "rates": [["2024-01-02", 1.09], ["2024-01-03", 1.1]],
+                 "news": [{"title": "ECB meeting"}]}]"#,
+        )
+        .unwrap();
+
+        let scenario = &scenarios[0];
+        assert_eq!(scenario.locale, Locale::De);
+        assert_eq!(scenario.rates.len(), 2);
+        assert_eq!(scenario.rates[1].get_val("EUR", "USD"), Some(1.1));
+        assert_eq!(scenario.news[0].title.as_deref(), Some("ECB meeting"));
+
+        assert!(matches!(
+            parse_scenarios(r#"[{"name": "bad", "from": "EUR", "to": "USD", "rates": [[1]]}]"#),
+            Err(EvalError::Fixture(_))
+        ));
+    }
+}
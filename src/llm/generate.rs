@@ -1,48 +1,255 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::Duration;
 
-use crate::environment::{self, get_system_prompt};
-use crate::llm::prompt::{get_date_prompt, get_news_prompt};
-use chrono::Utc;
-use reqwest;
+use crate::environment;
+use crate::llm::backend::{get_backend, LlmBackend, LlmError};
+use crate::llm::guardrails::Guardrails;
+use crate::llm::prompt::{format_news_prompt, get_date_prompt, get_news_prompt, get_system_prompt};
+use crate::llm::tools::chat_with_tools;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use serde_json::Value;
-use tokio::join;
+use tokio::sync::watch;
 
 use crate::database;
-use crate::environment::get_ollama_model;
+use crate::database::llm_result::LlmResultKind;
+use crate::i18n::Locale;
+use crate::utils::search::SearchResult;
 
 pub struct GenerationResult {
     pub content: String,
     pub search_duration: Duration,
+    /// Time from sending the request to receiving the first token
+    pub first_token_duration: Duration,
+    /// Time from sending the request to receiving the last token
     pub total_duration: Duration,
-    pub load_duration: Duration,
-    pub prompt_eval_duration: Duration,
-    pub eval_duration: Duration,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    /// Number of tools the model called before answering
+    pub tool_calls: usize,
+    /// When the reused response was generated, `None` for fresh responses
+    pub cached_at: Option<NaiveDateTime>,
+}
+
+/// How a response is generated, shared by reports and answers
+pub struct GenerationOptions<'a> {
+    /// Server whose system prompt is used
+    pub guild_id: Option<u64>,
+    /// Language the model is asked to answer in
+    pub locale: Locale,
+    /// Generate a new report instead of reusing a cached one, answers are never reused
+    pub refresh: bool,
+    /// Checks every response has to pass, cached or not
+    pub guardrails: &'a Guardrails,
+    /// Receives the text generated so far after every received chunk
+    pub progress: Option<watch::Sender<String>>,
+}
+
+/// Locks of the responses being generated, so identical requests arriving
+/// together wait for the first one and reuse its response
+static GENERATING: LazyLock<Mutex<HashMap<u64, Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 64-bit FNV-1a of `parts`, each prefixed with its length.
+///
+/// Hashes are stored with the responses, unlike `DefaultHasher` it gives the
+/// same value with every Rust release.
+fn get_hash(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        let len = (part.len() as u64).to_le_bytes();
+        for byte in len.iter().chain(part.as_bytes()) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Lock of the response identified by `key`, held while it is generated
+fn get_generation_lock(key: &str) -> Arc<tokio::sync::Mutex<()>> {
+    let mut generating = GENERATING.lock().unwrap();
+    // Forget the locks nobody holds anymore
+    generating.retain(|_, lock| Arc::strong_count(lock) > 1);
+    generating.entry(get_hash(&[key])).or_default().clone()
+}
+
+/// Identify a report by everything it is generated from besides the searches.
+///
+/// News and date search results differ from one search to the next and only
+/// add colour, they would make every lookup miss. The day is part of the
+/// context instead, as the date search depends on it.
+fn get_context_hash(model: &str, cache_key: &str, system_prompt: &str, date: NaiveDate) -> String {
+    let tools = format!(
+        "{}:{}",
+        environment::get_llm_tools_enabled(),
+        environment::get_llm_tool_max_steps()
+    );
+    let date = date.to_string();
+    format!(
+        "{:016x}",
+        get_hash(&[model, cache_key, system_prompt, &tools, &date])
+    )
+}
+
+/// Whether a response generated at `time` can still be reused at `now`
+fn is_fresh(time: NaiveDateTime, now: NaiveDateTime, ttl: u64) -> bool {
+    (now - time).num_seconds() <= i64::try_from(ttl).unwrap_or(i64::MAX)
+}
+
+/// Identify the backend and model, responses of other models are not reused
+fn get_model_key(backend: &dyn LlmBackend) -> String {
+    format!("{}:{}", backend.name(), backend.model())
 }
 
 impl GenerationResult {
-    pub fn error(e: String) -> GenerationResult {
-        let error_duration = Duration::from_nanos(0);
-        return GenerationResult {
-            content: e,
-            search_duration: error_duration,
-            total_duration: error_duration,
-            load_duration: error_duration,
-            prompt_eval_duration: error_duration,
-            eval_duration: error_duration,
+    /// Time spent generating tokens after the first one arrived
+    pub fn eval_duration(&self) -> Duration {
+        self.total_duration
+            .saturating_sub(self.first_token_duration)
+    }
+
+    /// Generation speed, when the backend reported a token count
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let secs = self.eval_duration().as_secs_f64();
+        match self.completion_tokens {
+            Some(tokens) if secs > 0.0 => Some(tokens as f64 / secs),
+            _ => None,
+        }
+    }
+}
+
+/// Run the chat with the tools loop and turn it into a [`GenerationResult`].
+///
+/// Responses failing `guardrails` are regenerated up to `LLM_GUARDRAIL_RETRIES`
+/// times, telling the model what was wrong.
+async fn complete(
+    backend: &dyn LlmBackend,
+    mut messages: Vec<Value>,
+    search_duration: Duration,
+    guardrails: &Guardrails,
+    progress: Option<&watch::Sender<String>>,
+) -> Result<(GenerationResult, String), LlmError> {
+    let retries = environment::get_llm_guardrail_retries();
+    let mut attempt = 0;
+
+    loop {
+        let (completion, tool_calls) = match chat_with_tools(backend, &mut messages, progress).await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                log::error!(
+                    "Failed to generate with {} ({}): {}",
+                    backend.name(),
+                    backend.model(),
+                    e
+                );
+                return Err(e);
+            }
         };
+
+        let e = match guardrails.check(&completion.content) {
+            Ok(content) => {
+                return Ok((
+                    GenerationResult {
+                        content,
+                        search_duration,
+                        first_token_duration: completion.first_token_duration,
+                        total_duration: completion.total_duration,
+                        prompt_tokens: completion.prompt_tokens,
+                        completion_tokens: completion.completion_tokens,
+                        tool_calls,
+                        cached_at: None,
+                    },
+                    completion.raw,
+                ))
+            }
+            Err(e) => e,
+        };
+
+        log::warn!(
+            "Rejected response of {} (attempt {}): {}",
+            backend.model(),
+            attempt + 1,
+            e
+        );
+        if attempt >= retries {
+            return Err(LlmError::Rejected(e));
+        }
+        attempt += 1;
+
+        messages.push(backend.assistant_message(&completion));
+        messages.push(serde_json::json!({
+            "role": "user",
+            "content": format!("Your answer was rejected because {}. Write it again without this problem.", e)
+        }));
     }
 }
 
 /// Generate sentence using language model
-pub async fn generate_sentence(user_prompt: &str) -> GenerationResult {
-    let base_url = environment::get_ollama_url();
+///
+/// The response is streamed from the backend selected by `LLM_BACKEND`.
+///
+/// `cache_key` stands for everything the report about the currency `pair` is
+/// written from besides the searches, such as the prompt rendered without
+/// news. A report generated for the same key, model, system prompt and day
+/// within `LLM_CACHE_TTL` is reused unless `options.refresh` is set.
+///
+/// Otherwise `prompt` is awaited for the prompt and the news it was rendered
+/// with, so that nothing is searched for a reused report.
+///
+/// Fails with [`LlmError::Disabled`] without searching when `LLM_BACKEND` is `none`.
+pub async fn generate_sentence(
+    cache_key: &str,
+    pair: (&str, &str),
+    prompt: impl Future<Output = (String, Vec<SearchResult>)>,
+    options: GenerationOptions<'_>,
+) -> Result<GenerationResult, LlmError> {
+    let GenerationOptions {
+        guild_id,
+        locale,
+        refresh,
+        guardrails,
+        progress,
+    } = options;
+    let backend = get_backend().ok_or(LlmError::Disabled)?;
 
     let datetime = Utc::now();
-    let search_start = std::time::Instant::now();
+    let system_prompt = get_system_prompt(guild_id, locale);
+    let model = get_model_key(backend.as_ref());
+    let context_hash = get_context_hash(&model, cache_key, &system_prompt, datetime.date_naive());
+
+    let lock = get_generation_lock(&context_hash);
+    let _generating = lock.lock().await;
 
-    let date_prompt_fut = get_date_prompt(datetime);
-    let news_prompt_fut = get_news_prompt(datetime);
-    let (date_prompt, news_prompt) = join!(date_prompt_fut, news_prompt_fut);
+    let ttl = environment::get_llm_cache_ttl();
+    if !refresh && ttl > 0 {
+        if let Some((content, time)) = database::llm_result::get_cached_llm_result(
+            &model,
+            &context_hash,
+        )
+        .filter(|(content, time)| {
+            is_fresh(*time, Utc::now().naive_utc(), ttl) && guardrails.check(content).is_ok()
+        }) {
+            log::info!("Reusing response generated at {} by {}", time, model);
+            return Ok(GenerationResult {
+                content,
+                search_duration: Duration::ZERO,
+                first_token_duration: Duration::ZERO,
+                total_duration: Duration::ZERO,
+                prompt_tokens: None,
+                completion_tokens: None,
+                tool_calls: 0,
+                cached_at: Some(time),
+            });
+        }
+    }
+
+    let search_start = std::time::Instant::now();
+    let (user_prompt, news) = prompt.await;
+    let news_prompt = format_news_prompt(datetime, &news);
+    let date_prompt = get_date_prompt(pair.0, pair.1, datetime).await;
     let search_duration = search_start.elapsed();
 
     let mut messages = vec![];
@@ -54,7 +261,7 @@ pub async fn generate_sentence(user_prompt: &str) -> GenerationResult {
         }));
     }
 
-    if !date_prompt.is_empty() {
+    if !date_prompt.trim().is_empty() {
         messages.push(serde_json::json!({
             "role": "system",
             "content": date_prompt
@@ -63,7 +270,7 @@ pub async fn generate_sentence(user_prompt: &str) -> GenerationResult {
 
     messages.push(serde_json::json!({
         "role": "system",
-        "content": get_system_prompt()
+        "content": system_prompt
     }));
 
     messages.push(serde_json::json!({
@@ -71,92 +278,169 @@ pub async fn generate_sentence(user_prompt: &str) -> GenerationResult {
         "content": user_prompt
     }));
 
-    let url = base_url + "/api/chat";
-    let json = serde_json::json!({
-        "model": get_ollama_model(),
-        "messages": messages,
-        "stream": false
-    });
-
-    let json_string = json.to_string();
-    log::debug!("json_string: {}", json_string);
-
-    let client = match reqwest::ClientBuilder::new()
-        .timeout(Duration::new(60 * 5, 0))
-        .build()
-    {
-        Ok(client) => client,
-        Err(e) => {
-            log::error!("Failed to create a client: {}", e);
-            return GenerationResult::error(format!(
-                "Error generating response: Failed to create a client ({})",
-                e
-            ));
-        }
-    };
-    let res = match client
-        .post(url)
-        .header("accept", "application/json")
-        .header("content-type", "application/json")
-        .body(json_string)
-        .send()
-        .await
-    {
-        Ok(response) => response,
-        Err(e) => {
-            log::error!("Failed to send request: {}", e);
-            return GenerationResult::error(format!(
-                "Error generating response: Failed to send request ({})",
-                e
-            ));
-        }
-    };
-
-    let text = match res.text().await {
-        Ok(body) => body,
-        Err(e) => {
-            log::error!("Failed to get response text: {}", e);
-            return GenerationResult::error(format!(
-                "Error generating response: Failed to retrieve response text ({})",
-                e
-            ));
-        }
-    };
+    let (result, raw) = complete(
+        backend.as_ref(),
+        messages,
+        search_duration,
+        guardrails,
+        progress.as_ref(),
+    )
+    .await?;
 
-    database::llm_result::save_llm_result(user_prompt, &text);
+    database::llm_result::save_llm_result(
+        LlmResultKind::Report,
+        &model,
+        &user_prompt,
+        &context_hash,
+        &raw,
+        Some(&result.content),
+    );
 
-    log::debug!("text: {}", &text);
+    Ok(result)
+}
 
-    let response: Value = match serde_json::from_str(&text) {
-        Ok(parsed) => parsed,
-        Err(e) => {
-            log::error!("Failed to parse JSON response: {}", e);
-            return GenerationResult::error(format!(
-                "Error generating response: Failed to parse JSON ({})",
-                e
-            ));
-        }
-    };
-
-    let content = response["message"]["content"]
-        .as_str()
-        .and_then(|content| Some(content.to_string()))
-        .unwrap_or(format!(
-            "Error generating response: fail to find [message][content]\nRaw JSON:\n```{}```",
-            &text
-        ));
+/// Answer a free-form question of a user
+///
+/// `history_prompt` describes the stored rates of the currency `pair` that
+/// news are searched for, `conversation` holds the
+/// earlier `(role, content)` messages of the user's conversation.
+pub async fn answer_question(
+    question: &str,
+    pair: (&str, &str),
+    history_prompt: &str,
+    conversation: &[(String, String)],
+    options: GenerationOptions<'_>,
+) -> Result<GenerationResult, LlmError> {
+    let GenerationOptions {
+        guild_id,
+        locale,
+        guardrails,
+        progress,
+        ..
+    } = options;
+    let backend = get_backend().ok_or(LlmError::Disabled)?;
 
-    let total_duration = Duration::from_nanos(response["total_duration"].as_u64().unwrap_or(0));
-    let load_duration = Duration::from_nanos(response["load_duration"].as_u64().unwrap_or(0));
-    let prompt_eval_duration =
-        Duration::from_nanos(response["prompt_eval_duration"].as_u64().unwrap_or(0));
-    let eval_duration = Duration::from_nanos(response["eval_duration"].as_u64().unwrap_or(0));
-    return GenerationResult {
-        content,
+    let search_start = std::time::Instant::now();
+    let news_prompt = get_news_prompt(pair.0, pair.1, Utc::now()).await;
+    let search_duration = search_start.elapsed();
+
+    let mut messages = vec![];
+
+    if !news_prompt.trim().is_empty() {
+        messages.push(serde_json::json!({
+            "role": "system",
+            "content": news_prompt
+        }));
+    }
+
+    messages.push(serde_json::json!({
+        "role": "system",
+        "content": get_system_prompt(guild_id, locale)
+    }));
+
+    messages.push(serde_json::json!({
+        "role": "system",
+        "content": history_prompt
+    }));
+
+    for (role, content) in conversation {
+        messages.push(serde_json::json!({
+            "role": role,
+            "content": content
+        }));
+    }
+
+    messages.push(serde_json::json!({
+        "role": "user",
+        "content": question
+    }));
+
+    let (result, raw) = complete(
+        backend.as_ref(),
+        messages,
         search_duration,
-        total_duration,
-        load_duration,
-        prompt_eval_duration,
-        eval_duration,
-    };
+        guardrails,
+        progress.as_ref(),
+    )
+    .await?;
+
+    // Answers depend on the conversation, they are kept but never reused
+    database::llm_result::save_llm_result(
+        LlmResultKind::Answer,
+        &get_model_key(backend.as_ref()),
+        question,
+        "",
+        &raw,
+        Some(&result.content),
+    );
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_fresh() {
+        let time = NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        assert!(is_fresh(time, time, 3600));
+        assert!(is_fresh(time, time + chrono::Duration::seconds(3600), 3600));
+        assert!(!is_fresh(
+            time,
+            time + chrono::Duration::seconds(3601),
+            3600
+        ));
+        assert!(is_fresh(time, time + chrono::Duration::days(365), u64::MAX));
+    }
+
+    #[test]
+    fn test_context_hash() {
+        // Stable across builds, the hashes are stored
+        assert_eq!(get_hash(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(get_hash(&["EUR to USD"]), 0x3fe1_93d9_7ae2_e7e2);
+        assert_ne!(get_hash(&["ab", "c"]), get_hash(&["a", "bc"]));
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let hash = get_context_hash("ollama:model", "EUR to USD", "Be brief", date);
+        assert_eq!(
+            hash,
+            get_context_hash("ollama:model", "EUR to USD", "Be brief", date)
+        );
+
+        // Every input makes a report of its own
+        for other in [
+            get_context_hash("openai:model", "EUR to USD", "Be brief", date),
+            get_context_hash("ollama:model", "EUR to JPY", "Be brief", date),
+            get_context_hash("ollama:model", "EUR to USD", "Be long", date),
+            get_context_hash(
+                "ollama:model",
+                "EUR to USD",
+                "Be brief",
+                date.succ_opt().unwrap(),
+            ),
+        ] {
+            assert_ne!(hash, other);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generation_lock() {
+        let lock = get_generation_lock("test_generation_lock");
+        let generating = lock.lock().await;
+
+        // The same report waits for the one being generated, others do not
+        let same = get_generation_lock("test_generation_lock");
+        assert!(Arc::ptr_eq(&lock, &same));
+        assert!(same.try_lock().is_err());
+        assert!(get_generation_lock("test_generation_lock_other")
+            .try_lock()
+            .is_ok());
+
+        drop(generating);
+        assert!(same.try_lock().is_ok());
+    }
 }
@@ -0,0 +1,274 @@
+use thiserror::Error;
+
+use crate::environment;
+
+/// Mentions that would ping a whole server, always blocked
+const MASS_MENTIONS: &[&str] = &["@everyone", "@here"];
+
+/// Reasoning blocks some models emit before answering
+const REASONING_TAGS: &[(&str, &str)] = &[("<think>", "</think>"), ("<thinking>", "</thinking>")];
+
+#[derive(Debug, Error)]
+pub enum GuardrailError {
+    #[error("the response is empty")]
+    Empty,
+
+    #[error("the response has {0} characters, at most {1} are allowed")]
+    TooLong(usize, usize),
+
+    #[error("the response contains the blocked term '{0}'")]
+    Blocked(String),
+
+    #[error("the figure {0} does not match the exchange rates")]
+    WrongFigure(String),
+}
+
+/// Checks run on every response before it is posted
+pub struct Guardrails {
+    pub max_length: usize,
+    /// Lowercase terms the response may not contain
+    pub blocklist: Vec<String>,
+    /// Values a figure next to one of `currencies` may quote
+    pub known_values: Vec<f64>,
+    pub currencies: Vec<String>,
+    /// Allowed relative difference between a figure and a known value
+    pub tolerance: f64,
+}
+
+impl Guardrails {
+    /// Guardrails configured by the environment for figures of `currencies`
+    pub fn new(known_values: Vec<f64>, currencies: &[&str]) -> Self {
+        Guardrails {
+            max_length: environment::get_llm_max_length(),
+            blocklist: environment::get_llm_blocklist()
+                .iter()
+                .map(|term| term.to_lowercase())
+                .collect(),
+            known_values,
+            currencies: currencies.iter().map(|c| c.to_uppercase()).collect(),
+            tolerance: environment::get_llm_number_tolerance(),
+        }
+    }
+
+    /// Clean up `text` and check it, returns the text to post
+    pub fn check(&self, text: &str) -> Result<String, GuardrailError> {
+        let text = strip_reasoning(text).trim().to_string();
+
+        if text.is_empty() {
+            return Err(GuardrailError::Empty);
+        }
+        self.check_length(&text)?;
+        self.check_terms(&text)?;
+        self.check_figures(&text)?;
+
+        Ok(text)
+    }
+
+    pub fn check_length(&self, text: &str) -> Result<(), GuardrailError> {
+        let length = text.chars().count();
+        if length > self.max_length {
+            return Err(GuardrailError::TooLong(length, self.max_length));
+        }
+        Ok(())
+    }
+
+    pub fn check_terms(&self, text: &str) -> Result<(), GuardrailError> {
+        let lowercase = text.to_lowercase();
+        match MASS_MENTIONS
+            .iter()
+            .map(|term| term.to_string())
+            .chain(self.blocklist.iter().cloned())
+            .find(|term| contains_term(&lowercase, term))
+        {
+            Some(term) => Err(GuardrailError::Blocked(term)),
+            None => Ok(()),
+        }
+    }
+
+    /// Every figure next to one of `currencies` has to be a known value
+    pub fn check_figures(&self, text: &str) -> Result<(), GuardrailError> {
+        match get_currency_figures(text, &self.currencies)
+            .into_iter()
+            .find(|figure| !self.is_known(figure))
+        {
+            Some(figure) => Err(GuardrailError::WrongFigure(figure)),
+            None => Ok(()),
+        }
+    }
+
+    fn is_known(&self, figure: &str) -> bool {
+        parse_figure(figure).iter().any(|(value, decimals)| {
+            // Rounding to the quoted number of decimals is fine
+            let rounding = 0.5 * 10f64.powi(-(*decimals as i32));
+            self.known_values.iter().any(|known| {
+                (value - known.abs()).abs() <= rounding.max(known.abs() * self.tolerance)
+            })
+        })
+    }
+}
+
+/// Remove `<think>` blocks, an unclosed block hides everything after it
+pub fn strip_reasoning(text: &str) -> String {
+    let mut text = text.to_string();
+    for (open, close) in REASONING_TAGS {
+        while let Some(start) = text.find(open) {
+            let end = text[start..]
+                .find(close)
+                .map(|i| start + i + close.len())
+                .unwrap_or(text.len());
+            text.replace_range(start..end, "");
+        }
+    }
+    text
+}
+
+/// Whether `term` appears in `text` on word boundaries
+fn contains_term(text: &str, term: &str) -> bool {
+    if term.is_empty() {
+        return false;
+    }
+    text.match_indices(term).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + term.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Decimal numbers written next to one of `currencies`, e.g. `1.0823 USD` or
+/// `USD 1,08`. Whole numbers and percentages are not figures.
+fn get_currency_figures(text: &str, currencies: &[String]) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut figures = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len()
+            && (chars[i].is_ascii_digit()
+                || (matches!(chars[i], '.' | ',')
+                    && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())))
+        {
+            i += 1;
+        }
+        let number: String = chars[start..i].iter().collect();
+
+        let is_decimal = number.contains(['.', ',']);
+        let is_percentage = chars[i..]
+            .iter()
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|c| *c == '%');
+        if !is_decimal || is_percentage {
+            continue;
+        }
+
+        let after: String = chars[i..]
+            .iter()
+            .skip_while(|c| c.is_whitespace())
+            .take(3)
+            .collect();
+        let before: String = chars[..start]
+            .iter()
+            .rev()
+            .skip_while(|c| c.is_whitespace())
+            .take(3)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        if currencies.iter().any(|c| *c == after || *c == before) {
+            figures.push(number);
+        }
+    }
+
+    figures
+}
+
+/// Possible values of a figure with their number of decimals.
+///
+/// A single separator followed by three digits is either a decimal or a
+/// thousands separator, both readings are returned.
+fn parse_figure(figure: &str) -> Vec<(f64, usize)> {
+    let decimal_at = figure.rfind(['.', ',']);
+    let mut readings = vec![];
+
+    if let Some(at) = decimal_at {
+        let whole: String = figure[..at].chars().filter(char::is_ascii_digit).collect();
+        let fraction = &figure[at + 1..];
+        if let Ok(value) = format!("{}.{}", whole, fraction).parse() {
+            readings.push((value, fraction.len()));
+        }
+    }
+
+    let separators = figure.matches(['.', ',']).count();
+    if decimal_at.is_none() || (separators == 1 && figure.len() - decimal_at.unwrap() == 4) {
+        let digits: String = figure.chars().filter(char::is_ascii_digit).collect();
+        if let Ok(value) = digits.parse() {
+            readings.push((value, 0));
+        }
+    }
+
+    readings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_guardrails(known_values: Vec<f64>) -> Guardrails {
+        Guardrails {
+            max_length: 200,
+            blocklist: vec!["scam".to_string()],
+            known_values,
+            currencies: vec!["EUR".to_string(), "USD".to_string()],
+            tolerance: 0.005,
+        }
+    }
+
+    #[test]
+    fn test_strip_reasoning() {
+        assert_eq!(
+            strip_reasoning("<think>hmm</think>EUR is up.<think>unclosed"),
+            "EUR is up."
+        );
+    }
+
+    #[test]
+    fn test_figures_match_rates() {
+        let guardrails = get_guardrails(vec![1.0823, 1.0790, -0.0033]);
+        assert!(guardrails
+            .check("1 EUR is 1.08 USD, up 0.0033 USD.")
+            .is_ok());
+        assert!(guardrails.check("1 EUR ist 1,0823 USD.").is_ok());
+        assert!(matches!(
+            guardrails.check("1 EUR is 1.25 USD."),
+            Err(GuardrailError::WrongFigure(_))
+        ));
+        // Percentages and figures of other currencies are not checked
+        assert!(guardrails
+            .check("Up 0.31% while 1 GBP is 1.17 CHF.")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_blocklist_and_length() {
+        let guardrails = get_guardrails(vec![]);
+        assert!(matches!(
+            guardrails.check("Hey @everyone, look!"),
+            Err(GuardrailError::Blocked(_))
+        ));
+        assert!(matches!(
+            guardrails.check("What a Scam."),
+            Err(GuardrailError::Blocked(_))
+        ));
+        assert!(guardrails.check("Scampi for everyone.").is_ok());
+        assert!(matches!(
+            guardrails.check(&"a".repeat(201)),
+            Err(GuardrailError::TooLong(201, 200))
+        ));
+    }
+}
@@ -1,2 +1,10 @@
+pub mod backend;
+pub mod eval;
 pub mod generate;
+pub mod guardrails;
+pub mod narrative;
+pub mod ollama;
+pub mod openai;
 pub mod prompt;
+pub mod template;
+pub mod tools;
@@ -0,0 +1,200 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::{
+    environment,
+    exchange_rate::ExchangeRateMap,
+    i18n::{fill, Locale},
+    utils::stats::{get_series, get_streak},
+};
+
+/// Number of days looked at for the high/low context
+const RANGE_DAYS: i64 = 30;
+
+fn get_magnitude(pct: f64, locale: Locale) -> &'static str {
+    let magnitudes = locale.catalog().narrative_magnitudes;
+    match pct.abs() {
+        p if p < 0.1 => magnitudes[0],
+        p if p < 0.5 => magnitudes[1],
+        p if p < 1.0 => magnitudes[2],
+        _ => magnitudes[3],
+    }
+}
+
+fn get_weekday_context(date: NaiveDate, locale: Locale) -> String {
+    let catalog = locale.catalog();
+    match date.weekday() {
+        Weekday::Sat | Weekday::Sun => catalog.narrative_weekend.to_string(),
+        Weekday::Mon => catalog.narrative_week_start.to_string(),
+        Weekday::Fri => catalog.narrative_week_end.to_string(),
+        weekday => fill(
+            catalog.narrative_midweek,
+            &[("WEEKDAY", locale.format_weekday(weekday))],
+        ),
+    }
+}
+
+/// Describe the latest move of `from` to `to` without a language model.
+///
+/// Used when the LLM is disabled, fails or times out. The output only depends
+/// on `rates` and `locale`, so the same data always gives the same text.
+pub fn get_narrative(rates: &[ExchangeRateMap], from: &str, to: &str, locale: Locale) -> String {
+    let catalog = locale.catalog();
+    let data = get_series(rates, from, to);
+    let threshold = environment::get_exchange_rate_change_threshold();
+
+    let (curr_date, curr_val) = match data.last() {
+        Some(last) => *last,
+        None => return fill(catalog.narrative_no_data, &[("FROM", from), ("TO", to)]),
+    };
+
+    let weekday = locale.format_weekday(curr_date.weekday());
+    let date = locale.format_date(curr_date);
+    let curr = locale.format_number(curr_val, 4);
+    let mut sentences = vec![];
+
+    match data.len().checked_sub(2).and_then(|i| data.get(i)) {
+        Some((last_date, last_val)) => {
+            let diff = curr_val - last_val;
+            let pct = diff / last_val * 100.0;
+            let prev = locale.format_number(*last_val, 4);
+            let last_date = locale.format_date(*last_date);
+
+            if diff.abs() < threshold {
+                sentences.push(fill(
+                    catalog.narrative_unchanged,
+                    &[
+                        ("WEEKDAY", weekday),
+                        ("DATE", &date),
+                        ("FROM", from),
+                        ("CURR", &curr),
+                        ("TO", to),
+                        ("PREV", &prev),
+                        ("LAST_DATE", &last_date),
+                    ],
+                ));
+            } else {
+                sentences.push(fill(
+                    if diff > 0.0 {
+                        catalog.narrative_rose
+                    } else {
+                        catalog.narrative_fell
+                    },
+                    &[
+                        ("WEEKDAY", weekday),
+                        ("DATE", &date),
+                        ("FROM", from),
+                        ("MAGNITUDE", get_magnitude(pct, locale)),
+                        ("TO", to),
+                        ("DIFF", &locale.format_number(diff.abs(), 4)),
+                        ("PCT", &locale.format_signed_number(pct, 2)),
+                        ("PREV", &prev),
+                        ("LAST_DATE", &last_date),
+                        ("CURR", &curr),
+                    ],
+                ));
+            }
+        }
+        None => sentences.push(fill(
+            catalog.narrative_single,
+            &[
+                ("WEEKDAY", weekday),
+                ("DATE", &date),
+                ("FROM", from),
+                ("CURR", &curr),
+                ("TO", to),
+            ],
+        )),
+    }
+
+    let (streak, direction) = get_streak(&data, threshold);
+    if streak >= 2 {
+        sentences.push(fill(
+            if direction > 0.0 {
+                catalog.narrative_gains_streak
+            } else {
+                catalog.narrative_losses_streak
+            },
+            &[("COUNT", &streak.to_string())],
+        ));
+    }
+
+    let days = RANGE_DAYS.to_string();
+    let range_start = curr_date - chrono::Duration::days(RANGE_DAYS);
+    let window: Vec<&(NaiveDate, f64)> = data.iter().filter(|(d, _)| *d > range_start).collect();
+    if window.len() >= 2 {
+        let (high_date, high) = **window.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        let (low_date, low) = **window.iter().min_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+
+        if curr_val >= high {
+            sentences.push(fill(catalog.narrative_highest, &[("DAYS", &days)]));
+        } else if curr_val <= low {
+            sentences.push(fill(catalog.narrative_lowest, &[("DAYS", &days)]));
+        } else {
+            sentences.push(fill(
+                catalog.narrative_range,
+                &[
+                    (
+                        "BELOW",
+                        &locale.format_number((high - curr_val) / high * 100.0, 2),
+                    ),
+                    ("DAYS", &days),
+                    ("HIGH", &locale.format_number(high, 4)),
+                    ("HIGH_DATE", &locale.format_date(high_date)),
+                    (
+                        "ABOVE",
+                        &locale.format_number((curr_val - low) / low * 100.0, 2),
+                    ),
+                    ("LOW", &locale.format_number(low, 4)),
+                    ("LOW_DATE", &locale.format_date(low_date)),
+                ],
+            ));
+        }
+    }
+
+    sentences.push(get_weekday_context(curr_date, locale));
+
+    sentences.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::get_daily_rates;
+
+    fn get_rates(values: &[f64]) -> Vec<ExchangeRateMap> {
+        get_daily_rates(&[("USD", values)])
+    }
+
+    #[test]
+    fn test_narrative_rise() {
+        let rates = get_rates(&[1.00, 1.01, 1.02, 1.05]);
+        let narrative = get_narrative(&rates, "EUR", "USD", Locale::En);
+        assert!(
+            narrative.contains("EUR rose sharply against USD"),
+            "{narrative}"
+        );
+        assert!(narrative.contains("3 gains in a row"), "{narrative}");
+        assert!(
+            narrative.contains("highest level in 30 days"),
+            "{narrative}"
+        );
+    }
+
+    #[test]
+    fn test_narrative_is_deterministic() {
+        let rates = get_rates(&[1.10, 1.08, 1.09, 1.07, 1.085]);
+        assert_eq!(
+            get_narrative(&rates, "EUR", "USD", Locale::En),
+            get_narrative(&rates, "EUR", "USD", Locale::En)
+        );
+    }
+
+    #[test]
+    fn test_narrative_localized() {
+        let rates = get_rates(&[1.00, 1.01, 1.02, 1.05]);
+        let narrative = get_narrative(&rates, "EUR", "USD", Locale::De);
+        assert!(narrative.contains("04.01.2024"), "{narrative}");
+        assert!(narrative.contains("1,0500"), "{narrative}");
+        assert!(narrative.contains("+2,94 %"), "{narrative}");
+    }
+}
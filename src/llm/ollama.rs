@@ -0,0 +1,205 @@
+use std::time::Instant;
+
+use serde_json::Value;
+use serenity::async_trait;
+use tokio::sync::watch;
+
+use crate::llm::backend::{
+    build_client, check_status, ChatCompletion, LineReader, LlmBackend, LlmError, StreamState,
+    ToolCall,
+};
+
+/// Ollama's native `/api/chat` endpoint, streamed as NDJSON.
+pub struct OllamaBackend {
+    url: String,
+    model: String,
+}
+
+/// Chunks of a streamed `/api/chat` response, one JSON object per line
+#[derive(Default)]
+struct NdjsonStream {
+    last_chunk: Option<Value>,
+    tool_calls: Vec<ToolCall>,
+}
+
+impl NdjsonStream {
+    fn push_line(&mut self, line: &str, state: &mut StreamState) -> Result<(), LlmError> {
+        let chunk: Value =
+            serde_json::from_str(line).map_err(|e| LlmError::Parse(e.to_string()))?;
+
+        if let Some(error) = chunk.get("error").and_then(|v| v.as_str()) {
+            return Err(LlmError::Backend(error.to_string()));
+        }
+
+        if let Some(delta) = chunk["message"]["content"].as_str() {
+            state.push(delta);
+        }
+
+        // Ollama sends every tool call complete, in a single chunk
+        if let Some(calls) = chunk["message"]["tool_calls"].as_array() {
+            for call in calls {
+                self.tool_calls.push(ToolCall {
+                    id: None,
+                    name: call["function"]["name"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    arguments: call["function"]["arguments"].clone(),
+                });
+            }
+        }
+        self.last_chunk = Some(chunk);
+        Ok(())
+    }
+
+    /// The completion of `state`, once the last chunk said done
+    fn finish(self, state: StreamState, raw: String) -> Result<ChatCompletion, LlmError> {
+        let response = match self.last_chunk {
+            Some(chunk) if chunk["done"].as_bool().unwrap_or(false) => chunk,
+            _ => return Err(LlmError::Incomplete),
+        };
+        Ok(state.finish(
+            raw,
+            self.tool_calls,
+            response["prompt_eval_count"].as_u64(),
+            response["eval_count"].as_u64(),
+        ))
+    }
+}
+
+impl OllamaBackend {
+    pub fn new(url: String, model: String) -> OllamaBackend {
+        OllamaBackend { url, model }
+    }
+}
+
+#[async_trait]
+impl LlmBackend for OllamaBackend {
+    fn name(&self) -> &'static str {
+        "Ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn chat(
+        &self,
+        messages: &[Value],
+        tools: &[Value],
+        progress: Option<&watch::Sender<String>>,
+    ) -> Result<ChatCompletion, LlmError> {
+        let url = format!("{}/api/chat", self.url);
+        let mut json = serde_json::json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": true
+        });
+        if !tools.is_empty() {
+            json["tools"] = Value::from(tools);
+        }
+
+        let json_string = json.to_string();
+        log::debug!("json_string: {}", json_string);
+
+        let client = build_client()?;
+        let start = Instant::now();
+        let res = client
+            .post(url)
+            .header("accept", "application/x-ndjson")
+            .header("content-type", "application/json")
+            .body(json_string)
+            .send()
+            .await
+            .map_err(|e| LlmError::Request(e.to_string()))?;
+        let res = check_status(res).await?;
+
+        let mut reader = LineReader::new(res);
+        let mut state = StreamState::new(start, progress);
+        let mut stream = NdjsonStream::default();
+        while let Some(line) = reader.next_line().await? {
+            stream.push_line(&line, &mut state)?;
+        }
+
+        log::debug!("text: {}", &reader.raw);
+
+        stream.finish(state, reader.raw)
+    }
+
+    fn assistant_message(&self, completion: &ChatCompletion) -> Value {
+        let tool_calls: Vec<Value> = completion
+            .tool_calls
+            .iter()
+            .map(|call| {
+                serde_json::json!({
+                    "function": {
+                        "name": call.name,
+                        "arguments": call.arguments
+                    }
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "role": "assistant",
+            "content": completion.content,
+            "tool_calls": tool_calls
+        })
+    }
+
+    fn tool_message(&self, call: &ToolCall, content: &str) -> Value {
+        serde_json::json!({
+            "role": "tool",
+            "tool_name": call.name,
+            "content": content
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::backend::split_lines;
+
+    fn parse(chunks: &[&[u8]]) -> Result<ChatCompletion, LlmError> {
+        let mut state = StreamState::new(Instant::now(), None);
+        let mut stream = NdjsonStream::default();
+        for line in split_lines(chunks) {
+            stream.push_line(&line, &mut state)?;
+        }
+        stream.finish(state, String::new())
+    }
+
+    #[test]
+    fn test_ndjson_stream() {
+        let completion = parse(&[
+            b"{\"message\":{\"content\":\"EUR rose\"},\"done\":false}\n{\"mess",
+            b"age\":{\"content\":\" today.\"},\"done\":false}\n",
+            b"{\"message\":{\"content\":\"\"},\"done\":true,\"prompt_eval_count\":12}",
+        ])
+        .unwrap();
+        assert_eq!(completion.content, "EUR rose today.");
+        assert!(completion.tool_calls.is_empty());
+        assert_eq!(completion.prompt_tokens, Some(12));
+
+        let tool_calls = parse(&[
+            b"{\"message\":{\"content\":\"\",\"tool_calls\":[{\"function\":",
+            b"{\"name\":\"get_rate\",\"arguments\":{\"from\":\"EUR\"}}}]},\"done\":false}\n",
+            b"{\"message\":{\"content\":\"\"},\"done\":true}\n",
+        ])
+        .unwrap()
+        .tool_calls;
+        assert_eq!(tool_calls[0].name, "get_rate");
+        assert_eq!(tool_calls[0].arguments["from"], "EUR");
+    }
+
+    #[test]
+    fn test_ndjson_stream_errors() {
+        // Cut off before the last chunk
+        let result = parse(&[b"{\"message\":{\"content\":\"EUR\"},\"done\":false}\n"]);
+        assert!(matches!(result, Err(LlmError::Incomplete)));
+
+        let result = parse(&[b"{\"error\":\"model not found\"}\n"]);
+        assert!(matches!(result, Err(LlmError::Backend(e)) if e == "model not found"));
+    }
+}
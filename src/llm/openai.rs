@@ -0,0 +1,324 @@
+use std::time::Instant;
+
+use serde_json::Value;
+use serenity::async_trait;
+use tokio::sync::watch;
+
+use crate::llm::backend::{
+    build_client, check_status, ChatCompletion, LineReader, LlmBackend, LlmError, StreamState,
+    ToolCall,
+};
+
+/// OpenAI compatible `/v1/chat/completions` endpoint, streamed as server-sent
+/// events. Works with llama.cpp server, vLLM, LocalAI and similar servers.
+pub struct OpenAiBackend {
+    url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+/// A tool call being streamed in fragments
+#[derive(Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// `url` without a trailing `/v1` or `/`, which the endpoint path adds
+fn get_server_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    url.strip_suffix("/v1")
+        .unwrap_or(url)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Events of a streamed `/v1/chat/completions` response
+#[derive(Default)]
+struct EventStream {
+    usage: Option<Value>,
+    partial_calls: Vec<PartialToolCall>,
+    done: bool,
+}
+
+impl EventStream {
+    /// Handle an SSE line, `true` once the stream says `[DONE]`
+    fn push_line(&mut self, line: &str, state: &mut StreamState) -> Result<bool, LlmError> {
+        // Comments and other SSE fields carry nothing for us
+        let data = match line.strip_prefix("data:") {
+            Some(data) => data.trim(),
+            None => return Ok(false),
+        };
+
+        if data == "[DONE]" {
+            self.done = true;
+            return Ok(true);
+        }
+
+        let chunk: Value =
+            serde_json::from_str(data).map_err(|e| LlmError::Parse(e.to_string()))?;
+
+        if let Some(error) = chunk.get("error") {
+            let message = error["message"].as_str().unwrap_or_default().to_string();
+            return Err(LlmError::Backend(if message.is_empty() {
+                error.to_string()
+            } else {
+                message
+            }));
+        }
+
+        if let Some(delta) = chunk["choices"][0]["delta"]["content"].as_str() {
+            state.push(delta);
+        }
+
+        // Tool calls arrive in fragments, keyed by their index
+        if let Some(calls) = chunk["choices"][0]["delta"]["tool_calls"].as_array() {
+            for call in calls {
+                let index = call["index"].as_u64().unwrap_or(0) as usize;
+                while self.partial_calls.len() <= index {
+                    self.partial_calls.push(PartialToolCall::default());
+                }
+                let partial = &mut self.partial_calls[index];
+                if let Some(id) = call["id"].as_str() {
+                    partial.id = id.to_string();
+                }
+                if let Some(name) = call["function"]["name"].as_str() {
+                    partial.name.push_str(name);
+                }
+                if let Some(arguments) = call["function"]["arguments"].as_str() {
+                    partial.arguments.push_str(arguments);
+                }
+            }
+        }
+
+        if chunk["choices"][0]["finish_reason"].is_string() {
+            self.done = true;
+        }
+
+        if chunk.get("usage").is_some_and(|u| u.is_object()) {
+            self.usage = chunk.get("usage").cloned();
+        }
+        Ok(false)
+    }
+
+    /// The completion of `state` with the assembled tool calls, once a choice finished
+    fn finish(self, state: StreamState, raw: String) -> Result<ChatCompletion, LlmError> {
+        if !self.done {
+            return Err(LlmError::Incomplete);
+        }
+
+        let usage = self.usage.as_ref();
+        let prompt_tokens = usage.and_then(|u| u["prompt_tokens"].as_u64());
+        let completion_tokens = usage.and_then(|u| u["completion_tokens"].as_u64());
+
+        let tool_calls = self
+            .partial_calls
+            .into_iter()
+            .map(|partial| {
+                let arguments = match partial.arguments.trim() {
+                    "" => Value::Object(Default::default()),
+                    arguments => serde_json::from_str(arguments)
+                        .map_err(|e| LlmError::Parse(e.to_string()))?,
+                };
+                Ok(ToolCall {
+                    id: Some(partial.id),
+                    name: partial.name,
+                    arguments,
+                })
+            })
+            .collect::<Result<Vec<_>, LlmError>>()?;
+
+        Ok(state.finish(raw, tool_calls, prompt_tokens, completion_tokens))
+    }
+}
+
+impl OpenAiBackend {
+    pub fn new(url: String, api_key: Option<String>, model: String) -> OpenAiBackend {
+        OpenAiBackend {
+            url: get_server_url(&url),
+            api_key,
+            model,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmBackend for OpenAiBackend {
+    fn name(&self) -> &'static str {
+        "OpenAI compatible"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn chat(
+        &self,
+        messages: &[Value],
+        tools: &[Value],
+        progress: Option<&watch::Sender<String>>,
+    ) -> Result<ChatCompletion, LlmError> {
+        let url = format!("{}/v1/chat/completions", self.url);
+        let mut json = serde_json::json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": true,
+            "stream_options": { "include_usage": true }
+        });
+        if !tools.is_empty() {
+            json["tools"] = Value::from(tools);
+        }
+
+        let json_string = json.to_string();
+        log::debug!("json_string: {}", json_string);
+
+        let client = build_client()?;
+        let mut request = client
+            .post(url)
+            .header("accept", "text/event-stream")
+            .header("content-type", "application/json");
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let start = Instant::now();
+        let res = request
+            .body(json_string)
+            .send()
+            .await
+            .map_err(|e| LlmError::Request(e.to_string()))?;
+        let res = check_status(res).await?;
+
+        let mut reader = LineReader::new(res);
+        let mut state = StreamState::new(start, progress);
+        let mut stream = EventStream::default();
+        while let Some(line) = reader.next_line().await? {
+            if stream.push_line(&line, &mut state)? {
+                break;
+            }
+        }
+
+        log::debug!("text: {}", &reader.raw);
+
+        stream.finish(state, reader.raw)
+    }
+
+    fn assistant_message(&self, completion: &ChatCompletion) -> Value {
+        let tool_calls: Vec<Value> = completion
+            .tool_calls
+            .iter()
+            .map(|call| {
+                serde_json::json!({
+                    "id": call.id,
+                    "type": "function",
+                    "function": {
+                        "name": call.name,
+                        "arguments": call.arguments.to_string()
+                    }
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "role": "assistant",
+            "content": completion.content,
+            "tool_calls": tool_calls
+        })
+    }
+
+    fn tool_message(&self, call: &ToolCall, content: &str) -> Value {
+        serde_json::json!({
+            "role": "tool",
+            "tool_call_id": call.id,
+            "content": content
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::backend::split_lines;
+
+    fn parse(chunks: &[&[u8]]) -> Result<ChatCompletion, LlmError> {
+        let mut state = StreamState::new(Instant::now(), None);
+        let mut stream = EventStream::default();
+        for line in split_lines(chunks) {
+            if stream.push_line(&line, &mut state)? {
+                break;
+            }
+        }
+        stream.finish(state, String::new())
+    }
+
+    #[test]
+    fn test_server_url() {
+        for url in [
+            "http://localhost:8080",
+            "http://localhost:8080/",
+            "http://localhost:8080/v1",
+            "http://localhost:8080/v1/",
+        ] {
+            assert_eq!(get_server_url(url), "http://localhost:8080");
+        }
+        assert_eq!(
+            get_server_url("https://example.com/llm/v1"),
+            "https://example.com/llm"
+        );
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let completion = parse(&[
+            b": keep-alive\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"EUR",
+            b" rose\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\" today.\"}}]}\n\n",
+            b"data: {\"choices\":[],\"usage\":{\"prompt_tokens\":12,\"completion_tokens\":3}}\n\n",
+            b"data: [DONE]\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"ignored\"}}]}\n\n",
+        ])
+        .unwrap();
+        assert_eq!(completion.content, "EUR rose today.");
+        assert_eq!(completion.prompt_tokens, Some(12));
+        assert_eq!(completion.completion_tokens, Some(3));
+        assert!(completion.tool_calls.is_empty());
+    }
+
+    #[test]
+    fn test_event_stream_tool_calls() {
+        // Two calls, their arguments spread over several chunks and lines
+        let completion = parse(&[
+            b"data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",",
+            b"\"function\":{\"name\":\"get_rate\",\"arguments\":\"{\\\"fr\"}}]}}]}\n\n",
+            b"data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,",
+            b"\"function\":{\"arguments\":\"om\\\": \\\"EUR\\\"}\"}}]}}]}\n\n",
+            b"data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":1,\"id\":\"call_2\",",
+            b"\"function\":{\"name\":\"search_news\",\"arguments\":\"\"}}]}}]}\n\n",
+            b"data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            b"data: [DONE]\n\n",
+        ])
+        .unwrap();
+        let calls = completion.tool_calls;
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id.as_deref(), Some("call_1"));
+        assert_eq!(calls[0].name, "get_rate");
+        assert_eq!(calls[0].arguments["from"], "EUR");
+        assert_eq!(calls[1].name, "search_news");
+        assert!(calls[1].arguments.as_object().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_event_stream_errors() {
+        // Cut off before a choice finished or [DONE]
+        let result = parse(&[b"data: {\"choices\":[{\"delta\":{\"content\":\"EUR\"}}]}\n\n"]);
+        assert!(matches!(result, Err(LlmError::Incomplete)));
+
+        let result = parse(&[b"data: {\"error\":{\"message\":\"model not found\"}}\n\n"]);
+        assert!(matches!(result, Err(LlmError::Backend(e)) if e == "model not found"));
+
+        let result = parse(&[
+            b"data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":",
+            b"{\"name\":\"get_rate\",\"arguments\":\"{\\\"from\"}}]}}]}\n\ndata: [DONE]\n\n",
+        ]);
+        assert!(matches!(result, Err(LlmError::Parse(_))));
+    }
+}
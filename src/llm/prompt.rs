@@ -1,36 +1,239 @@
+use std::collections::HashMap;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
 
 use crate::{
+    database::prompt_template::{get_prompt_template, seed_prompt_templates, DEFAULT_GUILD_ID},
     environment,
     exchange_rate::ExchangeRateMap,
-    utils::search::{get_news, search_date},
+    i18n::Locale,
+    llm::template::{Template, TemplateContext, TemplateError, TemplateSchema, TemplateValue},
+    utils::{
+        calendar::{get_pair_events, CalendarEvent},
+        holidays::get_closing_day,
+        search::{get_news, search_date, SearchResult},
+        stats::{get_series, get_since, get_stats, get_streak},
+    },
 };
 
-pub fn render_template(
-    template: &str,
+/// Variables available to the prompt templates
+pub const PROMPT_SCHEMA: TemplateSchema = TemplateSchema {
+    variables: &[
+        ("FROM", "Currency converted from"),
+        ("TO", "Currency converted to"),
+        ("CURR", "Current rate"),
+        ("PREV", "Previous rate"),
+        ("DIFF", "Current rate minus previous rate"),
+        ("CURR_DATE", "Date of the current rate"),
+        ("LAST_DATE", "Date of the previous rate"),
+        ("PCT_CHANGE", "Percent change since the previous rate"),
+        ("DIRECTION", "up, down or flat (change below the threshold)"),
+        ("HIGH_7D", "Highest rate of the last 7 days"),
+        ("LOW_7D", "Lowest rate of the last 7 days"),
+        ("AVG_7D", "Average rate of the last 7 days"),
+        ("HIGH_30D", "Highest rate of the last 30 days"),
+        ("LOW_30D", "Lowest rate of the last 30 days"),
+        ("AVG_30D", "Average rate of the last 30 days"),
+        ("HIGH_90D", "Highest rate of the last 90 days"),
+        ("LOW_90D", "Lowest rate of the last 90 days"),
+        ("AVG_90D", "Average rate of the last 90 days"),
+        (
+            "VOLATILITY",
+            "Standard deviation of the daily percent changes of the last 30 days",
+        ),
+        (
+            "ANNUALIZED_VOLATILITY",
+            "VOLATILITY scaled to a year of 252 trading days",
+        ),
+        (
+            "MAX_DRAWDOWN",
+            "Largest percent fall from a high to a later low in the last 90 days",
+        ),
+        (
+            "Z_SCORE",
+            "Latest percent change in standard deviations of the daily changes of the last 90 days",
+        ),
+        ("STREAK", "Number of moves in a row in the same direction"),
+        (
+            "STREAK_DIRECTION",
+            "gains or losses, empty without a streak",
+        ),
+        ("LONGEST_GAINS", "Most gains in a row in the last 90 days"),
+        ("LONGEST_LOSSES", "Most losses in a row in the last 90 days"),
+        ("WEEKDAY", "Weekday of the current rate"),
+        (
+            "HOLIDAY",
+            "Name of the ECB holiday on the current date, empty otherwise",
+        ),
+    ],
+    lists: &[
+        ("NEWS", &["title", "content", "url"]),
+        ("EVENTS", &["date", "time", "currencies", "kind", "title"]),
+    ],
+};
+
+/// Days before the current rate whose calendar events are in `EVENTS`
+const PAST_EVENT_DAYS: i64 = 7;
+
+/// Days after the current rate whose calendar events are in `EVENTS`
+const UPCOMING_EVENT_DAYS: i64 = 14;
+
+pub const INCREASE_PROMPT_TEMPLATE: &str = "INCREASE_PROMPT_TEMPLATE";
+pub const DECREASE_PROMPT_TEMPLATE: &str = "DECREASE_PROMPT_TEMPLATE";
+pub const EQUAL_PROMPT_TEMPLATE: &str = "EQUAL_PROMPT_TEMPLATE";
+pub const PROMPT_TEMPLATE: &str = "PROMPT_TEMPLATE";
+pub const SYSTEM_PROMPT: &str = "SYSTEM_PROMPT";
+
+/// Names of the templates stored in the database, named after the
+/// environment variables they were seeded from
+pub const TEMPLATE_NAMES: &[&str] = &[
+    INCREASE_PROMPT_TEMPLATE,
+    DECREASE_PROMPT_TEMPLATE,
+    EQUAL_PROMPT_TEMPLATE,
+    PROMPT_TEMPLATE,
+    SYSTEM_PROMPT,
+];
+
+/// Name and template of every prompt template configured by environment variables
+fn get_configured_templates() -> Vec<(&'static str, String)> {
+    let mut templates = vec![
+        (
+            INCREASE_PROMPT_TEMPLATE,
+            environment::get_increase_prompt_template(),
+        ),
+        (
+            DECREASE_PROMPT_TEMPLATE,
+            environment::get_decrease_prompt_template(),
+        ),
+        (
+            EQUAL_PROMPT_TEMPLATE,
+            environment::get_equal_prompt_template(),
+        ),
+    ];
+    if let Some(template) = environment::get_prompt_template() {
+        templates.push((PROMPT_TEMPLATE, template));
+    }
+    templates
+}
+
+/// Whether template `name` uses the template syntax, the system prompt is plain text
+pub fn is_prompt_template(name: &str) -> bool {
+    name != SYSTEM_PROMPT
+}
+
+/// Translated default of template `name`, `None` for templates without one
+fn get_translated_template(name: &str, locale: Locale) -> Option<&'static str> {
+    let catalog = locale.catalog();
+    match name {
+        INCREASE_PROMPT_TEMPLATE => Some(catalog.increase_prompt_template),
+        DECREASE_PROMPT_TEMPLATE => Some(catalog.decrease_prompt_template),
+        EQUAL_PROMPT_TEMPLATE => Some(catalog.equal_prompt_template),
+        _ => None,
+    }
+}
+
+/// Template `name` in effect for `guild_id` in `locale`.
+///
+/// A version saved by the guild wins, then the default seeded from the
+/// environment. A default left unchanged is replaced by its translation.
+pub fn get_template(guild_id: Option<u64>, name: &str, locale: Locale) -> Option<String> {
+    if let Some(template) = guild_id.and_then(|id| get_prompt_template(id, name)) {
+        return Some(template);
+    }
+
+    let template = get_prompt_template(DEFAULT_GUILD_ID, name).or_else(|| match name {
+        INCREASE_PROMPT_TEMPLATE => Some(environment::get_increase_prompt_template()),
+        DECREASE_PROMPT_TEMPLATE => Some(environment::get_decrease_prompt_template()),
+        EQUAL_PROMPT_TEMPLATE => Some(environment::get_equal_prompt_template()),
+        SYSTEM_PROMPT => Some(environment::get_system_prompt()),
+        _ => None,
+    })?;
+
+    match get_translated_template(name, locale) {
+        Some(translated) if get_translated_template(name, Locale::En) == Some(&template) => {
+            Some(translated.to_string())
+        }
+        _ => Some(template),
+    }
+}
+
+pub fn get_system_prompt(guild_id: Option<u64>, locale: Locale) -> String {
+    let system_prompt = get_template(guild_id, SYSTEM_PROMPT, locale)
+        .unwrap_or_else(environment::get_system_prompt);
+    match locale {
+        Locale::En => system_prompt,
+        _ => format!(
+            "{}\n\nAlways answer in {}.",
+            system_prompt,
+            locale.catalog().language
+        ),
+    }
+}
+
+/// Parse and validate a template against [`PROMPT_SCHEMA`]
+pub fn parse_template(template: &str) -> Result<Template, TemplateError> {
+    let template = Template::parse(template)?;
+    template.validate(&PROMPT_SCHEMA)?;
+    Ok(template)
+}
+
+/// Validate every configured prompt template, stop the program on errors.
+///
+/// Valid templates are seeded into the database on the first run.
+pub fn ensure_templates() {
+    let mut errors = vec![];
+    for (name, template) in get_configured_templates() {
+        match parse_template(&template) {
+            Ok(template) => {
+                log::debug!("{} uses {}", name, template.get_variables().join(", "))
+            }
+            Err(e) => errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        // stop the program
+        panic!(
+            "Invalid prompt templates!\n{}\nAvailable variables: {}",
+            errors.join("\n"),
+            PROMPT_SCHEMA
+                .variables
+                .iter()
+                .map(|(name, _)| *name)
+                .chain(PROMPT_SCHEMA.lists.iter().map(|(name, _)| *name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let mut templates = get_configured_templates();
+    templates.push((SYSTEM_PROMPT, environment::get_system_prompt()));
+    seed_prompt_templates(&templates);
+}
+
+/// High, low and average of the values after `since`
+fn get_range(data: &[(NaiveDate, f64)], since: NaiveDate) -> (f64, f64, f64) {
+    match get_stats(get_since(data, since), 0.0) {
+        Some(stats) => (stats.high, stats.low, stats.average),
+        None => (f64::NAN, f64::NAN, f64::NAN),
+    }
+}
+
+/// Values of all template variables for `from` to `to`
+pub fn get_template_context(
+    rates: &[ExchangeRateMap],
     from: &str,
     to: &str,
-    diff: f64,
-    current_rate: f64,
-    last_rate: f64,
-    current_date: &str,
-    last_date: &str,
-) -> String {
-    template
-        .replace("{FROM}", from)
-        .replace("{TO}", to)
-        .replace("{CURR}", &format!("{:.4}", current_rate))
-        .replace("{PREV}", &format!("{:.4}", last_rate))
-        .replace("{DIFF}", &format!("{:.4}", diff))
-        .replace("{CURR_DATE}", current_date)
-        .replace("{LAST_DATE}", last_date)
-}
-
-pub fn get_prompt(rates: &Vec<ExchangeRateMap>, from: &str, to: &str) -> String {
-    // let failed_rate = ExchangeRateMap::failed();
+    news: &[SearchResult],
+    events: &[CalendarEvent],
+) -> TemplateContext {
     let curr_rate = rates.last().cloned().unwrap_or_default();
-    let last_rate = rates.get(rates.len() - 2).cloned().unwrap_or_default();
+    let last_rate = rates
+        .len()
+        .checked_sub(2)
+        .and_then(|i| rates.get(i))
+        .cloned()
+        .unwrap_or_default();
 
     log::debug!("Last rate: {}", last_rate);
     log::debug!("Curr rate: {}", curr_rate);
@@ -38,64 +241,271 @@ pub fn get_prompt(rates: &Vec<ExchangeRateMap>, from: &str, to: &str) -> String
     // Calculate the difference between current and last rate values for the specific currency pair
     let curr_val: f64 = curr_rate.get_val(from, to).unwrap_or(-1.0);
     let last_val: f64 = last_rate.get_val(from, to).unwrap_or(-1.0);
-
-    let curr_date = curr_rate.datetime.format("%Y-%m-%d").to_string();
-    let last_date = last_rate.datetime.format("%Y-%m-%d").to_string();
     log::debug!("Current rate value: {}", curr_val);
     log::debug!("Last rate value: {}", last_val);
 
-    // Calculate the difference
     let diff = curr_val - last_val;
-
     let threshold: f64 = environment::get_exchange_rate_change_threshold();
-
     log::debug!("Threshold: {}", threshold);
-    let template = if diff.abs() < threshold {
-        environment::get_equal_prompt_template()
+
+    let direction = if diff.abs() < threshold {
+        "flat"
     } else if diff > 0.0 {
-        environment::get_increase_prompt_template()
-    } else if diff < 0.0 {
-        environment::get_decrease_prompt_template()
+        "up"
     } else {
-        environment::get_equal_prompt_template()
+        "down"
     };
 
-    // Create the appropriate prompt based on the diff and threshold
-    let prompt = render_template(
-        &template, &from, &to, diff, curr_val, last_val, &curr_date, &last_date,
-    );
+    let curr_date = curr_rate.get_date();
+    let data = get_series(rates, from, to);
+    let (streak, streak_direction) = get_streak(&data, threshold);
+    let recent = get_stats(get_since(&data, curr_date - Duration::days(30)), threshold);
+    let quarter = get_stats(get_since(&data, curr_date - Duration::days(90)), threshold);
+
+    let text = |s: &str| TemplateValue::Text(s.to_string());
+    let mut context: TemplateContext = HashMap::from([
+        ("FROM".to_string(), text(from)),
+        ("TO".to_string(), text(to)),
+        ("CURR".to_string(), TemplateValue::number(curr_val, 4)),
+        ("PREV".to_string(), TemplateValue::number(last_val, 4)),
+        ("DIFF".to_string(), TemplateValue::number(diff, 4)),
+        (
+            "CURR_DATE".to_string(),
+            text(&curr_date.format("%Y-%m-%d").to_string()),
+        ),
+        (
+            "LAST_DATE".to_string(),
+            text(&last_rate.get_date().format("%Y-%m-%d").to_string()),
+        ),
+        (
+            "PCT_CHANGE".to_string(),
+            TemplateValue::number(diff / last_val * 100.0, 2),
+        ),
+        ("DIRECTION".to_string(), text(direction)),
+        (
+            "VOLATILITY".to_string(),
+            TemplateValue::number(recent.as_ref().map_or(0.0, |s| s.volatility), 2),
+        ),
+        (
+            "ANNUALIZED_VOLATILITY".to_string(),
+            TemplateValue::number(recent.as_ref().map_or(0.0, |s| s.annualized_volatility), 2),
+        ),
+        (
+            "MAX_DRAWDOWN".to_string(),
+            TemplateValue::number(
+                quarter
+                    .as_ref()
+                    .and_then(|s| s.max_drawdown)
+                    .map_or(0.0, |drawdown| drawdown.percent),
+                2,
+            ),
+        ),
+        (
+            "Z_SCORE".to_string(),
+            TemplateValue::number(
+                quarter.as_ref().and_then(|s| s.z_score).unwrap_or(f64::NAN),
+                2,
+            ),
+        ),
+        (
+            "STREAK".to_string(),
+            TemplateValue::number(streak as f64, 0),
+        ),
+        (
+            "STREAK_DIRECTION".to_string(),
+            text(match (streak, streak_direction > 0.0) {
+                (0, _) => "",
+                (_, true) => "gains",
+                (_, false) => "losses",
+            }),
+        ),
+        (
+            "LONGEST_GAINS".to_string(),
+            TemplateValue::number(quarter.as_ref().map_or(0.0, |s| s.longest_gains as f64), 0),
+        ),
+        (
+            "LONGEST_LOSSES".to_string(),
+            TemplateValue::number(quarter.as_ref().map_or(0.0, |s| s.longest_losses as f64), 0),
+        ),
+        (
+            "WEEKDAY".to_string(),
+            text(&curr_date.weekday().to_string()),
+        ),
+        (
+            "HOLIDAY".to_string(),
+            text(&get_closing_day(curr_date, events).unwrap_or_default()),
+        ),
+    ]);
+
+    for days in [7, 30, 90] {
+        let (high, low, avg) = get_range(&data, curr_date - Duration::days(days));
+        context.insert(format!("HIGH_{}D", days), TemplateValue::number(high, 4));
+        context.insert(format!("LOW_{}D", days), TemplateValue::number(low, 4));
+        context.insert(format!("AVG_{}D", days), TemplateValue::number(avg, 4));
+    }
+
+    let news: Vec<HashMap<String, TemplateValue>> = news
+        .iter()
+        .map(|n| {
+            HashMap::from([
+                (
+                    "title".to_string(),
+                    text(n.title.as_deref().unwrap_or("No Title")),
+                ),
+                (
+                    "content".to_string(),
+                    text(n.content.as_deref().unwrap_or_default()),
+                ),
+                ("url".to_string(), text(n.url.as_deref().unwrap_or("N/A"))),
+            ])
+        })
+        .collect();
+    context.insert("NEWS".to_string(), TemplateValue::List(news));
+
+    let events: Vec<HashMap<String, TemplateValue>> = get_pair_events(
+        events,
+        from,
+        to,
+        curr_date - Duration::days(PAST_EVENT_DAYS),
+        curr_date + Duration::days(UPCOMING_EVENT_DAYS),
+    )
+    .into_iter()
+    .map(|event| {
+        HashMap::from([
+            (
+                "date".to_string(),
+                text(&event.date.format("%Y-%m-%d").to_string()),
+            ),
+            (
+                "time".to_string(),
+                text(
+                    &event
+                        .time
+                        .map(|time| time.format("%H:%M UTC").to_string())
+                        .unwrap_or_default(),
+                ),
+            ),
+            ("currencies".to_string(), text(&event.currencies.join(", "))),
+            ("kind".to_string(), text(event.kind.name())),
+            ("title".to_string(), text(&event.title)),
+        ])
+    })
+    .collect();
+    context.insert("EVENTS".to_string(), TemplateValue::List(events));
+
+    context
+}
+
+/// Name of the template for the rates in `context`.
+///
+/// `PROMPT_TEMPLATE` is used when set. Otherwise the increase, decrease or
+/// equal template is picked by comparing the change with the threshold.
+pub fn select_template(
+    guild_id: Option<u64>,
+    locale: Locale,
+    context: &TemplateContext,
+) -> &'static str {
+    if get_template(guild_id, PROMPT_TEMPLATE, locale).is_some() {
+        return PROMPT_TEMPLATE;
+    }
+    match context.get("DIRECTION") {
+        Some(TemplateValue::Text(direction)) if direction == "up" => INCREASE_PROMPT_TEMPLATE,
+        Some(TemplateValue::Text(direction)) if direction == "down" => DECREASE_PROMPT_TEMPLATE,
+        _ => EQUAL_PROMPT_TEMPLATE,
+    }
+}
+
+/// Render the prompt for `from` to `to` with the templates of `guild_id` in `locale`
+pub fn get_prompt(
+    rates: &[ExchangeRateMap],
+    from: &str,
+    to: &str,
+    news: &[SearchResult],
+    events: &[CalendarEvent],
+    guild_id: Option<u64>,
+    locale: Locale,
+) -> String {
+    let context = get_template_context(rates, from, to, news, events);
+    let name = select_template(guild_id, locale, &context);
+    let template = get_template(guild_id, name, locale).unwrap_or_default();
+
+    // Templates are validated before they are stored, this only fails on a bug
+    let prompt = match parse_template(&template).and_then(|t| t.render(&context)) {
+        Ok(prompt) => prompt,
+        Err(e) => {
+            log::error!("Failed to render prompt template: {}", e);
+            template
+        }
+    };
 
     log::debug!("Prompt: {}", prompt);
     prompt
 }
 
-pub async fn get_news_prompt(date: DateTime<Utc>) -> String {
-    let news_list = get_news(date, 5).await;
+/// Figures a response about `from` to `to` may quote: every rate, its inverse
+/// and the numeric template variables.
+pub fn get_known_values(rates: &[ExchangeRateMap], from: &str, to: &str) -> Vec<f64> {
+    let mut values: Vec<f64> = get_series(rates, from, to)
+        .into_iter()
+        .flat_map(|(_, v)| [v, 1.0 / v])
+        .filter(|v| v.is_finite())
+        .collect();
+
+    values.extend(
+        get_template_context(rates, from, to, &[], &[])
+            .values()
+            .filter_map(|value| match value {
+                TemplateValue::Number(v, _) => Some(v.abs()),
+                _ => None,
+            }),
+    );
+    values
+}
+
+/// Describe the rates of `from` to `to` saved by the reports, oldest first, for
+/// free-form questions
+pub fn get_history_prompt(rates: &[(NaiveDateTime, f64)], from: &str, to: &str) -> String {
+    let mut prompt = format!(
+        "Today is {}. Exchange rates from {} to {} reported by the bot (UTC time: rate):",
+        Utc::now().format("%Y-%m-%d"),
+        from,
+        to
+    );
 
+    for (time, rate) in rates {
+        prompt.push_str(&format!("\n{}: {:.4}", time.format("%Y-%m-%d %H:%M"), rate));
+    }
+
+    prompt
+}
+
+/// Format news found for `date` as a system prompt
+pub fn format_news_prompt(date: DateTime<Utc>, news_list: &[SearchResult]) -> String {
     let mut prompt = match news_list.len() {
-        0 => format!(""),
+        0 => String::new(),
         _ => format!("News on {}", date.format("%d/%m/%Y")),
     };
 
     for news in news_list {
-        prompt = prompt
-            + format!(
-                "\n# {}\n{}\n[link]({})\n",
-                &news.title.unwrap_or("No Title".to_string()),
-                &news.content.unwrap_or("No content...".to_string()),
-                &news.url.unwrap_or("N/A".to_string())
-            )
-            .as_str()
+        prompt.push_str(&format!(
+            "\n# {}\n{}\n[link]({})\n",
+            news.title.as_deref().unwrap_or("No Title"),
+            news.content.as_deref().unwrap_or("No content..."),
+            news.url.as_deref().unwrap_or("N/A")
+        ));
     }
 
-    prompt = prompt + "\n";
-
     log::debug!("News Prompt: {prompt}");
-    return prompt;
+    prompt
+}
+
+pub async fn get_news_prompt(from: &str, to: &str, date: DateTime<Utc>) -> String {
+    let news_list = get_news(from, to, date, 5).await;
+    format_news_prompt(date, &news_list)
 }
 
-pub async fn get_date_prompt(date: DateTime<Utc>) -> String {
-    let news_list = search_date(date, 5).await;
+pub async fn get_date_prompt(from: &str, to: &str, date: DateTime<Utc>) -> String {
+    let news_list = search_date(from, to, date, 5).await;
 
     let mut prompt = match news_list.len() {
         0 => format!(""),
@@ -0,0 +1,719 @@
+//! Prompt template engine.
+//!
+//! Templates keep the `{NAME}` placeholders of the original templates and add
+//! blocks:
+//!
+//! ```text
+//! {#if PCT_CHANGE > 0.5}a big move{#elif DIRECTION == "down"}a drop{#else}calm{/if}
+//! {#for item in NEWS}- {item.title}{/for}
+//! ```
+//!
+//! Conditions are a single variable (truthy when non-empty or non-zero),
+//! optionally negated with `!`, or a comparison (`==`, `!=`, `>`, `>=`, `<`,
+//! `<=`) between variables, numbers and double-quoted strings. Anything in
+//! braces that is not a name or a block, such as `{ "json": true }`, is kept
+//! as text.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+#[error("line {line}, column {column}: {message}")]
+pub struct TemplateError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn error(&self, message: String) -> TemplateError {
+        TemplateError {
+            line: self.line,
+            column: self.column,
+            message,
+        }
+    }
+}
+
+/// A value available to templates
+#[derive(Debug, Clone)]
+pub enum TemplateValue {
+    Text(String),
+    /// Number with its rendered form, so that rates and percentages can use
+    /// different precision while still being comparable.
+    Number(f64, String),
+    List(Vec<HashMap<String, TemplateValue>>),
+}
+
+impl TemplateValue {
+    pub fn number(value: f64, precision: usize) -> TemplateValue {
+        TemplateValue::Number(value, format!("{:.*}", precision, value))
+    }
+
+    fn is_truthy(&self) -> bool {
+        match self {
+            TemplateValue::Text(s) => !s.is_empty(),
+            TemplateValue::Number(n, _) => *n != 0.0 && !n.is_nan(),
+            TemplateValue::List(l) => !l.is_empty(),
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            TemplateValue::Text(s) => s.clone(),
+            TemplateValue::Number(_, s) => s.clone(),
+            TemplateValue::List(l) => format!("{} items", l.len()),
+        }
+    }
+}
+
+pub type TemplateContext = HashMap<String, TemplateValue>;
+
+/// Variables known to templates, used to validate them
+pub struct TemplateSchema {
+    pub variables: &'static [(&'static str, &'static str)],
+    /// Lists usable in `{#for}` and the fields of their items
+    pub lists: &'static [(&'static str, &'static [&'static str])],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Variable(String),
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Truthy { name: String, negate: bool },
+    Compare(Operand, Operator, Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(String, Position),
+    If {
+        branches: Vec<(Condition, Vec<Node>, Position)>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        item: String,
+        list: String,
+        body: Vec<Node>,
+        position: Position,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Variable(String, Position),
+    Open(String, String, Position),
+    Close(String, Position),
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+fn is_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut line = 1;
+    let mut column = 1;
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '{' {
+            if let Some(len) = chars[i + 1..].iter().position(|c| *c == '}' || *c == '{') {
+                let end = i + 1 + len;
+                if chars[end] == '}' {
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    let position = Position { line, column };
+                    let token = if let Some(tag) = inner.strip_prefix('#') {
+                        let tag = tag.trim();
+                        let (name, args) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+                        Some(Token::Open(
+                            name.to_string(),
+                            args.trim().to_string(),
+                            position,
+                        ))
+                    } else if let Some(tag) = inner.strip_prefix('/') {
+                        Some(Token::Close(tag.trim().to_string(), position))
+                    } else if is_name(&inner) {
+                        Some(Token::Variable(inner.clone(), position))
+                    } else {
+                        None
+                    };
+
+                    if let Some(token) = token {
+                        if !text.is_empty() {
+                            tokens.push(Token::Text(std::mem::take(&mut text)));
+                        }
+                        tokens.push(token);
+                        column += end - i + 1;
+                        i = end + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        text.push(c);
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+        i += 1;
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    tokens
+}
+
+fn parse_operand(s: &str, position: Position) -> Result<Operand, TemplateError> {
+    let s = s.trim();
+    if let Some(text) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        return Ok(Operand::Text(text.to_string()));
+    }
+    if let Ok(number) = s.parse::<f64>() {
+        return Ok(Operand::Number(number));
+    }
+    if is_name(s) {
+        return Ok(Operand::Variable(s.to_string()));
+    }
+    Err(position.error(format!("Invalid operand '{}'", s)))
+}
+
+/// Comparison operators, two character ones first so that `>=` is not read as `>`
+const OPERATORS: [(&str, Operator); 6] = [
+    ("==", Operator::Eq),
+    ("!=", Operator::Ne),
+    (">=", Operator::Ge),
+    ("<=", Operator::Le),
+    (">", Operator::Gt),
+    ("<", Operator::Lt),
+];
+
+/// Split `s` at its first operator outside of quoted text
+fn split_comparison(s: &str) -> Option<(&str, Operator, &str)> {
+    let mut quoted = false;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted {
+            for (symbol, operator) in OPERATORS {
+                if let Some(right) = s[i..].strip_prefix(symbol) {
+                    return Some((&s[..i], operator, right));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn parse_condition(s: &str, position: Position) -> Result<Condition, TemplateError> {
+    if let Some((left, operator, right)) = split_comparison(s) {
+        return Ok(Condition::Compare(
+            parse_operand(left, position)?,
+            operator,
+            parse_operand(right, position)?,
+        ));
+    }
+
+    let s = s.trim();
+    let (name, negate) = match s.strip_prefix('!') {
+        Some(name) => (name.trim(), true),
+        None => (s, false),
+    };
+    if !is_name(name) {
+        return Err(position.error(format!("Invalid condition '{}'", s)));
+    }
+    Ok(Condition::Truthy {
+        name: name.to_string(),
+        negate,
+    })
+}
+
+/// Parse nodes until one of `until` closes or continues the current block
+fn parse_nodes(
+    tokens: &[Token],
+    index: &mut usize,
+    until: &[&str],
+) -> Result<(Vec<Node>, Option<Token>), TemplateError> {
+    let mut nodes = vec![];
+
+    while *index < tokens.len() {
+        let token = tokens[*index].clone();
+        *index += 1;
+
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Variable(name, position) => nodes.push(Node::Variable(name, position)),
+            // `{#elif}` and `{#else}` continue the enclosing `{#if}`
+            Token::Open(ref tag, _, _)
+                if (tag == "elif" || tag == "else") && until.contains(&tag.as_str()) =>
+            {
+                return Ok((nodes, Some(token)));
+            }
+            Token::Close(ref tag, _) if until.contains(&tag.as_str()) => {
+                return Ok((nodes, Some(token)));
+            }
+            Token::Open(tag, args, position) => match tag.as_str() {
+                "if" => {
+                    let mut branches = vec![];
+                    let mut condition = parse_condition(&args, position)?;
+                    let mut branch_position = position;
+                    let mut otherwise = vec![];
+                    loop {
+                        let (body, end) = parse_nodes(tokens, index, &["elif", "else", "if"])?;
+                        branches.push((condition, body, branch_position));
+                        match end {
+                            Some(Token::Open(tag, args, p)) if tag == "elif" => {
+                                condition = parse_condition(&args, p)?;
+                                branch_position = p;
+                            }
+                            Some(Token::Open(tag, _, _)) if tag == "else" => {
+                                let (body, end) = parse_nodes(tokens, index, &["if"])?;
+                                match end {
+                                    Some(Token::Close(_, _)) => {}
+                                    _ => {
+                                        return Err(position
+                                            .error("'{#if}' is never closed by '{/if}'".into()))
+                                    }
+                                }
+                                otherwise = body;
+                                break;
+                            }
+                            Some(Token::Close(_, _)) => break,
+                            _ => {
+                                return Err(
+                                    position.error("'{#if}' is never closed by '{/if}'".into())
+                                )
+                            }
+                        }
+                    }
+                    nodes.push(Node::If {
+                        branches,
+                        otherwise,
+                    });
+                }
+                "for" => {
+                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    let (item, list) = match parts.as_slice() {
+                        [item, "in", list] if is_name(item) && is_name(list) => {
+                            (item.to_string(), list.to_string())
+                        }
+                        _ => {
+                            return Err(position.error(format!(
+                                "Expected '{{#for item in LIST}}', found '{{#for {}}}'",
+                                args
+                            )))
+                        }
+                    };
+                    let (body, end) = parse_nodes(tokens, index, &["for"])?;
+                    match end {
+                        Some(Token::Close(_, _)) => {}
+                        _ => {
+                            return Err(
+                                position.error("'{#for}' is never closed by '{/for}'".into())
+                            )
+                        }
+                    }
+                    nodes.push(Node::For {
+                        item,
+                        list,
+                        body,
+                        position,
+                    });
+                }
+                tag => return Err(position.error(format!("Unexpected '{{#{}}}'", tag))),
+            },
+            Token::Close(tag, position) => {
+                return Err(position.error(format!("Unexpected '{{/{}}}'", tag)))
+            }
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let tokens = tokenize(source);
+        let mut index = 0;
+        let (nodes, _) = parse_nodes(&tokens, &mut index, &[])?;
+        Ok(Template { nodes })
+    }
+
+    /// Check that every variable, list and item field exists in `schema`
+    pub fn validate(&self, schema: &TemplateSchema) -> Result<(), TemplateError> {
+        validate_nodes(&self.nodes, schema, &mut vec![])
+    }
+
+    /// Names of the variables and lists used by the template
+    pub fn get_variables(&self) -> Vec<String> {
+        let mut names = vec![];
+        collect_variables(&self.nodes, &mut names);
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> Result<String, TemplateError> {
+        let mut output = String::new();
+        render_nodes(&self.nodes, &mut vec![context.clone()], &mut output)?;
+        Ok(output)
+    }
+}
+
+fn collect_variables(nodes: &[Node], names: &mut Vec<String>) {
+    let add_operand = |operand: &Operand, names: &mut Vec<String>| {
+        if let Operand::Variable(name) = operand {
+            names.push(name.clone());
+        }
+    };
+
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Variable(name, _) => names.push(name.clone()),
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                for (condition, body, _) in branches {
+                    match condition {
+                        Condition::Truthy { name, .. } => names.push(name.clone()),
+                        Condition::Compare(left, _, right) => {
+                            add_operand(left, names);
+                            add_operand(right, names);
+                        }
+                    }
+                    collect_variables(body, names);
+                }
+                collect_variables(otherwise, names);
+            }
+            Node::For { list, body, .. } => {
+                names.push(list.clone());
+                collect_variables(body, names);
+            }
+        }
+    }
+}
+
+/// `items` holds the loop variables in scope and the list they iterate
+fn validate_name(
+    name: &str,
+    schema: &TemplateSchema,
+    items: &[(String, String)],
+    position: Position,
+) -> Result<(), TemplateError> {
+    if let Some((item, field)) = name.split_once('.') {
+        let list = items
+            .iter()
+            .rev()
+            .find(|(i, _)| i == item)
+            .map(|(_, list)| list)
+            .ok_or(position.error(format!("Unknown loop variable '{}'", item)))?;
+        let fields = schema
+            .lists
+            .iter()
+            .find(|(l, _)| l == list)
+            .map(|(_, fields)| *fields)
+            .unwrap_or_default();
+        if !fields.contains(&field) {
+            return Err(position.error(format!(
+                "Unknown field '{}' of {} items, expected one of: {}",
+                field,
+                list,
+                fields.join(", ")
+            )));
+        }
+        return Ok(());
+    }
+
+    if schema.variables.iter().any(|(v, _)| *v == name)
+        || schema.lists.iter().any(|(l, _)| *l == name)
+    {
+        return Ok(());
+    }
+
+    Err(position.error(format!("Unknown variable '{}'", name)))
+}
+
+fn validate_nodes(
+    nodes: &[Node],
+    schema: &TemplateSchema,
+    items: &mut Vec<(String, String)>,
+) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Variable(name, position) => validate_name(name, schema, items, *position)?,
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                for (condition, body, position) in branches {
+                    match condition {
+                        Condition::Truthy { name, .. } => {
+                            validate_name(name, schema, items, *position)?
+                        }
+                        Condition::Compare(left, _, right) => {
+                            for operand in [left, right] {
+                                if let Operand::Variable(name) = operand {
+                                    validate_name(name, schema, items, *position)?;
+                                }
+                            }
+                        }
+                    }
+                    validate_nodes(body, schema, items)?;
+                }
+                validate_nodes(otherwise, schema, items)?;
+            }
+            Node::For {
+                item,
+                list,
+                body,
+                position,
+            } => {
+                if !schema.lists.iter().any(|(l, _)| l == list) {
+                    return Err(position.error(format!("Unknown list '{}'", list)));
+                }
+                items.push((item.clone(), list.clone()));
+                validate_nodes(body, schema, items)?;
+                items.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Item fields are stored in the loop scope under their full `item.field` name
+fn lookup<'a>(
+    name: &str,
+    scopes: &'a [TemplateContext],
+    position: Position,
+) -> Result<&'a TemplateValue, TemplateError> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|scope| scope.get(name))
+        .ok_or(position.error(format!("Unknown variable '{}'", name)))
+}
+
+fn resolve(
+    operand: &Operand,
+    scopes: &[TemplateContext],
+    position: Position,
+) -> Result<TemplateValue, TemplateError> {
+    match operand {
+        Operand::Variable(name) => lookup(name, scopes, position).cloned(),
+        Operand::Number(n) => Ok(TemplateValue::Number(*n, n.to_string())),
+        Operand::Text(s) => Ok(TemplateValue::Text(s.clone())),
+    }
+}
+
+fn evaluate(
+    condition: &Condition,
+    scopes: &[TemplateContext],
+    position: Position,
+) -> Result<bool, TemplateError> {
+    match condition {
+        Condition::Truthy { name, negate } => {
+            Ok(lookup(name, scopes, position)?.is_truthy() != *negate)
+        }
+        Condition::Compare(left, operator, right) => {
+            let left = resolve(left, scopes, position)?;
+            let right = resolve(right, scopes, position)?;
+            let ordering = match (&left, &right) {
+                (TemplateValue::Number(a, _), TemplateValue::Number(b, _)) => a.partial_cmp(b),
+                (a, b) => Some(a.render().cmp(&b.render())),
+            };
+            let ordering = match ordering {
+                Some(ordering) => ordering,
+                None => return Ok(*operator == Operator::Ne),
+            };
+            Ok(match operator {
+                Operator::Eq => ordering.is_eq(),
+                Operator::Ne => ordering.is_ne(),
+                Operator::Gt => ordering.is_gt(),
+                Operator::Ge => ordering.is_ge(),
+                Operator::Lt => ordering.is_lt(),
+                Operator::Le => ordering.is_le(),
+            })
+        }
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    scopes: &mut Vec<TemplateContext>,
+    output: &mut String,
+) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable(name, position) => {
+                output.push_str(&lookup(name, scopes, *position)?.render());
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                let mut matched = false;
+                for (condition, body, position) in branches {
+                    if evaluate(condition, scopes, *position)? {
+                        render_nodes(body, scopes, output)?;
+                        matched = true;
+                        break;
+                    }
+                }
+                if !matched {
+                    render_nodes(otherwise, scopes, output)?;
+                }
+            }
+            Node::For {
+                item,
+                list,
+                body,
+                position,
+            } => {
+                let entries = match lookup(list, scopes, *position)? {
+                    TemplateValue::List(entries) => entries.clone(),
+                    _ => return Err(position.error(format!("'{}' is not a list", list))),
+                };
+                for entry in entries {
+                    let scope: TemplateContext = entry
+                        .into_iter()
+                        .map(|(field, value)| (format!("{}.{}", item, field), value))
+                        .collect();
+                    scopes.push(scope);
+                    let result = render_nodes(body, scopes, output);
+                    scopes.pop();
+                    result?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: TemplateSchema = TemplateSchema {
+        variables: &[("FROM", ""), ("PCT_CHANGE", ""), ("HOLIDAY", "")],
+        lists: &[("NEWS", &["title", "url"])],
+    };
+
+    fn get_context() -> TemplateContext {
+        HashMap::from([
+            ("FROM".to_string(), TemplateValue::Text("USD".to_string())),
+            ("PCT_CHANGE".to_string(), TemplateValue::number(0.734, 2)),
+            ("HOLIDAY".to_string(), TemplateValue::Text(String::new())),
+            (
+                "NEWS".to_string(),
+                TemplateValue::List(vec![
+                    HashMap::from([("title".to_string(), TemplateValue::Text("A".to_string()))]),
+                    HashMap::from([("title".to_string(), TemplateValue::Text("B".to_string()))]),
+                ]),
+            ),
+        ])
+    }
+
+    fn render(source: &str) -> String {
+        Template::parse(source)
+            .unwrap()
+            .render(&get_context())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_variables_and_literal_braces() {
+        assert_eq!(
+            render("{FROM} moved {PCT_CHANGE}% { not a variable }"),
+            "USD moved 0.73% { not a variable }"
+        );
+    }
+
+    #[test]
+    fn test_render_conditionals() {
+        let source = r#"{#if PCT_CHANGE > 1}big{#elif FROM == "USD"}dollar{#else}none{/if}"#;
+        assert_eq!(render(source), "dollar");
+        assert_eq!(render("{#if !HOLIDAY}open{/if}"), "open");
+        // Operators in quoted text are part of the text
+        assert_eq!(render(r#"{#if FROM == "a>=b"}yes{#else}no{/if}"#), "no");
+        assert_eq!(render(r#"{#if FROM != "a==b"}yes{/if}"#), "yes");
+        assert_eq!(render(r#"{#if "a==b" != FROM}yes{/if}"#), "yes");
+    }
+
+    #[test]
+    fn test_render_loops() {
+        assert_eq!(render("{#for n in NEWS}[{n.title}]{/for}"), "[A][B]");
+    }
+
+    #[test]
+    fn test_render_nested_blocks() {
+        let source = r#"{#if FROM}{#for n in NEWS}{#if n.title == "B"}{n.title}{/if}{/for}{/if}"#;
+        assert_eq!(render(source), "B");
+    }
+
+    #[test]
+    fn test_validate_unknown_variable() {
+        let template = Template::parse("ok {FROM}\n  {TYPO}").unwrap();
+        assert_eq!(
+            template.validate(&SCHEMA),
+            Err(TemplateError {
+                line: 2,
+                column: 3,
+                message: "Unknown variable 'TYPO'".to_string()
+            })
+        );
+
+        let template = Template::parse("{#for n in NEWS}{n.body}{/for}").unwrap();
+        assert!(template.validate(&SCHEMA).is_err());
+    }
+
+    #[test]
+    fn test_parse_unclosed_block() {
+        assert!(Template::parse("{#if FROM}never closed").is_err());
+        assert!(Template::parse("{/for}").is_err());
+    }
+}
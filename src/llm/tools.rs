@@ -0,0 +1,304 @@
+use chrono::{Duration, NaiveDate, Utc};
+use serde_json::{json, Value};
+use tokio::sync::watch;
+
+use crate::{
+    environment,
+    exchange_rate::ExchangeRateMap,
+    llm::backend::{ChatCompletion, LlmBackend, LlmError, ToolCall},
+    utils::{
+        search::{get_providers, search_all, SearchCategory},
+        stats,
+    },
+};
+
+/// Longest history a tool call may request
+const MAX_HISTORY_DAYS: i64 = 365;
+
+/// Number of search results returned to the model
+const MAX_SEARCH_RESULTS: usize = 5;
+
+/// Function definitions offered to the model, in the OpenAI format
+pub fn get_tool_definitions() -> Vec<Value> {
+    vec![
+        json!({
+            "type": "function",
+            "function": {
+                "name": "get_rate",
+                "description": "Get the exchange rate between two currencies on a date. Without a date the latest rate is returned.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "from": { "type": "string", "description": "ISO 4217 code of the currency to convert from, e.g. USD" },
+                        "to": { "type": "string", "description": "ISO 4217 code of the currency to convert to, e.g. EUR" },
+                        "date": { "type": "string", "description": "Date in YYYY-MM-DD format" }
+                    },
+                    "required": ["from", "to"]
+                }
+            }
+        }),
+        json!({
+            "type": "function",
+            "function": {
+                "name": "get_history",
+                "description": "Get the daily exchange rates between two currencies for the last days.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "from": { "type": "string", "description": "ISO 4217 code of the currency to convert from" },
+                        "to": { "type": "string", "description": "ISO 4217 code of the currency to convert to" },
+                        "days": { "type": "integer", "description": "Number of days to look back, at most 365" }
+                    },
+                    "required": ["from", "to", "days"]
+                }
+            }
+        }),
+        json!({
+            "type": "function",
+            "function": {
+                "name": "get_stats",
+                "description": "Get statistics (latest, change, high, low, average, volatility, max drawdown, streaks, z-score of the latest change) of an exchange rate over the last days.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "from": { "type": "string", "description": "ISO 4217 code of the currency to convert from" },
+                        "to": { "type": "string", "description": "ISO 4217 code of the currency to convert to" },
+                        "days": { "type": "integer", "description": "Number of days to look back, 30 by default" }
+                    },
+                    "required": ["from", "to"]
+                }
+            }
+        }),
+        json!({
+            "type": "function",
+            "function": {
+                "name": "search_news",
+                "description": "Search recent news articles.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "Search query" }
+                    },
+                    "required": ["query"]
+                }
+            }
+        }),
+    ]
+}
+
+fn get_str_arg(call: &ToolCall, name: &str) -> Result<String, String> {
+    call.arguments[name]
+        .as_str()
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .ok_or(format!("Missing argument '{}'", name))
+}
+
+/// Models are inconsistent about sending numbers as numbers
+fn get_days_arg(call: &ToolCall, default: i64) -> i64 {
+    let days = match &call.arguments["days"] {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    };
+    days.unwrap_or(default).clamp(1, MAX_HISTORY_DAYS)
+}
+
+async fn get_series(from: &str, to: &str, days: i64) -> Result<Vec<(NaiveDate, f64)>, String> {
+    let from_date = (Utc::now() - Duration::days(days)).date_naive();
+    let rates = ExchangeRateMap::get_rates(from_date, Some(from.to_string()))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let series: Vec<(NaiveDate, f64)> = rates
+        .iter()
+        .filter_map(|r| r.get_val(from, to).map(|v| (r.get_date(), v)))
+        .collect();
+
+    if series.is_empty() {
+        return Err(format!("No data available from {} to {}", from, to));
+    }
+    Ok(series)
+}
+
+async fn get_rate(call: &ToolCall) -> Result<Value, String> {
+    let from = get_str_arg(call, "from")?;
+    let to = get_str_arg(call, "to")?;
+    let date = match call.arguments["date"].as_str() {
+        Some(date) if !date.trim().is_empty() => NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        _ => Utc::now().date_naive(),
+    };
+
+    let rate = ExchangeRateMap::get_rate(date, Some(from.clone()))
+        .await
+        .map_err(|e| e.to_string())?;
+    let value = rate
+        .get_val(&from, &to)
+        .ok_or(format!("No rate from {} to {}", from, to))?;
+
+    Ok(json!({
+        "from": from,
+        "to": to,
+        "date": rate.get_date().format("%Y-%m-%d").to_string(),
+        "rate": value
+    }))
+}
+
+async fn get_history(call: &ToolCall) -> Result<Value, String> {
+    let from = get_str_arg(call, "from")?;
+    let to = get_str_arg(call, "to")?;
+    let days = get_days_arg(call, 30);
+
+    let series = get_series(&from, &to, days).await?;
+    let rates: Vec<Value> = series
+        .iter()
+        .map(|(date, rate)| json!({ "date": date.format("%Y-%m-%d").to_string(), "rate": rate }))
+        .collect();
+
+    Ok(json!({ "from": from, "to": to, "rates": rates }))
+}
+
+async fn get_stats(call: &ToolCall) -> Result<Value, String> {
+    let from = get_str_arg(call, "from")?;
+    let to = get_str_arg(call, "to")?;
+    let days = get_days_arg(call, 30);
+
+    let series = get_series(&from, &to, days).await?;
+    let threshold = environment::get_exchange_rate_change_threshold();
+    let stats = stats::get_stats(&series, threshold).ok_or("No data available".to_string())?;
+    let (first_date, first) = stats.first;
+    let (last_date, last) = stats.last;
+
+    Ok(json!({
+        "from": from,
+        "to": to,
+        "start_date": first_date.format("%Y-%m-%d").to_string(),
+        "end_date": last_date.format("%Y-%m-%d").to_string(),
+        "latest": last,
+        "change": last - first,
+        "change_percent": stats.change,
+        "high": stats.high,
+        "low": stats.low,
+        "average": stats.average,
+        "daily_volatility_percent": stats.volatility,
+        "annualized_volatility_percent": stats.annualized_volatility,
+        "max_drawdown_percent": stats.max_drawdown.map(|drawdown| drawdown.percent),
+        "longest_gains": stats.longest_gains,
+        "longest_losses": stats.longest_losses,
+        "latest_change_z_score": stats.z_score
+    }))
+}
+
+async fn search_news(call: &ToolCall) -> Result<Value, String> {
+    let query = call.arguments["query"]
+        .as_str()
+        .filter(|q| !q.trim().is_empty())
+        .ok_or("Missing argument 'query'".to_string())?;
+
+    if get_providers().is_empty() {
+        return Err("Search is not available".to_string());
+    }
+
+    let results: Vec<Value> = search_all(query, SearchCategory::News, MAX_SEARCH_RESULTS)
+        .await
+        .into_iter()
+        .map(|result| {
+            json!({
+                "title": result.title,
+                "content": result.content,
+                "url": result.url
+            })
+        })
+        .collect();
+
+    Ok(json!({ "query": query, "results": results }))
+}
+
+/// Run a tool call, errors are returned to the model as `{"error": ...}`
+pub async fn execute_tool(call: &ToolCall) -> String {
+    log::debug!("Tool call: {} {}", call.name, call.arguments);
+
+    let result = match call.name.as_str() {
+        "get_rate" => get_rate(call).await,
+        "get_history" => get_history(call).await,
+        "get_stats" => get_stats(call).await,
+        "search_news" => search_news(call).await,
+        name => Err(format!("Unknown tool '{}'", name)),
+    };
+
+    match result {
+        Ok(v) => v.to_string(),
+        Err(e) => {
+            log::warn!("Tool call {} failed: {}", call.name, e);
+            json!({ "error": e }).to_string()
+        }
+    }
+}
+
+/// Chat with `backend`, running the tools the model asks for.
+///
+/// Tools are only offered when `LLM_TOOLS` is enabled. After
+/// `LLM_TOOL_MAX_STEPS` rounds of tool calls the model has to answer without
+/// them. Token counts and durations are summed over all rounds, the first
+/// token duration is the time until the final answer started.
+///
+/// Returns the final completion and the number of tools called.
+pub async fn chat_with_tools(
+    backend: &dyn LlmBackend,
+    messages: &mut Vec<Value>,
+    progress: Option<&watch::Sender<String>>,
+) -> Result<(ChatCompletion, usize), LlmError> {
+    let tools = if environment::get_llm_tools_enabled() {
+        get_tool_definitions()
+    } else {
+        vec![]
+    };
+    let max_steps = environment::get_llm_tool_max_steps();
+
+    let mut raw = vec![];
+    let mut prompt_tokens = None;
+    let mut completion_tokens = None;
+    let mut elapsed = std::time::Duration::ZERO;
+    let mut tool_count = 0;
+    let mut step = 0;
+
+    loop {
+        let offered: &[Value] = if step < max_steps { &tools } else { &[] };
+        let completion = backend.chat(messages, offered, progress).await?;
+
+        raw.push(completion.raw.clone());
+        prompt_tokens = sum_tokens(prompt_tokens, completion.prompt_tokens);
+        completion_tokens = sum_tokens(completion_tokens, completion.completion_tokens);
+
+        if completion.tool_calls.is_empty() || offered.is_empty() {
+            return Ok((
+                ChatCompletion {
+                    raw: raw.join("\n"),
+                    prompt_tokens,
+                    completion_tokens,
+                    first_token_duration: elapsed + completion.first_token_duration,
+                    total_duration: elapsed + completion.total_duration,
+                    ..completion
+                },
+                tool_count,
+            ));
+        }
+
+        elapsed += completion.total_duration;
+        messages.push(backend.assistant_message(&completion));
+        for call in &completion.tool_calls {
+            let result = execute_tool(call).await;
+            messages.push(backend.tool_message(call, &result));
+            tool_count += 1;
+        }
+        step += 1;
+    }
+}
+
+fn sum_tokens(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
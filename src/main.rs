@@ -4,11 +4,11 @@ mod commands;
 mod database;
 mod environment;
 mod exchange_rate;
+mod i18n;
 mod llm;
 mod utils;
 
-#[tokio::main]
-async fn main() {
+fn main() {
     match dotenv() {
         Ok(_) => {
             env_logger::init();
@@ -24,6 +24,26 @@ async fn main() {
 
     log::debug!("Log level: {}", log::max_level());
 
+    // Sets FONTCONFIG_FILE, which is only safe before the runtime starts its threads
+    if let Err(e) = utils::theme::install_fonts() {
+        log::warn!("Cannot install the chart fonts: {}", e);
+    }
+    run();
+}
+
+#[tokio::main]
+async fn run() {
+    // `eval` replays the evaluation fixtures instead of starting the bot
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("eval") {
+        environment::ensure_eval_environment();
+        if let Err(e) = llm::eval::run(&args[1..]).await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     environment::ensure_environment().await;
     bot::run_bot().await;
 }
@@ -0,0 +1,318 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::exchange_rate::ExchangeRateMap;
+
+use super::{calendar::CalendarEvent, holidays::is_closing_day};
+
+/// Scales the median absolute deviation to the standard deviation of normal data
+const MAD_SCALE: f64 = 0.6745;
+
+/// Smallest deviation of the daily log changes, so that pegged currencies
+/// moving a little are not outliers
+const MIN_DEVIATION: f64 = 0.001;
+
+/// Fewest daily changes of a currency its outliers are found from
+const MIN_CHANGES: usize = 5;
+
+/// Something wrong with fetched rates
+#[derive(Clone, Debug, PartialEq)]
+pub enum Anomaly {
+    /// Rate that is not a positive number
+    Invalid { currency: String, value: f64 },
+    /// Rate moving far more than its recent daily changes
+    Outlier {
+        currency: String,
+        value: f64,
+        previous: f64,
+        score: f64,
+    },
+    /// Currencies of the recent rates that are gone
+    Missing(Vec<String>),
+    /// Rates older than expected, by working days
+    Stale {
+        date: NaiveDate,
+        working_days: usize,
+    },
+    /// Rates dated after the date they were fetched for
+    Future(NaiveDate),
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Anomaly::Invalid { currency, value } => write!(f, "{} is {}", currency, value),
+            Anomaly::Outlier {
+                currency,
+                value,
+                previous,
+                score,
+            } => write!(
+                f,
+                "{} moved from {} to {} ({:.1}× the previous rate, robust z-score {:.0})",
+                currency,
+                previous,
+                value,
+                value / previous,
+                score
+            ),
+            Anomaly::Missing(currencies) => write!(f, "{} missing", currencies.join(", ")),
+            Anomaly::Stale { date, working_days } => write!(
+                f,
+                "dated {}, {} working days old",
+                date.format("%Y-%m-%d"),
+                working_days
+            ),
+            Anomaly::Future(date) => write!(f, "dated {} in the future", date.format("%Y-%m-%d")),
+        }
+    }
+}
+
+impl Anomaly {
+    /// Currency whose rate cannot be used, `None` when the anomaly concerns all rates
+    pub fn currency(&self) -> Option<&str> {
+        match self {
+            Anomaly::Invalid { currency, .. } | Anomaly::Outlier { currency, .. } => Some(currency),
+            _ => None,
+        }
+    }
+}
+
+fn get_median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    Some(match values.len() % 2 {
+        0 => (values[middle - 1] + values[middle]) / 2.0,
+        _ => values[middle],
+    })
+}
+
+/// Robust z-score of `value` among `values`, from their median and median
+/// absolute deviation
+pub fn get_robust_z_score(value: f64, values: &[f64], min_deviation: f64) -> Option<f64> {
+    let median = get_median(&mut values.to_vec())?;
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - median).abs()).collect();
+    let mad = get_median(&mut deviations)?.max(min_deviation);
+    Some(MAD_SCALE * (value - median) / mad)
+}
+
+/// Rates of `rates` that are not positive, or whose change from the last of
+/// `history` has a robust z-score above `max_score` among the daily changes of
+/// `history`.
+///
+/// Changes are compared as logarithms, so that a rate off by 100× is as far
+/// off as one off by 1/100. Days repeating the day before, such as filled
+/// closing days, are left out of the changes.
+pub fn get_outliers(
+    rates: &ExchangeRateMap,
+    history: &[ExchangeRateMap],
+    max_score: f64,
+) -> Vec<Anomaly> {
+    let mut anomalies: Vec<Anomaly> = rates
+        .map
+        .iter()
+        .filter(|(_, value)| !(value.is_finite() && **value > 0.0))
+        .map(|(currency, value)| Anomaly::Invalid {
+            currency: currency.clone(),
+            value: *value,
+        })
+        .collect();
+
+    let Some(last) = history.last() else {
+        return anomalies;
+    };
+    let mut currencies: Vec<&String> = rates.map.keys().collect();
+    currencies.sort();
+    for currency in currencies {
+        let value = rates.map[currency];
+        let values: Vec<f64> = history
+            .iter()
+            .filter_map(|h| h.get_val(&rates.base, currency))
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .collect();
+        let changes: Vec<f64> = values
+            .windows(2)
+            .map(|pair| (pair[1] / pair[0]).ln())
+            .filter(|change| *change != 0.0)
+            .collect();
+        let Some(previous) = last.get_val(&rates.base, currency) else {
+            continue;
+        };
+        if changes.len() < MIN_CHANGES || !(value > 0.0 && previous > 0.0) {
+            continue;
+        }
+        let score = get_robust_z_score((value / previous).ln(), &changes, MIN_DEVIATION)
+            .unwrap_or_default()
+            .abs();
+        if score > max_score {
+            anomalies.push(Anomaly::Outlier {
+                currency: currency.clone(),
+                value,
+                previous,
+                score,
+            });
+        }
+    }
+    anomalies
+}
+
+/// Currencies quoted on every day of `history` that `rates` has no rate of.
+///
+/// Days filled in by the fallback provider quote many more currencies than the
+/// main provider, only the currencies both quote are expected.
+pub fn get_missing(rates: &ExchangeRateMap, history: &[ExchangeRateMap]) -> Option<Anomaly> {
+    let (last, before) = history.split_last()?;
+    let mut missing: Vec<String> = last
+        .map
+        .keys()
+        .chain([&last.base])
+        .filter(|currency| {
+            before
+                .iter()
+                .all(|day| day.get_val(&day.base, currency).is_some())
+        })
+        .filter(|currency| rates.get_val(&rates.base, currency).is_none())
+        .cloned()
+        .collect();
+    missing.sort();
+    match missing.is_empty() {
+        true => None,
+        false => Some(Anomaly::Missing(missing)),
+    }
+}
+
+/// Whether rates dated `date` are too old for rates expected on `expected`.
+///
+/// Rates of `expected` itself may not be published yet, so the working days
+/// between both dates count, which are more than `max_days` when stale.
+pub fn get_staleness(
+    date: NaiveDate,
+    expected: NaiveDate,
+    events: &[CalendarEvent],
+    max_days: usize,
+) -> Option<Anomaly> {
+    if date > expected {
+        return Some(Anomaly::Future(date));
+    }
+    let working_days = date
+        .iter_days()
+        .skip(1)
+        .take_while(|day| *day < expected)
+        .filter(|day| !is_closing_day(*day, events))
+        .count();
+    match working_days > max_days {
+        true => Some(Anomaly::Stale { date, working_days }),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::{get_daily_rates, get_day};
+    use chrono::Duration;
+    use std::collections::HashMap;
+
+    /// Monday after the four weeks of [`get_history`]
+    fn get_next_day(usd: f64, jpy: f64) -> ExchangeRateMap {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 29).unwrap();
+        get_day(date, "EUR", &[("USD", usd), ("JPY", jpy)])
+    }
+
+    /// Four weeks of usual moves of the main provider, with the weekends carried forward
+    fn get_history() -> Vec<ExchangeRateMap> {
+        let usd: Vec<f64> = (0..20)
+            .map(|i| 1.08 + [0.0, 0.004, -0.003, 0.002, -0.005][i % 5])
+            .collect();
+        let jpy: Vec<f64> = usd.iter().map(|usd| usd / 1.08 * 160.0).collect();
+        get_daily_rates(&[("USD", &usd), ("JPY", &jpy)])
+    }
+
+    #[test]
+    fn test_outliers() {
+        let history = get_history();
+
+        // A usual move
+        assert_eq!(
+            get_outliers(&get_next_day(1.085, 160.5), &history, 10.0),
+            []
+        );
+
+        // JPY off by 100×, in any base
+        let anomalies = get_outliers(&get_next_day(1.08, 16000.0), &history, 10.0);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].currency(), Some("JPY"));
+        let mut in_usd = get_next_day(1.0 / 1.08, 16000.0 / 1.08);
+        in_usd.base = "USD".to_string();
+        in_usd.map = HashMap::from([
+            ("EUR".to_string(), 1.0 / 1.08),
+            ("JPY".to_string(), 16000.0 / 1.08),
+        ]);
+        assert_eq!(
+            get_outliers(&in_usd, &history, 10.0)[0].currency(),
+            Some("JPY")
+        );
+
+        let anomalies = get_outliers(&get_next_day(0.0, 160.0), &history, 10.0);
+        assert!(matches!(&anomalies[0], Anomaly::Invalid { currency, .. } if currency == "USD"));
+
+        // Too little history for outliers
+        assert_eq!(
+            get_outliers(&get_next_day(1.08, 16000.0), &history[..3], 10.0),
+            []
+        );
+    }
+
+    #[test]
+    fn test_missing() {
+        let history = get_history();
+        let mut rates = get_next_day(1.08, 160.0);
+        assert_eq!(get_missing(&rates, &history), None);
+        rates.map.remove("JPY");
+        assert_eq!(
+            get_missing(&rates, &history),
+            Some(Anomaly::Missing(vec!["JPY".to_string()]))
+        );
+        assert_eq!(get_missing(&rates, &[]), None);
+
+        // The fallback provider filled in a holiday with more currencies
+        let mut history = get_history();
+        history.push(get_day(
+            NaiveDate::from_ymd_opt(2024, 1, 29).unwrap(),
+            "EUR",
+            &[("USD", 1.08), ("JPY", 160.0), ("XAU", 0.0005)],
+        ));
+        let date = NaiveDate::from_ymd_opt(2024, 1, 30).unwrap();
+        let mut rates = get_day(date, "EUR", &[("USD", 1.08), ("JPY", 160.0)]);
+        // The main provider never quotes XAU, it is not missing the next day
+        assert_eq!(get_missing(&rates, &history), None);
+        rates.map.remove("USD");
+        assert_eq!(
+            get_missing(&rates, &history),
+            Some(Anomaly::Missing(vec!["USD".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_staleness() {
+        // Friday 2024-01-05 to Monday 2024-01-08, the weekend does not count
+        let friday = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        assert_eq!(get_staleness(friday, monday, &[], 0), None);
+        assert_eq!(
+            get_staleness(friday - Duration::days(2), monday, &[], 1),
+            Some(Anomaly::Stale {
+                date: friday - Duration::days(2),
+                working_days: 2
+            })
+        );
+        assert_eq!(
+            get_staleness(monday, friday, &[], 1),
+            Some(Anomaly::Future(monday))
+        );
+    }
+}
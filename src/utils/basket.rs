@@ -0,0 +1,314 @@
+use std::sync::{LazyLock, Mutex};
+
+use thiserror::Error;
+
+use crate::database::basket::{delete_basket, get_saved_baskets, save_basket};
+use crate::exchange_rate::ExchangeRateMap;
+
+/// Shortest basket name, longer than currency codes so that they never clash
+const MIN_NAME_LENGTH: usize = 4;
+
+const MAX_NAME_LENGTH: usize = 12;
+
+/// Baskets of the database, loaded on first use
+static BASKETS: LazyLock<Mutex<Option<Vec<Basket>>>> = LazyLock::new(|| Mutex::new(None));
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BasketError {
+    #[error("`{0}` is not a basket name, use 4 to 12 letters or digits")]
+    InvalidName(String),
+
+    #[error("`{0}` is not a currency and a weight such as `USD:40`")]
+    InvalidComponent(String),
+
+    #[error("{0} is in the basket more than once")]
+    DuplicateCurrency(String),
+
+    #[error("The basket has no currencies")]
+    Empty,
+
+    #[error("There is no rate of {0}")]
+    NoRate(String),
+}
+
+/// How the currencies of a basket add up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BasketKind {
+    /// Fixed amounts of the currencies, their shares drift with the rates like the SDR
+    Weighted,
+    /// Weighted geometric average of the currencies, with fixed shares like the DXY
+    Geometric,
+}
+
+impl BasketKind {
+    pub const NAMES: &'static [&'static str] = &["weighted", "geometric"];
+
+    pub fn from_name(name: &str) -> Option<BasketKind> {
+        match name.to_lowercase().as_str() {
+            "weighted" => Some(BasketKind::Weighted),
+            "geometric" => Some(BasketKind::Geometric),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BasketKind::Weighted => "weighted",
+            BasketKind::Geometric => "geometric",
+        }
+    }
+}
+
+/// Currency made of other currencies, usable wherever a currency code is
+#[derive(Clone, Debug, PartialEq)]
+pub struct Basket {
+    pub name: String,
+    pub kind: BasketKind,
+    /// Currencies and their amounts when weighted, their exponents when geometric
+    pub components: Vec<(String, f64)>,
+    /// Makes a basket worth 1 of the base currency of the day it was defined
+    pub scale: f64,
+}
+
+impl Basket {
+    /// Basket `name` of `shares`, worth 1 of the base currency of `rates`.
+    ///
+    /// The shares are normalized, so that `USD:40,EUR:60` and `USD:0.4,EUR:0.6`
+    /// are the same basket.
+    pub fn new(
+        name: &str,
+        kind: BasketKind,
+        shares: &[(String, f64)],
+        rates: &ExchangeRateMap,
+    ) -> Result<Basket, BasketError> {
+        let name = name.trim().to_uppercase();
+        if !is_basket_name(&name) {
+            return Err(BasketError::InvalidName(name));
+        }
+        let total: f64 = shares.iter().map(|(_, share)| share).sum();
+        if shares.is_empty() || total <= 0.0 {
+            return Err(BasketError::Empty);
+        }
+
+        let mut components = vec![];
+        for (currency, share) in shares {
+            // Only currencies of the rates, not other baskets
+            let value = match currency == &rates.base {
+                true => Some(1.0),
+                false => rates.map.get(currency).map(|units| 1.0 / units),
+            };
+            let value = value.ok_or_else(|| BasketError::NoRate(currency.clone()))?;
+            let share = share / total;
+            components.push((
+                currency.clone(),
+                match kind {
+                    BasketKind::Weighted => share / value,
+                    BasketKind::Geometric => share,
+                },
+            ));
+        }
+
+        let mut basket = Basket {
+            name,
+            kind,
+            components,
+            scale: 1.0,
+        };
+        let value = basket
+            .get_value(rates, &rates.base)
+            .ok_or_else(|| BasketError::NoRate(basket.name.clone()))?;
+        basket.scale = 1.0 / value;
+        Ok(basket)
+    }
+
+    /// Value of one basket in `currency` with `rates`, `None` when a rate is missing
+    pub fn get_value(&self, rates: &ExchangeRateMap, currency: &str) -> Option<f64> {
+        let values = self
+            .components
+            .iter()
+            .map(|(code, weight)| Some((rates.get_val(code, currency)?, *weight)));
+        let value = match self.kind {
+            BasketKind::Weighted => values
+                .map(|value| value.map(|(value, amount)| value * amount))
+                .sum::<Option<f64>>()?,
+            BasketKind::Geometric => values
+                .map(|value| value.map(|(value, exponent)| value.powf(exponent)))
+                .product::<Option<f64>>()?,
+        };
+        Some(self.scale * value).filter(|value| value.is_finite() && *value > 0.0)
+    }
+
+    /// Shares of the currencies in percent with `rates`, for weighted baskets
+    /// they change with the rates
+    pub fn get_shares(&self, rates: &ExchangeRateMap) -> Option<Vec<(String, f64)>> {
+        match self.kind {
+            BasketKind::Weighted => {
+                let total = self.get_value(rates, &rates.base)?;
+                self.components
+                    .iter()
+                    .map(|(code, amount)| {
+                        let value = rates.get_val(code, &rates.base)?;
+                        Some((code.clone(), self.scale * amount * value / total * 100.0))
+                    })
+                    .collect()
+            }
+            BasketKind::Geometric => Some(
+                self.components
+                    .iter()
+                    .map(|(code, exponent)| (code.clone(), exponent * 100.0))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Whether `name` can be a basket, which currency codes cannot
+pub fn is_basket_name(name: &str) -> bool {
+    (MIN_NAME_LENGTH..=MAX_NAME_LENGTH).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Currencies and weights of `text` such as `USD:40, EUR:30, CNY:30%`
+pub fn parse_shares(text: &str) -> Result<Vec<(String, f64)>, BasketError> {
+    let mut shares: Vec<(String, f64)> = vec![];
+    for component in text.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        let invalid = || BasketError::InvalidComponent(component.to_string());
+        let (currency, weight) = component.split_once(':').ok_or_else(invalid)?;
+        let currency = currency.trim().to_uppercase();
+        let weight: f64 = weight
+            .trim()
+            .trim_end_matches('%')
+            .parse()
+            .map_err(|_| invalid())?;
+        if currency.len() != 3 || !weight.is_finite() || weight <= 0.0 {
+            return Err(invalid());
+        }
+        if shares.iter().any(|(code, _)| *code == currency) {
+            return Err(BasketError::DuplicateCurrency(currency));
+        }
+        shares.push((currency, weight));
+    }
+    match shares.is_empty() {
+        true => Err(BasketError::Empty),
+        false => Ok(shares),
+    }
+}
+
+/// All baskets, by name
+pub fn get_baskets() -> Vec<Basket> {
+    let mut baskets = BASKETS.lock().unwrap();
+    baskets.get_or_insert_with(get_saved_baskets).clone()
+}
+
+/// Basket called `name`, `None` for currencies and unknown baskets
+pub fn get_basket(name: &str) -> Option<Basket> {
+    if !is_basket_name(name) {
+        return None;
+    }
+    get_baskets()
+        .into_iter()
+        .find(|basket| basket.name.eq_ignore_ascii_case(name))
+}
+
+/// Save `basket`, replacing the basket of the same name
+pub fn set_basket(basket: Basket) {
+    save_basket(&basket);
+    let mut baskets = BASKETS.lock().unwrap();
+    let baskets = baskets.get_or_insert_with(get_saved_baskets);
+    baskets.retain(|b| b.name != basket.name);
+    baskets.push(basket);
+    baskets.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// Delete basket `name`, `false` when there is none
+pub fn remove_basket(name: &str) -> bool {
+    let Some(basket) = get_basket(name) else {
+        return false;
+    };
+    delete_basket(&basket.name);
+    let mut baskets = BASKETS.lock().unwrap();
+    if let Some(baskets) = baskets.as_mut() {
+        baskets.retain(|b| b.name != basket.name);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::{get_day, get_start};
+
+    fn get_rates(usd: f64, cny: f64) -> ExchangeRateMap {
+        get_day(get_start(), "EUR", &[("USD", usd), ("CNY", cny)])
+    }
+
+    #[test]
+    fn test_parse_shares() {
+        assert_eq!(
+            parse_shares("usd:40, EUR:30%,CNY: 30"),
+            Ok(vec![
+                ("USD".to_string(), 40.0),
+                ("EUR".to_string(), 30.0),
+                ("CNY".to_string(), 30.0)
+            ])
+        );
+        assert_eq!(
+            parse_shares("USD:40,USD:60"),
+            Err(BasketError::DuplicateCurrency("USD".to_string()))
+        );
+        assert!(parse_shares("USD=40").is_err());
+        assert!(parse_shares("USD:-1").is_err());
+        assert_eq!(parse_shares(" , "), Err(BasketError::Empty));
+        assert!(!is_basket_name("USD"));
+        assert!(is_basket_name("ASIA3"));
+    }
+
+    #[test]
+    fn test_basket_value() {
+        let shares = parse_shares("USD:40,EUR:30,CNY:30").unwrap();
+        let defined = get_rates(1.1, 8.0);
+        let moved = get_rates(1.1 * 1.1, 8.0);
+        for kind in [BasketKind::Weighted, BasketKind::Geometric] {
+            let basket = Basket::new("mix3", kind, &shares, &defined).unwrap();
+            assert_eq!(basket.name, "MIX3");
+            // Worth 1 EUR when defined, in any currency
+            assert!((basket.get_value(&defined, "EUR").unwrap() - 1.0).abs() < 1e-9);
+            assert!((basket.get_value(&defined, "USD").unwrap() - 1.1).abs() < 1e-9);
+
+            let shares = basket.get_shares(&defined).unwrap();
+            assert!((shares[0].1 - 40.0).abs() < 1e-9, "{:?}", kind);
+
+            // USD 10% weaker against EUR and CNY
+            let value = basket.get_value(&moved, "USD").unwrap();
+            let expected = match kind {
+                BasketKind::Weighted => 1.1 * (0.4 + 0.6 * 1.1),
+                BasketKind::Geometric => 1.1 * 1.1f64.powf(0.6),
+            };
+            assert!((value - expected).abs() < 1e-9, "{:?}", kind);
+        }
+        assert_eq!(
+            Basket::new(
+                "MIX3",
+                BasketKind::Weighted,
+                &[("GBP".to_string(), 1.0)],
+                &defined
+            ),
+            Err(BasketError::NoRate("GBP".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_basket_rates() {
+        let defined = get_rates(1.1, 8.0);
+        let shares = parse_shares("USD:50,CNY:50").unwrap();
+        let basket = Basket::new("TEST", BasketKind::Geometric, &shares, &defined).unwrap();
+
+        let rates = get_rates(1.2, 8.0);
+        let value = basket.get_value(&rates, "USD").unwrap();
+        assert!((value - 1.1 * (1.2f64 / 1.1).powf(0.5)).abs() < 1e-9);
+        // Worth the same in the base currency, which `get_val` converts from
+        let base_value = basket.get_value(&rates, &rates.base).unwrap();
+        assert!((base_value * 1.2 - value).abs() < 1e-9);
+        assert_eq!(basket.get_value(&rates, "GBP"), None);
+    }
+}
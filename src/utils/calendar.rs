@@ -0,0 +1,472 @@
+use std::{
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use thiserror::Error;
+
+use crate::{
+    environment,
+    utils::currencies::{get_currencies, get_currency_info},
+};
+
+/// How long a loaded calendar is used before it is loaded again
+const CALENDAR_REFRESH: Duration = Duration::from_secs(60 * 60);
+
+/// Seconds a calendar may take to download
+const CALENDAR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Abbreviations of central banks found in event titles, with their currency
+const CENTRAL_BANK_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("ECB", "EUR"),
+    ("Fed", "USD"),
+    ("FOMC", "USD"),
+    ("BoC", "CAD"),
+    ("BoE", "GBP"),
+    ("BoJ", "JPY"),
+    ("SNB", "CHF"),
+    ("RBA", "AUD"),
+    ("RBNZ", "NZD"),
+    ("PBoC", "CNY"),
+    ("RBI", "INR"),
+];
+
+#[derive(Debug, Error)]
+pub enum CalendarError {
+    #[error("Cannot read calendar: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Cannot download calendar: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("Invalid calendar: {0}")]
+    Format(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    RateDecision,
+    Inflation,
+    Holiday,
+    Other,
+}
+
+impl EventKind {
+    /// Kind named by a CSV column or an ICS category, e.g. `rate_decision` or `CPI`
+    fn parse(name: &str) -> Option<EventKind> {
+        match name.trim().to_lowercase().replace(['_', '-'], " ").as_str() {
+            "rate decision" | "interest rate" | "monetary policy" => Some(EventKind::RateDecision),
+            "cpi" | "inflation" => Some(EventKind::Inflation),
+            "holiday" | "bank holiday" => Some(EventKind::Holiday),
+            "other" => Some(EventKind::Other),
+            _ => None,
+        }
+    }
+
+    /// Kind of an event without one, guessed from its title
+    fn guess(title: &str) -> EventKind {
+        let title = title.to_lowercase();
+        let contains = |words: &[&str]| words.iter().any(|word| title.contains(word));
+        if contains(&["holiday", "bank closed", "market closed"]) {
+            EventKind::Holiday
+        } else if contains(&["cpi", "inflation", "consumer price"]) {
+            EventKind::Inflation
+        } else if contains(&["rate decision", "interest rate", "monetary policy"]) {
+            EventKind::RateDecision
+        } else {
+            EventKind::Other
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::RateDecision => "rate decision",
+            EventKind::Inflation => "inflation",
+            EventKind::Holiday => "holiday",
+            EventKind::Other => "other",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalendarEvent {
+    pub date: NaiveDate,
+    /// UTC time of the event, `None` for events lasting all day
+    pub time: Option<NaiveTime>,
+    /// Currencies the event moves, e.g. `EUR` for an ECB decision
+    pub currencies: Vec<String>,
+    pub kind: EventKind,
+    pub title: String,
+}
+
+impl CalendarEvent {
+    fn new(
+        date: NaiveDate,
+        time: Option<NaiveTime>,
+        currencies: Vec<String>,
+        kind: Option<EventKind>,
+        title: String,
+    ) -> CalendarEvent {
+        let currencies = match currencies.is_empty() {
+            true => get_title_currencies(&title),
+            false => currencies,
+        };
+        CalendarEvent {
+            date,
+            time,
+            currencies,
+            kind: kind.unwrap_or_else(|| EventKind::guess(&title)),
+            title,
+        }
+    }
+
+    pub fn concerns(&self, currency: &str) -> bool {
+        self.currencies
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(currency))
+    }
+}
+
+/// Currencies whose code, central bank or region is named in `title`
+fn get_title_currencies(title: &str) -> Vec<String> {
+    let words: Vec<&str> = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let lowercase = title.to_lowercase();
+
+    let mut currencies: Vec<String> = vec![];
+    let mut add = |code: &str| {
+        if !currencies.iter().any(|c| c == code) {
+            currencies.push(code.to_string());
+        }
+    };
+
+    for word in &words {
+        if let Some(info) = get_currency_info(word).filter(|info| info.code == *word) {
+            add(info.code);
+        }
+        for (abbreviation, code) in CENTRAL_BANK_ABBREVIATIONS {
+            if word == abbreviation {
+                add(code);
+            }
+        }
+    }
+    for info in get_currencies() {
+        if lowercase.contains(&info.central_bank.to_lowercase())
+            || lowercase.contains(&info.region.to_lowercase())
+        {
+            add(info.code);
+        }
+    }
+    currencies
+}
+
+/// Events of the configured calendar, empty when there is none.
+///
+/// The calendar is loaded again after [`CALENDAR_REFRESH`]. When that fails
+/// the events loaded before are kept.
+pub async fn get_events() -> Vec<CalendarEvent> {
+    /// Events of the calendar and when they were loaded
+    type Loaded = (Instant, Vec<CalendarEvent>);
+    static CALENDAR: LazyLock<Mutex<Option<Loaded>>> = LazyLock::new(|| Mutex::new(None));
+
+    let Some(source) = environment::get_economic_calendar() else {
+        return vec![];
+    };
+
+    if let Some((loaded, events)) = CALENDAR.lock().unwrap().as_ref() {
+        if loaded.elapsed() < CALENDAR_REFRESH {
+            return events.clone();
+        }
+    }
+
+    let loaded = load_calendar(&source).await;
+    let mut calendar = CALENDAR.lock().unwrap();
+    let events = match loaded {
+        Ok(events) => {
+            log::debug!("Loaded {} calendar events from {}", events.len(), source);
+            events
+        }
+        Err(e) => {
+            log::error!("Error loading calendar '{}': {}", source, e);
+            calendar
+                .take()
+                .map(|(_, events)| events)
+                .unwrap_or_default()
+        }
+    };
+    // Also after errors, so that a broken calendar is not loaded on every call
+    *calendar = Some((Instant::now(), events.clone()));
+    events
+}
+
+/// Events of the ICS or CSV calendar at `source`, a URL or a file path
+async fn load_calendar(source: &str) -> Result<Vec<CalendarEvent>, CalendarError> {
+    let text = match source.starts_with("http://") || source.starts_with("https://") {
+        true => {
+            reqwest::Client::builder()
+                .timeout(CALENDAR_TIMEOUT)
+                .build()?
+                .get(source)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?
+        }
+        false => tokio::fs::read_to_string(source).await?,
+    };
+    parse_calendar(&text)
+}
+
+/// Events of an ICS or CSV calendar, told apart by their content
+pub fn parse_calendar(text: &str) -> Result<Vec<CalendarEvent>, CalendarError> {
+    let text = text.trim_start_matches('\u{feff}');
+    match text.trim_start().starts_with("BEGIN:VCALENDAR") {
+        true => Ok(parse_ics(text)),
+        false => parse_csv(text),
+    }
+}
+
+/// Events of an iCalendar file.
+///
+/// Currencies are read from the `X-CURRENCY` property and the kind from
+/// `CATEGORIES`, both are guessed from the summary when missing. Times
+/// without a `Z` are taken as UTC.
+fn parse_ics(text: &str) -> Vec<CalendarEvent> {
+    // Long lines continue on lines starting with a space or tab
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = vec![];
+    let mut properties: Option<Vec<(String, String)>> = None;
+    for line in lines {
+        match line.trim_end() {
+            "BEGIN:VEVENT" => properties = Some(vec![]),
+            "END:VEVENT" => {
+                if let Some(properties) = properties.take() {
+                    match parse_ics_event(&properties) {
+                        Some(event) => events.push(event),
+                        None => log::warn!("Skipping calendar event: {:?}", properties),
+                    }
+                }
+            }
+            line => {
+                if let (Some(properties), Some((name, value))) =
+                    (properties.as_mut(), line.split_once(':'))
+                {
+                    // Parameters such as `;VALUE=DATE` are not needed
+                    let name = name.split(';').next().unwrap_or_default();
+                    properties.push((name.to_uppercase(), value.to_string()));
+                }
+            }
+        }
+    }
+    events
+}
+
+fn parse_ics_event(properties: &[(String, String)]) -> Option<CalendarEvent> {
+    let property = |name: &str| {
+        properties
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let start = property("DTSTART")?.trim_end_matches('Z');
+    let (date, time) = match NaiveDateTime::parse_from_str(start, "%Y%m%dT%H%M%S") {
+        Ok(datetime) => (datetime.date(), Some(datetime.time())),
+        Err(_) => (NaiveDate::parse_from_str(start, "%Y%m%d").ok()?, None),
+    };
+    let title = unescape_ics(property("SUMMARY")?);
+    let currencies = property("X-CURRENCY").map_or(vec![], split_currencies);
+    let kind = property("CATEGORIES").and_then(|categories| {
+        unescape_ics(categories)
+            .split(',')
+            .find_map(EventKind::parse)
+    });
+
+    Some(CalendarEvent::new(date, time, currencies, kind, title))
+}
+
+fn unescape_ics(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/// Currency codes separated by commas, semicolons or spaces
+fn split_currencies(value: &str) -> Vec<String> {
+    value
+        .split([',', ';', ' '])
+        .filter(|code| !code.trim().is_empty())
+        .map(|code| code.trim().to_uppercase())
+        .collect()
+}
+
+/// Events of a CSV file with a header line.
+///
+/// The `date` (`YYYY-MM-DD`) and `title` columns are required, `time`
+/// (`HH:MM` UTC), `currency` and `kind` are optional.
+fn parse_csv(text: &str) -> Result<Vec<CalendarEvent>, CalendarError> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = split_csv_line(lines.next().unwrap_or_default())
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|n| n == name);
+
+    let (Some(date_column), Some(title_column)) = (column("date"), column("title")) else {
+        return Err(CalendarError::Format(
+            "expected an ICS calendar or a CSV file with date and title columns".to_string(),
+        ));
+    };
+    let time_column = column("time");
+    let currency_column = column("currency").or_else(|| column("currencies"));
+    let kind_column = column("kind").or_else(|| column("type"));
+
+    let mut events = vec![];
+    for line in lines {
+        let fields = split_csv_line(line);
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|i| fields.get(i))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+
+        let Some(date) =
+            field(Some(date_column)).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        else {
+            log::warn!("Skipping calendar line without a valid date: {}", line);
+            continue;
+        };
+        let Some(title) = field(Some(title_column)) else {
+            log::warn!("Skipping calendar line without a title: {}", line);
+            continue;
+        };
+
+        events.push(CalendarEvent::new(
+            date,
+            field(time_column).and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok()),
+            field(currency_column).map_or(vec![], split_currencies),
+            field(kind_column).and_then(EventKind::parse),
+            title.to_string(),
+        ));
+    }
+    Ok(events)
+}
+
+/// Fields of a CSV line, quoted fields may contain commas and `""` quotes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Events of `from` or `to` between `start` and `end` (inclusive), in order
+pub fn get_pair_events<'a>(
+    events: &'a [CalendarEvent],
+    from: &str,
+    to: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<&'a CalendarEvent> {
+    let mut pair_events: Vec<&CalendarEvent> = events
+        .iter()
+        .filter(|event| event.date >= start && event.date <= end)
+        .filter(|event| event.concerns(from) || event.concerns(to))
+        .collect();
+    pair_events.sort_by_key(|event| (event.date, event.time));
+    pair_events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   VERSION:2.0\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART:20241017T121500Z\r\n\
+                   SUMMARY:ECB monetary policy\r\n  decision\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART;VALUE=DATE:20241128\r\n\
+                   SUMMARY:Thanksgiving Day\r\n\
+                   CATEGORIES:Holiday\r\n\
+                   X-CURRENCY:USD\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   SUMMARY:No date\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+
+        let events = parse_calendar(ics).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].date, date(2024, 10, 17));
+        assert_eq!(events[0].time, NaiveTime::from_hms_opt(12, 15, 0));
+        assert_eq!(events[0].title, "ECB monetary policy decision");
+        assert_eq!(events[0].currencies, ["EUR"]);
+        assert_eq!(events[0].kind, EventKind::RateDecision);
+        assert_eq!(events[1].time, None);
+        assert_eq!(events[1].currencies, ["USD"]);
+        assert_eq!(events[1].kind, EventKind::Holiday);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "date,time,currency,kind,title\n\
+                   2024-10-23,14:00,CAD,rate_decision,\"Bank of Canada rate decision, with press conference\"\n\
+                   2024-10-15,,,,Canada CPI\n\
+                   not a date,,,,Broken line\n";
+
+        let events = parse_calendar(csv).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].title,
+            "Bank of Canada rate decision, with press conference"
+        );
+        assert_eq!(events[1].currencies, ["CAD"]);
+        assert_eq!(events[1].kind, EventKind::Inflation);
+
+        let pair_events =
+            get_pair_events(&events, "USD", "CAD", date(2024, 10, 1), date(2024, 10, 31));
+        assert_eq!(pair_events[0].date, date(2024, 10, 15));
+        assert!(
+            get_pair_events(&events, "EUR", "JPY", date(2024, 10, 1), date(2024, 10, 31))
+                .is_empty()
+        );
+
+        assert!(parse_calendar("when,what\n").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+/// Opening, highest, lowest and closing rate of a period
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candle {
+    /// First day of the period
+    pub start: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CandlePeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl CandlePeriod {
+    /// First day of the period holding `date`, weeks start on Monday
+    pub fn get_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            CandlePeriod::Day => date,
+            CandlePeriod::Week => {
+                date - Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            CandlePeriod::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Days of the period starting on `start`
+    pub fn get_days(&self, start: NaiveDate) -> i64 {
+        match self {
+            CandlePeriod::Day => 1,
+            CandlePeriod::Week => 7,
+            CandlePeriod::Month => {
+                let next = match start.month() {
+                    12 => NaiveDate::from_ymd_opt(start.year() + 1, 1, 1),
+                    month => NaiveDate::from_ymd_opt(start.year(), month + 1, 1),
+                };
+                next.map_or(31, |next| (next - start).num_days())
+            }
+        }
+    }
+}
+
+/// Rates grouped into one candle per `period`, oldest first
+pub fn get_candles(rates: &[(NaiveDateTime, f64)], period: CandlePeriod) -> Vec<Candle> {
+    let mut rates = rates.to_vec();
+    rates.sort_by_key(|(time, _)| *time);
+
+    let mut candles: Vec<Candle> = vec![];
+    for (time, rate) in rates {
+        let start = period.get_start(time.date());
+        match candles.last_mut() {
+            Some(candle) if candle.start == start => {
+                candle.high = candle.high.max(rate);
+                candle.low = candle.low.min(rate);
+                candle.close = rate;
+            }
+            _ => candles.push(Candle {
+                start,
+                open: rate,
+                high: rate,
+                low: rate,
+                close: rate,
+            }),
+        }
+    }
+    candles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(m: u32, d: u32, h: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, m, d)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_get_candles() {
+        let rates = vec![
+            (time(1, 31, 0), 1.05),
+            (time(1, 29, 0), 1.00),
+            (time(1, 30, 0), 1.10),
+            (time(2, 1, 9), 1.02),
+            (time(2, 1, 17), 0.98),
+        ];
+
+        let weekly = get_candles(&rates, CandlePeriod::Week);
+        assert_eq!(weekly.len(), 1);
+        assert_eq!(
+            weekly[0],
+            Candle {
+                start: NaiveDate::from_ymd_opt(2024, 1, 29).unwrap(),
+                open: 1.00,
+                high: 1.10,
+                low: 0.98,
+                close: 0.98,
+            }
+        );
+
+        let monthly = get_candles(&rates, CandlePeriod::Month);
+        assert_eq!(monthly.len(), 2);
+        assert_eq!(monthly[0].close, 1.05);
+        assert_eq!(monthly[1].open, 1.02);
+        assert_eq!(CandlePeriod::Month.get_days(monthly[1].start), 29);
+
+        let daily = get_candles(&rates, CandlePeriod::Day);
+        assert_eq!(daily.len(), 4);
+        assert_eq!((daily[3].high, daily[3].low), (1.02, 0.98));
+    }
+}
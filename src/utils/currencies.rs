@@ -0,0 +1,90 @@
+/// What news about a currency mentions
+#[derive(Debug)]
+pub struct CurrencyInfo {
+    pub code: &'static str,
+    /// Name of the currency without the country, e.g. `dollar`
+    pub name: &'static str,
+    /// Country or region issuing the currency
+    pub region: &'static str,
+    pub central_bank: &'static str,
+}
+
+const fn info(
+    code: &'static str,
+    name: &'static str,
+    region: &'static str,
+    central_bank: &'static str,
+) -> CurrencyInfo {
+    CurrencyInfo {
+        code,
+        name,
+        region,
+        central_bank,
+    }
+}
+
+/// The currencies of the default exchange rate API
+const CURRENCIES: &[CurrencyInfo] = &[
+    info("AUD", "dollar", "Australia", "Reserve Bank of Australia"),
+    info("BGN", "lev", "Bulgaria", "Bulgarian National Bank"),
+    info("BRL", "real", "Brazil", "Banco Central do Brasil"),
+    info("CAD", "dollar", "Canada", "Bank of Canada"),
+    info("CHF", "franc", "Switzerland", "Swiss National Bank"),
+    info("CNY", "yuan", "China", "People's Bank of China"),
+    info("CZK", "koruna", "Czech Republic", "Czech National Bank"),
+    info("DKK", "krone", "Denmark", "Danmarks Nationalbank"),
+    info("EUR", "euro", "Eurozone", "European Central Bank"),
+    info("GBP", "pound", "United Kingdom", "Bank of England"),
+    info("HKD", "dollar", "Hong Kong", "Hong Kong Monetary Authority"),
+    info("HUF", "forint", "Hungary", "Magyar Nemzeti Bank"),
+    info("IDR", "rupiah", "Indonesia", "Bank Indonesia"),
+    info("ILS", "shekel", "Israel", "Bank of Israel"),
+    info("INR", "rupee", "India", "Reserve Bank of India"),
+    info("ISK", "krona", "Iceland", "Central Bank of Iceland"),
+    info("JPY", "yen", "Japan", "Bank of Japan"),
+    info("KRW", "won", "South Korea", "Bank of Korea"),
+    info("MXN", "peso", "Mexico", "Banco de México"),
+    info("MYR", "ringgit", "Malaysia", "Bank Negara Malaysia"),
+    info("NOK", "krone", "Norway", "Norges Bank"),
+    info(
+        "NZD",
+        "dollar",
+        "New Zealand",
+        "Reserve Bank of New Zealand",
+    ),
+    info("PHP", "peso", "Philippines", "Bangko Sentral ng Pilipinas"),
+    info("PLN", "zloty", "Poland", "National Bank of Poland"),
+    info("RON", "leu", "Romania", "National Bank of Romania"),
+    info("SEK", "krona", "Sweden", "Riksbank"),
+    info(
+        "SGD",
+        "dollar",
+        "Singapore",
+        "Monetary Authority of Singapore",
+    ),
+    info("THB", "baht", "Thailand", "Bank of Thailand"),
+    info(
+        "TRY",
+        "lira",
+        "Turkey",
+        "Central Bank of the Republic of Turkey",
+    ),
+    info(
+        "TWD",
+        "dollar",
+        "Taiwan",
+        "Central Bank of the Republic of China",
+    ),
+    info("USD", "dollar", "United States", "Federal Reserve"),
+    info("ZAR", "rand", "South Africa", "South African Reserve Bank"),
+];
+
+pub fn get_currency_info(code: &str) -> Option<&'static CurrencyInfo> {
+    CURRENCIES
+        .iter()
+        .find(|info| info.code.eq_ignore_ascii_case(code))
+}
+
+pub fn get_currencies() -> &'static [CurrencyInfo] {
+    CURRENCIES
+}
@@ -0,0 +1,56 @@
+//! Exchange rates shaped like those of [`ExchangeRateMap::get_rates`], shared by the tests
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+
+use crate::exchange_rate::ExchangeRateMap;
+
+use super::stats::get_series;
+
+/// First day of the rates, a Monday
+pub fn get_start() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+}
+
+/// Rates of `base` on `date`
+pub fn get_day(date: NaiveDate, base: &str, rates: &[(&str, f64)]) -> ExchangeRateMap {
+    ExchangeRateMap {
+        datetime: date.and_time(NaiveTime::MIN).and_utc(),
+        base: base.to_string(),
+        map: rates
+            .iter()
+            .map(|(currency, value)| (currency.to_string(), *value))
+            .collect(),
+    }
+}
+
+/// Rates of EUR published on the working days from [`get_start`] on, taking
+/// the next value of every currency of `series` each day.
+///
+/// Weekends repeat the rates of Friday, the way `get_rates` fills closing days.
+pub fn get_daily_rates(series: &[(&str, &[f64])]) -> Vec<ExchangeRateMap> {
+    let days = series.iter().map(|(_, values)| values.len()).min();
+    let mut rates: Vec<ExchangeRateMap> = vec![];
+    let mut date = get_start();
+    for day in 0..days.unwrap_or_default() {
+        while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            let map = rates.last().map(|friday| friday.map.clone());
+            rates.push(ExchangeRateMap {
+                map: map.unwrap_or_default(),
+                ..get_day(date, "EUR", &[])
+            });
+            date += Duration::days(1);
+        }
+        let values: Vec<(&str, f64)> = series
+            .iter()
+            .map(|(currency, values)| (*currency, values[day]))
+            .collect();
+        rates.push(get_day(date, "EUR", &values));
+        date += Duration::days(1);
+    }
+    rates
+}
+
+/// Daily values of EUR to USD taking `values` on working days, see [`get_daily_rates`]
+pub fn get_daily_series(values: &[f64]) -> Vec<(NaiveDate, f64)> {
+    get_series(&get_daily_rates(&[("USD", values)]), "EUR", "USD")
+}
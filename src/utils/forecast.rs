@@ -0,0 +1,334 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use super::stats::get_trading_days;
+
+/// Two-sided 95% quantile of the normal distribution
+const Z_95: f64 = 1.96;
+
+/// Span of the average of the daily moves of the EWMA drift
+const EWMA_SPAN: f64 = 20.0;
+
+/// Smoothing of the level, trend and season of Holt-Winters
+const HOLT_WINTERS_SMOOTHING: (f64, f64, f64) = (0.5, 0.05, 0.1);
+
+/// Working days in a week, the season of Holt-Winters
+const SEASON_LENGTH: usize = 5;
+
+/// Fewest rates a forecast is made from, two seasons of Holt-Winters
+pub const MIN_HISTORY: usize = 2 * SEASON_LENGTH;
+
+/// Forecasts the backtest averages over
+const BACKTEST_FORECASTS: usize = 20;
+
+/// Model extrapolating the rates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForecastModel {
+    /// Latest rate moving by an exponentially weighted average of the daily moves
+    EwmaDrift,
+    /// Least squares line through the rates
+    LinearRegression,
+    /// Additive Holt-Winters with a weekly season
+    HoltWinters,
+}
+
+impl ForecastModel {
+    pub const ALL: [ForecastModel; 3] = [
+        ForecastModel::EwmaDrift,
+        ForecastModel::LinearRegression,
+        ForecastModel::HoltWinters,
+    ];
+
+    pub const NAMES: &'static [&'static str] = &["ewma", "linear", "holt-winters"];
+
+    pub fn from_name(name: &str) -> Option<ForecastModel> {
+        match name.to_lowercase().as_str() {
+            "ewma" => Some(ForecastModel::EwmaDrift),
+            "linear" => Some(ForecastModel::LinearRegression),
+            "holt-winters" => Some(ForecastModel::HoltWinters),
+            _ => None,
+        }
+    }
+
+    /// Name shown in messages and charts
+    pub fn label(&self) -> &'static str {
+        match self {
+            ForecastModel::EwmaDrift => "EWMA drift",
+            ForecastModel::LinearRegression => "Linear regression",
+            ForecastModel::HoltWinters => "Holt-Winters",
+        }
+    }
+
+    /// Forecasts of the next `steps` values and the half width of their 95%
+    /// interval, `None` with fewer than [`MIN_HISTORY`] values
+    fn predict(&self, values: &[f64], steps: usize) -> Option<Vec<(f64, f64)>> {
+        if values.len() < MIN_HISTORY {
+            return None;
+        }
+        let forecast = match self {
+            ForecastModel::EwmaDrift => predict_ewma_drift(values, steps),
+            ForecastModel::LinearRegression => predict_linear(values, steps),
+            ForecastModel::HoltWinters => predict_holt_winters(values, steps),
+        };
+        Some(forecast)
+    }
+}
+
+/// Forecast of one day
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ForecastPoint {
+    pub date: NaiveDate,
+    pub value: f64,
+    /// Lower end of the 95% interval
+    pub low: f64,
+    /// Upper end of the 95% interval
+    pub high: f64,
+}
+
+/// Error of forecasts made from earlier rates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backtest {
+    /// Number of forecasts
+    pub forecasts: usize,
+    /// Mean absolute percentage error of all forecast days
+    pub error: f64,
+    /// Percentage of the rates within the 95% interval
+    pub coverage: f64,
+}
+
+/// Root mean square of `errors`
+fn get_rms(errors: &[f64]) -> f64 {
+    match errors.len() {
+        0 => 0.0,
+        len => (errors.iter().map(|e| e * e).sum::<f64>() / len as f64).sqrt(),
+    }
+}
+
+fn predict_ewma_drift(values: &[f64], steps: usize) -> Vec<(f64, f64)> {
+    let alpha = 2.0 / (EWMA_SPAN + 1.0);
+    let mut drift = values[1] - values[0];
+    let mut errors = vec![];
+    for pair in values[1..].windows(2) {
+        let diff = pair[1] - pair[0];
+        errors.push(diff - drift);
+        drift = alpha * diff + (1.0 - alpha) * drift;
+    }
+    let deviation = get_rms(&errors);
+    let last = values[values.len() - 1];
+    (1..=steps)
+        .map(|h| {
+            (
+                last + h as f64 * drift,
+                Z_95 * deviation * (h as f64).sqrt(),
+            )
+        })
+        .collect()
+}
+
+fn predict_linear(values: &[f64], steps: usize) -> Vec<(f64, f64)> {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let sxx: f64 = (0..values.len()).map(|x| (x as f64 - mean_x).powi(2)).sum();
+    let sxy: f64 = values
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (x as f64 - mean_x) * (y - mean_y))
+        .sum();
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+
+    let residuals: f64 = values
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (y - intercept - slope * x as f64).powi(2))
+        .sum();
+    let deviation = (residuals / (n - 2.0)).sqrt();
+    (1..=steps)
+        .map(|h| {
+            let x = n - 1.0 + h as f64;
+            let spread = (1.0 + 1.0 / n + (x - mean_x).powi(2) / sxx).sqrt();
+            (intercept + slope * x, Z_95 * deviation * spread)
+        })
+        .collect()
+}
+
+fn predict_holt_winters(values: &[f64], steps: usize) -> Vec<(f64, f64)> {
+    let (alpha, beta, gamma) = HOLT_WINTERS_SMOOTHING;
+    let m = SEASON_LENGTH;
+    let first = values[..m].iter().sum::<f64>() / m as f64;
+    let second = values[m..2 * m].iter().sum::<f64>() / m as f64;
+
+    // The first season around its average, which is in the middle of the season
+    let mut trend = (second - first) / m as f64;
+    let middle = (m - 1) as f64 / 2.0;
+    let mut level = first + middle * trend;
+    let mut season: Vec<f64> = values[..m]
+        .iter()
+        .enumerate()
+        .map(|(i, v)| v - first - (i as f64 - middle) * trend)
+        .collect();
+    let mut errors = vec![];
+    for (t, value) in values.iter().enumerate().skip(m) {
+        let s = season[t % m];
+        errors.push(value - (level + trend + s));
+        let new_level = alpha * (value - s) + (1.0 - alpha) * (level + trend);
+        trend = beta * (new_level - level) + (1.0 - beta) * trend;
+        season[t % m] = gamma * (value - new_level) + (1.0 - gamma) * s;
+        level = new_level;
+    }
+    let deviation = get_rms(&errors);
+    let n = values.len();
+    (1..=steps)
+        .map(|h| {
+            (
+                level + h as f64 * trend + season[(n + h - 1) % m],
+                Z_95 * deviation * (h as f64).sqrt(),
+            )
+        })
+        .collect()
+}
+
+/// Working days after `date` until `days` days later
+pub fn get_working_days(date: NaiveDate, days: i64) -> Vec<NaiveDate> {
+    (1..=days)
+        .map(|i| date + Duration::days(i))
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .collect()
+}
+
+/// Values of the trading days of sorted `data`, the weekends and holidays
+/// carried forward would look like days without a move to the models
+fn get_values(data: &[(NaiveDate, f64)]) -> Vec<f64> {
+    get_trading_days(data).iter().map(|(_, v)| *v).collect()
+}
+
+/// Forecast of `model` for the working days of the `days` days after sorted `data`,
+/// `None` without enough rates
+pub fn get_forecast(
+    model: ForecastModel,
+    data: &[(NaiveDate, f64)],
+    days: i64,
+) -> Option<Vec<ForecastPoint>> {
+    let (last_date, _) = *data.last()?;
+    let dates = get_working_days(last_date, days);
+    let values = get_values(data);
+    let forecast = model.predict(&values, dates.len())?;
+    Some(
+        dates
+            .into_iter()
+            .zip(forecast)
+            .map(|(date, (value, width))| ForecastPoint {
+                date,
+                value,
+                low: value - width,
+                high: value + width,
+            })
+            .collect(),
+    )
+}
+
+/// Error of the forecasts of `model` over the next `steps` rates, made from
+/// the rates before each of the last trading days of `data`.
+///
+/// `None` when there are not enough rates for a single forecast.
+pub fn get_backtest(
+    model: ForecastModel,
+    data: &[(NaiveDate, f64)],
+    steps: usize,
+) -> Option<Backtest> {
+    let values = get_values(data);
+    let last_origin = values.len().checked_sub(steps)?;
+    let first_origin = last_origin
+        .saturating_sub(BACKTEST_FORECASTS - 1)
+        .max(MIN_HISTORY);
+
+    let mut forecasts = 0;
+    let mut errors = vec![];
+    let mut covered = 0;
+    for origin in first_origin..=last_origin {
+        let Some(forecast) = model.predict(&values[..origin], steps) else {
+            continue;
+        };
+        forecasts += 1;
+        for ((value, width), actual) in forecast.into_iter().zip(&values[origin..]) {
+            errors.push(((value - actual) / actual).abs() * 100.0);
+            if (actual - value).abs() <= width {
+                covered += 1;
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        return None;
+    }
+    Some(Backtest {
+        forecasts,
+        error: errors.iter().sum::<f64>() / errors.len() as f64,
+        coverage: covered as f64 / errors.len() as f64 * 100.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::get_daily_series as get_data;
+
+    #[test]
+    fn test_forecast_line() {
+        let values: Vec<f64> = (0..30).map(|i| 1.0 + i as f64 * 0.01).collect();
+        let data = get_data(&values);
+        // Learned from the working days only, the weekends do not flatten the line
+        let working: Vec<_> = data
+            .iter()
+            .filter(|(date, _)| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+            .copied()
+            .collect();
+        for model in ForecastModel::ALL {
+            assert_eq!(
+                get_forecast(model, &data, 7),
+                get_forecast(model, &working, 7)
+            );
+            let forecast = get_forecast(model, &data, 7).unwrap();
+            // Friday 2024-02-09 is the last rate, the weekend is skipped
+            assert_eq!(forecast.len(), 5, "{:?}", model);
+            assert_eq!(
+                forecast[0].date,
+                NaiveDate::from_ymd_opt(2024, 2, 12).unwrap()
+            );
+            assert!((forecast[4].value - 1.34).abs() < 1e-6, "{:?}", model);
+
+            let backtest = get_backtest(model, &data, 5).unwrap();
+            assert_eq!(backtest.forecasts, 16);
+            assert!(backtest.error < 1e-6, "{:?}", model);
+        }
+        assert_eq!(
+            get_forecast(ForecastModel::HoltWinters, &get_data(&values[..9]), 7),
+            None
+        );
+        assert_eq!(
+            get_backtest(ForecastModel::HoltWinters, &get_data(&values[..12]), 5),
+            None
+        );
+    }
+
+    #[test]
+    fn test_forecast_interval() {
+        // A weekly pattern and noise around a flat rate
+        let values: Vec<f64> = (0..60)
+            .map(|i| 1.0 + [0.0, 0.02, -0.01, 0.01, -0.02][i % 5] + (i * 7 % 11) as f64 * 0.0005)
+            .collect();
+        let data = get_data(&values);
+        for model in ForecastModel::ALL {
+            let forecast = get_forecast(model, &data, 14).unwrap();
+            assert!(forecast.iter().all(|p| p.low < p.value && p.value < p.high));
+            // Further days are less certain
+            let width = |p: &ForecastPoint| p.high - p.low;
+            assert!(width(&forecast[9]) >= width(&forecast[0]), "{:?}", model);
+        }
+
+        // The weekly pattern is learned by Holt-Winters only
+        let error = |model| get_backtest(model, &data, 5).unwrap().error;
+        assert!(error(ForecastModel::HoltWinters) < error(ForecastModel::EwmaDrift));
+        assert!(error(ForecastModel::HoltWinters) < error(ForecastModel::LinearRegression));
+    }
+}
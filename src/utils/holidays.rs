@@ -0,0 +1,95 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use super::calendar::{CalendarEvent, EventKind};
+
+/// Easter Sunday of `year` (anonymous Gregorian algorithm)
+fn get_easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// Name of the TARGET closing day on `date`.
+///
+/// The ECB publishes no reference rates on these days, so the default
+/// exchange rate API has no data for them either.
+pub fn get_holiday(date: NaiveDate) -> Option<&'static str> {
+    match (date.month(), date.day()) {
+        (1, 1) => return Some("New Year's Day"),
+        (5, 1) => return Some("Labour Day"),
+        (12, 25) => return Some("Christmas Day"),
+        (12, 26) => return Some("Christmas Holiday"),
+        _ => {}
+    }
+
+    let easter = get_easter(date.year())?;
+    if date == easter - Duration::days(2) {
+        return Some("Good Friday");
+    }
+    if date == easter + Duration::days(1) {
+        return Some("Easter Monday");
+    }
+    None
+}
+
+/// Name of the holiday the ECB publishes no rates on `date`.
+///
+/// Besides the TARGET closing days, EUR holidays of the economic calendar
+/// count as well.
+pub fn get_closing_day(date: NaiveDate, events: &[CalendarEvent]) -> Option<String> {
+    if let Some(holiday) = get_holiday(date) {
+        return Some(holiday.to_string());
+    }
+    events
+        .iter()
+        .find(|event| {
+            event.date == date && event.kind == EventKind::Holiday && event.concerns("EUR")
+        })
+        .map(|event| event.title.clone())
+}
+
+/// Whether the ECB publishes no rates on `date`, on weekends and holidays
+pub fn is_closing_day(date: NaiveDate, events: &[CalendarEvent]) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || get_closing_day(date, events).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_closing_days() {
+        assert_eq!(get_holiday(date(2024, 3, 29)), Some("Good Friday"));
+        assert_eq!(get_holiday(date(2024, 4, 1)), Some("Easter Monday"));
+
+        let events = vec![CalendarEvent {
+            date: date(2024, 10, 3),
+            time: None,
+            currencies: vec!["EUR".to_string()],
+            kind: EventKind::Holiday,
+            title: "Market closure".to_string(),
+        }];
+        assert_eq!(
+            get_closing_day(date(2024, 10, 3), &events).as_deref(),
+            Some("Market closure")
+        );
+        assert!(is_closing_day(date(2024, 10, 5), &[]));
+        assert!(!is_closing_day(date(2024, 10, 3), &[]));
+    }
+}
@@ -0,0 +1,83 @@
+/// Simple moving average of the last `period` values, `None` until there
+/// are enough values
+pub fn get_sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|i| match i + 1 >= period && period > 0 {
+            true => Some(values[i + 1 - period..=i].iter().sum::<f64>() / period as f64),
+            false => None,
+        })
+        .collect()
+}
+
+/// Exponential moving average over `period` values, starting with the simple
+/// average of the first `period` values
+pub fn get_ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut ema: Option<f64> = None;
+    get_sma(values, period)
+        .into_iter()
+        .zip(values)
+        .map(|(sma, value)| {
+            ema = match ema {
+                Some(ema) => Some(alpha * value + (1.0 - alpha) * ema),
+                None => sma,
+            };
+            ema
+        })
+        .collect()
+}
+
+/// Lower band, moving average and upper band `width` standard deviations
+/// around the average of the last `period` values
+pub fn get_bollinger_bands(
+    values: &[f64],
+    period: usize,
+    width: f64,
+) -> Vec<Option<(f64, f64, f64)>> {
+    get_sma(values, period)
+        .into_iter()
+        .enumerate()
+        .map(|(i, sma)| {
+            let sma = sma?;
+            let window = &values[i + 1 - period..=i];
+            let deviation =
+                (window.iter().map(|v| (v - sma).powi(2)).sum::<f64>() / period as f64).sqrt();
+            Some((sma - width * deviation, sma, sma + width * deviation))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moving_averages() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(
+            get_sma(&values, 3),
+            [None, None, Some(2.0), Some(3.0), Some(4.0)]
+        );
+        // Starts at the simple average, then weighs new values by 2 / (3 + 1)
+        assert_eq!(
+            get_ema(&values, 3),
+            [None, None, Some(2.0), Some(3.0), Some(4.0)]
+        );
+        assert_eq!(
+            get_ema(&[1.0, 1.0, 3.0], 1),
+            [Some(1.0), Some(1.0), Some(3.0)]
+        );
+        assert_eq!(
+            get_ema(&[2.0, 2.0, 6.0], 2)[2],
+            Some(2.0 / 3.0 * 6.0 + 1.0 / 3.0 * 2.0)
+        );
+
+        let bands = get_bollinger_bands(&[1.0, 3.0, 1.0, 3.0], 2, 2.0);
+        assert_eq!(bands[0], None);
+        assert_eq!(bands[1], Some((0.0, 2.0, 4.0)));
+        assert_eq!(
+            get_bollinger_bands(&[2.0, 2.0], 2, 2.0)[1],
+            Some((2.0, 2.0, 2.0))
+        );
+    }
+}
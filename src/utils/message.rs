@@ -1,24 +1,49 @@
 use chrono::{Duration, Utc};
+use tokio::sync::watch;
 
 use crate::{
     database::exchange_rate::save_exchange_rate,
     environment,
     exchange_rate::ExchangeRateMap,
-    llm::{generate::generate_sentence, prompt::get_prompt},
+    i18n::{fill, Locale},
+    llm::{
+        backend::LlmError,
+        generate::{generate_sentence, GenerationOptions},
+        guardrails::Guardrails,
+        narrative::get_narrative,
+        prompt::{get_known_values, get_prompt},
+    },
+    utils::{calendar::get_events, search::get_news},
 };
 
-use super::plots::get_trend_graph;
+use super::{
+    plots::{get_chart, ChartOptions},
+    theme::get_guild_theme,
+};
 
 pub struct ExchangeRateMessage {
     pub message: String,
     pub graph: Option<Vec<u8>>,
 }
 
-pub async fn get_exchange_rate_message(from: &str, to: &str) -> ExchangeRateMessage {
-    // Calculate the date 7 days ago
-    let from_date = (Utc::now() - Duration::days(30)).date_naive();
+/// Build the exchange rate report for `from` to `to`.
+///
+/// The templates of `guild_id` are used and the report is written in `locale`.
+/// A recently generated report is reused unless `refresh` is set. `progress`
+/// receives the partially generated text while the LLM is streaming.
+pub async fn get_exchange_rate_message(
+    from: &str,
+    to: &str,
+    guild_id: Option<u64>,
+    locale: Locale,
+    refresh: bool,
+    progress: Option<watch::Sender<String>>,
+) -> ExchangeRateMessage {
+    // 90 days are needed by the prompt template variables, only 30 are charted
+    let from_date = (Utc::now() - Duration::days(90)).date_naive();
 
     let rates = ExchangeRateMap::get_rates(from_date, Some(from.into())).await;
+    let catalog = locale.catalog();
 
     match rates {
         Ok(rates) => {
@@ -26,10 +51,11 @@ pub async fn get_exchange_rate_message(from: &str, to: &str) -> ExchangeRateMess
             for r in &rates {
                 log::debug!("{}",r);
             };
-            let prompt = get_prompt(&rates, from, to);
+            // Rendered without news and events, the prompt identifies a cached report
+            let cache_key = get_prompt(&rates, from, to, &[], &[], guild_id, locale);
 
             let rate: f64 = rates
-                .get(0)
+                .last()
                 .cloned()
                 .unwrap_or_default()
                 .get_val(from, to)
@@ -41,52 +67,159 @@ pub async fn get_exchange_rate_message(from: &str, to: &str) -> ExchangeRateMess
             // keep track how much time it takes to generate the sentence
             let start = std::time::Instant::now();
 
-            let llm_res = generate_sentence(prompt.as_str()).await;
+            let guardrails = Guardrails::new(get_known_values(&rates, from, to), &[from, to]);
+            let llm_res = generate_sentence(
+                &cache_key,
+                (from, to),
+                async {
+                    let news = get_news(from, to, Utc::now(), 5).await;
+                    let events = get_events().await;
+                    let prompt = get_prompt(&rates, from, to, &news, &events, guild_id, locale);
+                    (prompt, news)
+                },
+                GenerationOptions {
+                    guild_id,
+                    locale,
+                    refresh,
+                    guardrails: &guardrails,
+                    progress,
+                },
+            )
+            .await;
 
             let elapsed_llm = start.elapsed();
 
             let start_graph = std::time::Instant::now();
-            let graph_result = get_trend_graph(&rates, from, to);
+            let chart_from = (Utc::now() - Duration::days(30)).date_naive();
+            let graph_result = get_chart(
+                &rates,
+                from,
+                to,
+                chart_from,
+                &ChartOptions {
+                    mode: environment::get_chart_mode(),
+                    overlays: environment::get_chart_overlays(),
+                    theme: get_guild_theme(guild_id),
+                    format: environment::get_chart_format(),
+                    locale,
+                },
+            );
             let elapsed_graph = start_graph.elapsed();
             let elapsed_total = start.elapsed();
 
+            // Without a usable LLM, the template narrative takes over
+            let (content, llm_message) = match llm_res {
+                Ok(llm_res) if llm_res.cached_at.is_some() => {
+                    let cached_at = llm_res.cached_at.unwrap_or_default();
+                    let llm_message = fill(
+                        catalog.cached_response,
+                        &[
+                            ("DATE", &locale.format_date(cached_at.date())),
+                            ("TIME", &cached_at.format("%H:%M").to_string()),
+                        ],
+                    );
+                    (llm_res.content, llm_message)
+                }
+                Ok(llm_res) => {
+                    let eval_duration = llm_res.eval_duration();
+                    let search_duration = llm_res.search_duration;
+                    let speed_message = llm_res
+                        .tokens_per_second()
+                        .map(|speed| {
+                            format!(
+                                ", {}",
+                                fill(
+                                    catalog.tokens_per_second,
+                                    &[("SPEED", &locale.format_number(speed, 1))]
+                                )
+                            )
+                        })
+                        .unwrap_or_default();
+                    let token_message = match (llm_res.prompt_tokens, llm_res.completion_tokens) {
+                        (Some(prompt_tokens), Some(tokens)) => format!(
+                            " ({}{})",
+                            fill(
+                                catalog.prompt_and_generated_tokens,
+                                &[
+                                    ("PROMPT", &prompt_tokens.to_string()),
+                                    ("GENERATED", &tokens.to_string())
+                                ]
+                            ),
+                            speed_message
+                        ),
+                        (None, Some(tokens)) => format!(
+                            " ({})",
+                            fill(catalog.generated_tokens, &[("GENERATED", &tokens.to_string())])
+                        ),
+                        _ => String::new(),
+                    };
+                    let tool_message = match llm_res.tool_calls {
+                        0 => String::new(),
+                        n => format!("\n{}", fill(catalog.called_tools, &[("COUNT", &n.to_string())])),
+                    };
+
+                    let llm_message = format!(
+                        "{}\n{}\n{}{}{}",
+                        fill(
+                            catalog.searched_in,
+                            &[("SECONDS", &locale.format_seconds(search_duration))]
+                        ),
+                        fill(
+                            catalog.first_token_in,
+                            &[("SECONDS", &locale.format_seconds(llm_res.first_token_duration))]
+                        ),
+                        fill(
+                            catalog.evaluated_in,
+                            &[("SECONDS", &locale.format_seconds(eval_duration))]
+                        ),
+                        token_message,
+                        tool_message,
+                    );
+                    (llm_res.content, llm_message)
+                }
+                Err(LlmError::Disabled) => (
+                    get_narrative(&rates, from, to, locale),
+                    catalog.narrative_footer.to_string(),
+                ),
+                Err(LlmError::Rejected(e)) => (
+                    get_narrative(&rates, from, to, locale),
+                    fill(catalog.narrative_rejected_footer, &[("ERROR", &e.to_string())]),
+                ),
+                Err(e) => (
+                    get_narrative(&rates, from, to, locale),
+                    fill(
+                        catalog.narrative_llm_unavailable_footer,
+                        &[("ERROR", &e.to_string())],
+                    ),
+                ),
+            };
+
             let graph_message = match &graph_result {
                 Ok(_) => String::new(), // No additional message if there's no error
-                Err(err) => format!("\nGraph generation error: {}", err), // Include error message
+                Err(err) => format!("\n{}", fill(catalog.graph_error, &[("ERROR", &err.to_string())])), // Include error message
             };
 
             let msg = format!(
                 "{}\n\
                 ```\n\
                 1 {} = {} {}\n\
-                Searched in {}.{:03} seconds\n\
-                Model Loaded in {}.{:03} seconds\n\
-                Evaluated in {}.{:03} seconds\n\
-                Graph generated in {}.{:03} seconds{}\n\
-                Generated in {}.{:03} seconds\n\
+                {}\n\
+                {}{}\n\
+                {}\n\
                 ```",
-                llm_res.content,
+                content,
 
                 from,
-                rate,
+                locale.format_decimal(&rate.to_string()),
                 to,
 
-                llm_res.search_duration.as_secs(),
-                llm_res.search_duration.subsec_millis(),
-
-                llm_res.load_duration.as_secs(),
-                llm_res.load_duration.subsec_millis(),
+                llm_message,
 
-                llm_res.eval_duration.as_secs(),
-                llm_res.eval_duration.subsec_millis(),
-
-                elapsed_graph.as_secs(),
-                elapsed_graph.subsec_millis(),
+                fill(catalog.graph_generated_in, &[("SECONDS", &locale.format_seconds(elapsed_graph))]),
 
                 graph_message, // Add the error message dynamically
-                
-                elapsed_total.as_secs(),
-                elapsed_total.subsec_millis(),
+
+                fill(catalog.generated_in, &[("SECONDS", &locale.format_seconds(elapsed_total))]),
             );
 
             ExchangeRateMessage{
@@ -96,8 +229,13 @@ pub async fn get_exchange_rate_message(from: &str, to: &str) -> ExchangeRateMess
         }
         Err(e) => {
             ExchangeRateMessage{
-                message:format!("Error fetching API. Please verify the API URL and Internet connection. URL used: `{}`\n`Error: {:?}`", 
-                environment::get_exchange_rate_api_url(), e),
+                message: fill(
+                    catalog.api_error,
+                    &[
+                        ("URL", &environment::get_exchange_rate_api_url()),
+                        ("ERROR", &format!("{:?}", e)),
+                    ],
+                ),
                 graph: None
             }
         }
@@ -1,3 +1,16 @@
+pub mod anomaly;
+pub mod basket;
+pub mod calendar;
+pub mod candles;
+pub mod currencies;
+#[cfg(test)]
+pub mod fixtures;
+pub mod forecast;
+pub mod holidays;
+pub mod indicators;
 pub mod message;
 pub mod plots;
+pub mod progress;
 pub mod search;
+pub mod stats;
+pub mod theme;
@@ -1,13 +1,39 @@
-use std::io;
+use std::{io, ops::Range};
 
-use chrono::NaiveDate;
+use chrono::{Duration, NaiveDate, NaiveTime};
 use image::{ImageEncoder, ImageError, RgbImage};
-use plotters::style::WHITE;
-
-use plotters::prelude::*;
+use plotters::{
+    coord::{types::RangedCoordf64, Shift},
+    prelude::*,
+    style::text_anchor::{HPos, Pos, VPos},
+};
 use thiserror::Error;
 
-use crate::exchange_rate::ExchangeRateMap;
+use crate::{
+    database::exchange_rate::get_saved_exchange_rates,
+    exchange_rate::ExchangeRateMap,
+    i18n::{fill, Catalog, Locale},
+};
+
+use super::{
+    candles::{get_candles, Candle, CandlePeriod},
+    forecast::{ForecastModel, ForecastPoint},
+    indicators::{get_bollinger_bands, get_ema, get_sma},
+    stats::get_series,
+    theme::Theme,
+};
+
+/// Period of the averages and bands listed without one
+const DEFAULT_OVERLAY_PERIOD: usize = 20;
+
+/// Standard deviations between the Bollinger bands and their average
+const BOLLINGER_WIDTH: f64 = 2.0;
+
+/// Width and height of the cells of the cross rate matrix at 96 DPI
+const MATRIX_CELL_SIZE: (u32, u32) = (100, 44);
+
+/// Percent change taking the full color, unless a larger change does
+const MATRIX_MIN_COLORED_CHANGE: f64 = 0.5;
 
 #[derive(Error, Debug)]
 pub enum PlotError {
@@ -29,6 +55,9 @@ pub enum PlotError {
     #[error("Failed to encode PNG: {0}")]
     PngEncodingError(#[from] ImageError),
 
+    #[error("Failed to encode WebP: {0}")]
+    WebpEncodingError(ImageError),
+
     #[error("I/O Error: {0}")]
     IoError(#[from] io::Error),
 
@@ -36,93 +65,1242 @@ pub enum PlotError {
     NoDataError(String, String),
 }
 
-pub fn get_trend_graph(
-    rates: &Vec<ExchangeRateMap>,
+/// How the rates are charted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartMode {
+    Line,
+    Candles(CandlePeriod),
+}
+
+impl ChartMode {
+    pub const NAMES: &'static [&'static str] = &["line", "daily", "weekly", "monthly"];
+
+    pub fn from_name(name: &str) -> Option<ChartMode> {
+        match name.to_lowercase().as_str() {
+            "line" => Some(ChartMode::Line),
+            "daily" => Some(ChartMode::Candles(CandlePeriod::Day)),
+            "weekly" => Some(ChartMode::Candles(CandlePeriod::Week)),
+            "monthly" => Some(ChartMode::Candles(CandlePeriod::Month)),
+            _ => None,
+        }
+    }
+}
+
+/// File format of the charts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    /// Lossless, smaller than PNG
+    WebP,
+    /// Vector image, for embedding outside of Discord
+    Svg,
+}
+
+impl ImageFormat {
+    pub const NAMES: &'static [&'static str] = &["png", "webp", "svg"];
+
+    pub fn from_name(name: &str) -> Option<ImageFormat> {
+        match name.to_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "webp" => Some(ImageFormat::WebP),
+            "svg" => Some(ImageFormat::Svg),
+            _ => None,
+        }
+    }
+
+    /// Name of the file `stem` in this format, e.g. `graph.png`
+    pub fn file_name(&self, stem: &str) -> String {
+        let extension = match self {
+            ImageFormat::Png => "png",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Svg => "svg",
+        };
+        format!("{}.{}", stem, extension)
+    }
+}
+
+/// How a chart is drawn
+pub struct ChartOptions {
+    pub mode: ChartMode,
+    /// Drawn over line charts only
+    pub overlays: Overlays,
+    pub theme: Theme,
+    pub format: ImageFormat,
+    /// Language of the captions and labels
+    pub locale: Locale,
+}
+
+/// Chart of the rates from `from` to `to` since `since`.
+///
+/// Daily candles are built from the rates saved by every report, weekly and
+/// monthly candles from the daily `rates`.
+pub fn get_chart(
+    rates: &[ExchangeRateMap],
     from: &str,
     to: &str,
+    since: NaiveDate,
+    options: &ChartOptions,
 ) -> Result<Vec<u8>, PlotError> {
-    let width = 800;
-    let height = 400;
-
-    // Extract the dates and exchange rates for the `from` and `to` currencies
-    let mut data: Vec<(NaiveDate, f64)> = vec![];
-    for rate_map in rates {
-        if let Some(exchange_rate) = rate_map.get_val(from, to) {
-            data.push((rate_map.datetime.date_naive(), exchange_rate));
+    let ChartOptions {
+        mode,
+        theme,
+        format,
+        locale,
+        ..
+    } = options;
+    match mode {
+        ChartMode::Line => get_trend_graph(rates, from, to, since, options),
+        ChartMode::Candles(CandlePeriod::Day) => {
+            let candles = get_candles(
+                &get_saved_exchange_rates(from, to, since),
+                CandlePeriod::Day,
+            );
+            get_candlestick_graph(
+                &candles,
+                CandlePeriod::Day,
+                from,
+                to,
+                theme,
+                *locale,
+                *format,
+            )
+        }
+        ChartMode::Candles(period) => {
+            let series: Vec<_> = get_series(rates, from, to)
+                .into_iter()
+                .filter(|(date, _)| *date >= since)
+                .map(|(date, rate)| (date.and_time(NaiveTime::MIN), rate))
+                .collect();
+            get_candlestick_graph(
+                &get_candles(&series, *period),
+                *period,
+                from,
+                to,
+                theme,
+                *locale,
+                *format,
+            )
         }
     }
+}
+
+/// Lines, bands and markers drawn over the trend chart
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overlays {
+    /// Periods of the simple moving averages
+    pub sma: Vec<usize>,
+    /// Periods of the exponential moving averages
+    pub ema: Vec<usize>,
+    /// Period of the Bollinger bands
+    pub bollinger: Option<usize>,
+    /// Mark the lowest, highest and last rate with their value
+    pub markers: bool,
+}
 
-    if data.is_empty() {
-        return Err(PlotError::NoDataError(from.to_string(), to.to_string()));
+impl Overlays {
+    /// Overlays of a comma separated list such as `sma20,ema50,bollinger,markers`.
+    ///
+    /// Averages and bands listed without a period use [`DEFAULT_OVERLAY_PERIOD`].
+    pub fn parse(list: &str) -> Result<Overlays, String> {
+        let mut overlays = Overlays::default();
+        for item in list
+            .split(',')
+            .map(|item| item.trim().to_lowercase())
+            .filter(|item| !item.is_empty())
+        {
+            let name = item.trim_end_matches(|c: char| c.is_ascii_digit());
+            let period = match &item[name.len()..] {
+                "" => DEFAULT_OVERLAY_PERIOD,
+                digits => digits
+                    .parse()
+                    .ok()
+                    .filter(|period| *period > 1)
+                    .ok_or(format!("invalid period in `{}`", item))?,
+            };
+            match name {
+                "sma" => overlays.sma.push(period),
+                "ema" => overlays.ema.push(period),
+                "bollinger" => overlays.bollinger = Some(period),
+                "markers" => overlays.markers = true,
+                _ => {
+                    return Err(format!(
+                        "unknown overlay `{}`, expected sma, ema, bollinger or markers",
+                        item
+                    ))
+                }
+            }
+        }
+        Ok(overlays)
     }
+}
+
+/// Decimals of the rates on the y-axis.
+///
+/// About five significant digits, e.g. 2 for JPY pairs around 150 and 4 for
+/// pairs around 1, and more when the chart spans less than the last digit.
+fn get_precision(min: f64, max: f64) -> usize {
+    let magnitude = min.abs().max(max.abs()).log10().floor();
+    let digits = match magnitude.is_finite() {
+        true => 4 - magnitude as i32,
+        false => 4,
+    };
+    // 10 labels on the axis have to differ
+    let step = (max - min) / 10.0;
+    let step_digits = match step > 0.0 {
+        true => (-step.log10()).ceil() as i32,
+        false => 0,
+    };
+    digits.max(step_digits).clamp(0, 8) as usize
+}
+
+/// Range of the y-axis, with some room around the rates
+fn get_y_range(min: f64, max: f64) -> Range<f64> {
+    // Rates saved during a day are often all the same
+    let margin = ((max - min) * 0.05).max(max.abs() * 0.001);
+    min - margin..max + margin
+}
+
+/// Pixels taken by the labels of the y-axis
+fn get_y_label_area_size(max: f64, precision: usize) -> u32 {
+    format!("{:.*}", precision, max).len() as u32 * 7 + 12
+}
+
+/// Chart of values over dates, as drawn by most charts
+type DateChart<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedDate<NaiveDate>, RangedCoordf64>>;
+
+/// Chart of `dates` and `values` titled `caption`, with room for `y_label_size`
+/// pixels of labels on the y-axis
+fn build_chart<'a, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    theme: &Theme,
+    caption: &str,
+    y_label_size: u32,
+    dates: Range<NaiveDate>,
+    values: Range<f64>,
+) -> Result<DateChart<'a, DB>, PlotError> {
+    ChartBuilder::on(root)
+        .caption(caption, theme.text(20))
+        .margin(theme.px(20))
+        .x_label_area_size(theme.px(35))
+        .y_label_area_size(theme.px(y_label_size))
+        .build_cartesian_2d(dates, values)
+        .map_err(|e| PlotError::DrawBorderError(format!("{:?}", e)))
+}
+
+/// Draw the grid and axes of `chart` in `theme`, labelling values with `format`
+fn style_mesh<DB: DrawingBackend>(
+    chart: &mut DateChart<'_, DB>,
+    theme: &Theme,
+    catalog: &Catalog,
+    y_desc: &str,
+    format: &dyn Fn(&f64) -> String,
+) -> Result<(), PlotError> {
+    chart
+        .configure_mesh()
+        .x_labels(10) // Adjust label count dynamically based on date range
+        .x_label_formatter(&|date| date.format("%Y-%m-%d").to_string())
+        .y_labels(10) // Add more granularity to the y-axis
+        .y_label_formatter(format)
+        .y_desc(y_desc)
+        .x_desc(catalog.chart_date)
+        .axis_desc_style(theme.text(15))
+        .label_style(theme.text(12))
+        .axis_style(theme.foreground)
+        .bold_line_style(theme.foreground.mix(0.2))
+        .light_line_style(theme.foreground.mix(0.1))
+        .draw()
+        .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))
+}
+
+pub fn get_trend_graph(
+    rates: &[ExchangeRateMap],
+    from: &str,
+    to: &str,
+    since: NaiveDate,
+    options: &ChartOptions,
+) -> Result<Vec<u8>, PlotError> {
+    let chart = TrendChart {
+        rates,
+        from,
+        to,
+        since,
+        overlays: &options.overlays,
+    };
+    render(&chart, &options.theme, options.locale, options.format)
+}
 
-    // Sort data by date
-    data.sort_by_key(|(date, _)| *date);
+/// Rates from `from` to `to` since `since` as a line, with `overlays`
+struct TrendChart<'a> {
+    rates: &'a [ExchangeRateMap],
+    from: &'a str,
+    to: &'a str,
+    since: NaiveDate,
+    overlays: &'a Overlays,
+}
+
+impl Chart for TrendChart<'_> {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        theme: &Theme,
+        locale: Locale,
+    ) -> Result<(), PlotError> {
+        let catalog = locale.catalog();
+        let TrendChart {
+            rates,
+            from,
+            to,
+            since,
+            overlays,
+        } = *self;
 
-    // Prepare the buffer for the graph
-    let mut buffer = vec![0; (width * height * 3) as usize];
+        // Averages are computed over all rates, so that they cover the whole chart
+        let series = get_series(rates, from, to);
+        let values: Vec<f64> = series.iter().map(|(_, rate)| *rate).collect();
+        let shown = |line: Vec<Option<f64>>| -> Vec<(NaiveDate, f64)> {
+            series
+                .iter()
+                .zip(line)
+                .filter_map(|((date, _), value)| Some((*date, value?)))
+                .filter(|(date, _)| *date >= since)
+                .collect()
+        };
+
+        let data = shown(values.iter().map(|rate| Some(*rate)).collect());
+        if data.is_empty() {
+            return Err(PlotError::NoDataError(from.to_string(), to.to_string()));
+        }
+
+        let mut lines: Vec<(String, Vec<(NaiveDate, f64)>)> = vec![];
+        for period in &overlays.sma {
+            lines.push((
+                format!("SMA ({})", period),
+                shown(get_sma(&values, *period)),
+            ));
+        }
+        for period in &overlays.ema {
+            lines.push((
+                format!("EMA ({})", period),
+                shown(get_ema(&values, *period)),
+            ));
+        }
+        let bands: Vec<(NaiveDate, f64, f64)> = match overlays.bollinger {
+            Some(period) => series
+                .iter()
+                .zip(get_bollinger_bands(&values, period, BOLLINGER_WIDTH))
+                .filter_map(|((date, _), bands)| {
+                    bands.map(|(lower, _, upper)| (*date, lower, upper))
+                })
+                .filter(|(date, _, _)| *date >= since)
+                .collect(),
+            None => vec![],
+        };
 
-    {
-        // Create a drawing area using a bitmap backend
-        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
-        root.fill(&WHITE)
+        root.fill(&theme.background)
             .map_err(|e| PlotError::FillError(format!("{:?}", e)))?;
 
         // Dynamically determine the range of dates and rates
-        let max_rate = data.iter().map(|(_, rate)| *rate).fold(f64::MIN, f64::max);
-        let min_rate = data.iter().map(|(_, rate)| *rate).fold(f64::MAX, f64::min);
+        let shown_rates = data
+            .iter()
+            .chain(lines.iter().flat_map(|(_, line)| line))
+            .map(|(_, rate)| *rate)
+            .chain(bands.iter().flat_map(|(_, lower, upper)| [*lower, *upper]));
+        let (min_rate, max_rate) = shown_rates.fold((f64::MAX, f64::MIN), |(min, max), rate| {
+            (min.min(rate), max.max(rate))
+        });
+        let precision = get_precision(min_rate, max_rate);
         let date_range = data.first().unwrap().0..data.last().unwrap().0;
 
-        let mut chart = ChartBuilder::on(&root)
-            .caption(
-                format!("Exchange Rate Trend: {} to {}", from, to),
-                ("sans-serif", 20),
-            )
-            .margin(20)
-            .x_label_area_size(35)
-            .y_label_area_size(40)
-            .build_cartesian_2d(date_range, min_rate..max_rate)
-            .map_err(|e| PlotError::DrawBorderError(format!("{:?}", e)))?;
+        // Length and half height of the legend symbols
+        let legend = theme.offset((20, 5));
+
+        let mut chart = build_chart(
+            root,
+            theme,
+            &fill(catalog.chart_trend, &[("FROM", from), ("TO", to)]),
+            get_y_label_area_size(max_rate, precision),
+            date_range,
+            get_y_range(min_rate, max_rate),
+        )?;
+        style_mesh(&mut chart, theme, catalog, catalog.chart_rate, &|rate| {
+            format!("{:.*}", precision, rate)
+        })?;
+
+        if let Some(period) = overlays.bollinger.filter(|_| !bands.is_empty()) {
+            // Upper band forwards, lower band backwards
+            let area: Vec<(NaiveDate, f64)> = bands
+                .iter()
+                .map(|(date, _, upper)| (*date, *upper))
+                .chain(bands.iter().rev().map(|(date, lower, _)| (*date, *lower)))
+                .collect();
+            chart
+                .draw_series(std::iter::once(Polygon::new(
+                    area,
+                    theme.foreground.mix(0.08).filled(),
+                )))
+                .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?
+                .label(format!("Bollinger ({}, {}σ)", period, BOLLINGER_WIDTH))
+                .legend(|(x, y)| {
+                    Rectangle::new(
+                        [(x, y - legend.1), (x + legend.0, y + legend.1)],
+                        theme.foreground.mix(0.15).filled(),
+                    )
+                });
+        }
+
+        chart
+            .draw_series(LineSeries::new(
+                data.iter().map(|(date, rate)| (*date, *rate)),
+                theme.line.stroke_width(theme.px(1)),
+            ))
+            .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?
+            .label(fill(catalog.chart_pair, &[("FROM", from), ("TO", to)]))
+            .legend(move |(x, y)| {
+                PathElement::new(
+                    vec![(x, y), (x + legend.0, y)],
+                    theme.line.stroke_width(theme.px(1)),
+                )
+            });
+
+        for (i, (label, line)) in lines.into_iter().enumerate() {
+            // The first color of the palette is left to the rate
+            let color = theme.color(i + 1).stroke_width(theme.px(1));
+            chart
+                .draw_series(LineSeries::new(line, color))
+                .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?
+                .label(label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + legend.0, y)], color));
+        }
 
+        // On the left, where the last rate is not, away from the first rate
+        let legend_position = match data[0].1 > (min_rate + max_rate) / 2.0 {
+            true => SeriesLabelPosition::LowerLeft,
+            false => SeriesLabelPosition::UpperLeft,
+        };
         chart
-            .configure_mesh()
-            .x_labels(10) // Adjust label count dynamically based on date range
-            .x_label_formatter(&|date| date.format("%Y-%m-%d").to_string())
-            .y_labels(10) // Add more granularity to the y-axis
-            .y_label_formatter(&|rate| format!("{:.2}", rate)) // Format the exchange rates
-            .y_desc("Exchange Rate")
-            .x_desc("Date")
-            .axis_desc_style(("sans-serif", 15))
+            .configure_series_labels()
+            .position(legend_position)
+            .label_font(theme.text(12))
+            .background_style(theme.background)
+            .border_style(theme.foreground)
             .draw()
             .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
 
+        // After the legend, which could hide them
+        if overlays.markers {
+            let min = data.iter().min_by(|a, b| a.1.total_cmp(&b.1));
+            let max = data.iter().max_by(|a, b| a.1.total_cmp(&b.1));
+            let last = data.last();
+            let middle = data[data.len() / 2].0;
+            let markers = [
+                (min, catalog.chart_min, theme.fall, 2),
+                (max, catalog.chart_max, theme.rise, -16),
+            ];
+            chart
+                .draw_series(
+                    markers
+                        .into_iter()
+                        // The last rate already shows a lowest or highest last rate
+                        .filter(|(point, _, _, _)| *point != last)
+                        .chain([(last, catalog.chart_last, theme.line, -16)])
+                        .filter_map(|(point, name, color, y)| {
+                            let (date, rate) = point?;
+                            let label = format!("{} {:.*}", name, precision, rate);
+                            // Left of the points in the right half, where they would be cut
+                            let x = match *date < middle {
+                                true => 6,
+                                false => -(label.chars().count() as i32 * 7 + 6),
+                            };
+                            Some(
+                                EmptyElement::at((*date, *rate))
+                                    + Circle::new((0, 0), theme.px(4), color.filled())
+                                    + Text::new(label, theme.offset((x, y)), theme.text(13)),
+                            )
+                        }),
+                )
+                .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Percent change of every value since the first one
+fn rebase(series: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, f64)> {
+    match series.first() {
+        Some((_, first)) if *first != 0.0 => series
+            .iter()
+            .map(|(date, value)| (*date, (value / first - 1.0) * 100.0))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Chart of how the `quotes` moved against `base` since `since`, every
+/// series rebased to 0% on its first day.
+///
+/// Quotes without rates are left out, there is an error when none has any.
+pub fn get_comparison_graph(
+    rates: &[ExchangeRateMap],
+    base: &str,
+    quotes: &[String],
+    since: NaiveDate,
+    theme: &Theme,
+    locale: Locale,
+    format: ImageFormat,
+) -> Result<Vec<u8>, PlotError> {
+    let chart = ComparisonChart {
+        rates,
+        base,
+        quotes,
+        since,
+    };
+    render(&chart, theme, locale, format)
+}
+
+/// Percent change of the `quotes` against `base` since `since`
+struct ComparisonChart<'a> {
+    rates: &'a [ExchangeRateMap],
+    base: &'a str,
+    quotes: &'a [String],
+    since: NaiveDate,
+}
+
+impl Chart for ComparisonChart<'_> {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        theme: &Theme,
+        locale: Locale,
+    ) -> Result<(), PlotError> {
+        let catalog = locale.catalog();
+        let ComparisonChart {
+            rates,
+            base,
+            quotes,
+            since,
+        } = *self;
+
+        // Value of the quote in the base, rising when the quote gains
+        let lines: Vec<(&String, Vec<(NaiveDate, f64)>)> = quotes
+            .iter()
+            .map(|quote| {
+                let series: Vec<(NaiveDate, f64)> = get_series(rates, quote, base)
+                    .into_iter()
+                    .filter(|(date, _)| *date >= since)
+                    .collect();
+                (quote, rebase(&series))
+            })
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+
+        if lines.is_empty() {
+            return Err(PlotError::NoDataError(base.to_string(), quotes.join(", ")));
+        }
+
+        root.fill(&theme.background)
+            .map_err(|e| PlotError::FillError(format!("{:?}", e)))?;
+
+        let points = || lines.iter().flat_map(|(_, line)| line);
+        let (min_change, max_change) = points().fold((0.0_f64, 0.0_f64), |(min, max), (_, v)| {
+            (min.min(*v), max.max(*v))
+        });
+        let first_date = points().map(|(date, _)| *date).min().unwrap_or(since);
+        let last_date = points().map(|(date, _)| *date).max().unwrap_or(since);
+        // Only the range matters, percent changes have no usual precision
+        let step = (max_change - min_change) / 10.0;
+        let precision = match step > 0.0 {
+            true => ((-step.log10()).ceil() as i32).clamp(1, 4) as usize,
+            false => 1,
+        };
+
+        // Length and half height of the legend symbols
+        let legend = theme.offset((20, 5));
+
+        let mut chart = build_chart(
+            root,
+            theme,
+            &fill(
+                catalog.chart_comparison,
+                &[("BASE", base), ("DATE", &locale.format_date(first_date))],
+            ),
+            format!("{:+.*}%", precision, min_change.abs().max(max_change.abs())).len() as u32 * 7
+                + 12,
+            first_date..last_date,
+            get_y_range(min_change, max_change),
+        )?;
+        style_mesh(
+            &mut chart,
+            theme,
+            catalog,
+            catalog.chart_change,
+            &|change| format!("{:+.*}%", precision, change),
+        )?;
+
         chart
             .draw_series(LineSeries::new(
-                data.iter().map(|(date, rate)| (*date, *rate)),
-                &BLUE,
+                [(first_date, 0.0), (last_date, 0.0)],
+                theme.foreground.mix(0.4).stroke_width(theme.px(1)),
+            ))
+            .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+
+        for (i, (quote, line)) in lines.iter().enumerate() {
+            let color = theme.color(i).stroke_width(theme.px(2));
+            chart
+                .draw_series(LineSeries::new(line.iter().copied(), color))
+                .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?
+                .label(format!(
+                    "{} ({:+.*}%)",
+                    quote,
+                    precision,
+                    line.last().map_or(0.0, |(_, change)| *change)
+                ))
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + legend.0, y)], color));
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .label_font(theme.text(12))
+            .background_style(theme.background)
+            .border_style(theme.foreground)
+            .draw()
+            .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+
+        Ok(())
+    }
+}
+
+pub fn get_candlestick_graph(
+    candles: &[Candle],
+    period: CandlePeriod,
+    from: &str,
+    to: &str,
+    theme: &Theme,
+    locale: Locale,
+    format: ImageFormat,
+) -> Result<Vec<u8>, PlotError> {
+    let chart = CandlestickChart {
+        candles,
+        period,
+        from,
+        to,
+    };
+    render(&chart, theme, locale, format)
+}
+
+/// Candles of `period` from `from` to `to`
+struct CandlestickChart<'a> {
+    candles: &'a [Candle],
+    period: CandlePeriod,
+    from: &'a str,
+    to: &'a str,
+}
+
+impl Chart for CandlestickChart<'_> {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        theme: &Theme,
+        locale: Locale,
+    ) -> Result<(), PlotError> {
+        let catalog = locale.catalog();
+        let CandlestickChart {
+            candles,
+            period,
+            from,
+            to,
+        } = *self;
+
+        let (Some(first), Some(last)) = (candles.first(), candles.last()) else {
+            return Err(PlotError::NoDataError(from.to_string(), to.to_string()));
+        };
+
+        root.fill(&theme.background)
+            .map_err(|e| PlotError::FillError(format!("{:?}", e)))?;
+
+        let max_rate = candles.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+        let min_rate = candles.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+        let precision = get_precision(min_rate, max_rate);
+        // Half a period of room on both sides, so that no candle is cut
+        let date_range = first.start - Duration::days(period.get_days(first.start) / 2)
+            ..last.start + Duration::days(period.get_days(last.start));
+        let days = (date_range.end - date_range.start).num_days().max(1);
+
+        let caption = fill(
+            match period {
+                CandlePeriod::Day => catalog.chart_candles_day,
+                CandlePeriod::Week => catalog.chart_candles_week,
+                CandlePeriod::Month => catalog.chart_candles_month,
+            },
+            &[("FROM", from), ("TO", to)],
+        );
+        let mut chart = build_chart(
+            root,
+            theme,
+            &caption,
+            get_y_label_area_size(max_rate, precision),
+            date_range,
+            get_y_range(min_rate, max_rate),
+        )?;
+        style_mesh(&mut chart, theme, catalog, catalog.chart_rate, &|rate| {
+            format!("{:.*}", precision, rate)
+        })?;
+
+        // Candles take 70% of the width of their period
+        let plot_width = chart.plotting_area().dim_in_pixel().0 as f64;
+        let candle_width = |candle: &Candle| {
+            let period_width = plot_width * period.get_days(candle.start) as f64 / days as f64;
+            (period_width * 0.7).clamp(1.0, theme.px(40) as f64) as u32
+        };
+
+        chart
+            .draw_series(candles.iter().map(|candle| {
+                // Centered in their period
+                let center = candle.start + Duration::days(period.get_days(candle.start) / 2);
+                CandleStick::new(
+                    center,
+                    candle.open,
+                    candle.high,
+                    candle.low,
+                    candle.close,
+                    theme.rise.filled(),
+                    theme.fall.filled(),
+                    candle_width(candle),
+                )
+            }))
+            .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+
+        Ok(())
+    }
+}
+
+/// Chart of the rates from `from` to `to` in `data` followed by `forecast` of `model`
+pub fn get_forecast_graph(
+    data: &[(NaiveDate, f64)],
+    forecast: &[ForecastPoint],
+    model: ForecastModel,
+    (from, to): (&str, &str),
+    theme: &Theme,
+    locale: Locale,
+    format: ImageFormat,
+) -> Result<Vec<u8>, PlotError> {
+    let chart = ForecastChart {
+        data,
+        forecast,
+        model,
+        from,
+        to,
+    };
+    render(&chart, theme, locale, format)
+}
+
+/// Rates as a line, the forecast as a dashed line in its shaded interval
+struct ForecastChart<'a> {
+    data: &'a [(NaiveDate, f64)],
+    forecast: &'a [ForecastPoint],
+    model: ForecastModel,
+    from: &'a str,
+    to: &'a str,
+}
+
+impl Chart for ForecastChart<'_> {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        theme: &Theme,
+        locale: Locale,
+    ) -> Result<(), PlotError> {
+        let catalog = locale.catalog();
+        let ForecastChart {
+            data,
+            forecast,
+            model,
+            from,
+            to,
+        } = *self;
+
+        let (Some(first), Some(last)) = (data.first(), data.last()) else {
+            return Err(PlotError::NoDataError(from.to_string(), to.to_string()));
+        };
+
+        root.fill(&theme.background)
+            .map_err(|e| PlotError::FillError(format!("{:?}", e)))?;
+
+        let (min_rate, max_rate) = data
+            .iter()
+            .map(|(_, rate)| *rate)
+            .chain(forecast.iter().flat_map(|point| [point.low, point.high]))
+            .fold((f64::MAX, f64::MIN), |(min, max), rate| {
+                (min.min(rate), max.max(rate))
+            });
+        let precision = get_precision(min_rate, max_rate);
+        let end = forecast.last().map_or(last.0, |point| point.date);
+
+        // Length and half height of the legend symbols
+        let legend = theme.offset((20, 5));
+
+        let mut chart = build_chart(
+            root,
+            theme,
+            &fill(
+                catalog.chart_forecast,
+                &[("FROM", from), ("TO", to), ("MODEL", model.label())],
+            ),
+            get_y_label_area_size(max_rate, precision),
+            first.0..end,
+            get_y_range(min_rate, max_rate),
+        )?;
+        style_mesh(&mut chart, theme, catalog, catalog.chart_rate, &|rate| {
+            format!("{:.*}", precision, rate)
+        })?;
+
+        // Both start at the last rate, so that they join the line of the rates
+        let interval: Vec<(NaiveDate, f64)> = std::iter::once(*last)
+            .chain(forecast.iter().map(|point| (point.date, point.high)))
+            .chain(forecast.iter().rev().map(|point| (point.date, point.low)))
+            .collect();
+        let line: Vec<(NaiveDate, f64)> = std::iter::once(*last)
+            .chain(forecast.iter().map(|point| (point.date, point.value)))
+            .collect();
+
+        chart
+            .draw_series(std::iter::once(Polygon::new(
+                interval,
+                theme.line.mix(0.15).filled(),
+            )))
+            .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?
+            .label(catalog.chart_interval)
+            .legend(|(x, y)| {
+                Rectangle::new(
+                    [(x, y - legend.1), (x + legend.0, y + legend.1)],
+                    theme.line.mix(0.15).filled(),
+                )
+            });
+
+        chart
+            .draw_series(LineSeries::new(
+                data.iter().copied(),
+                theme.line.stroke_width(theme.px(1)),
             ))
             .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?
-            .label(format!("{} to {}", from, to))
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
+            .label(fill(catalog.chart_pair, &[("FROM", from), ("TO", to)]))
+            .legend(move |(x, y)| {
+                PathElement::new(
+                    vec![(x, y), (x + legend.0, y)],
+                    theme.line.stroke_width(theme.px(1)),
+                )
+            });
 
+        let dashed = theme.color(1).stroke_width(theme.px(2));
+        chart
+            .draw_series(DashedLineSeries::new(
+                line,
+                theme.px(6),
+                theme.px(4),
+                dashed,
+            ))
+            .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?
+            .label(catalog.chart_forecast_line)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + legend.0 / 2, y)], dashed));
+
+        // On the left, away from the first rate
+        let legend_position = match first.1 > (min_rate + max_rate) / 2.0 {
+            true => SeriesLabelPosition::LowerLeft,
+            false => SeriesLabelPosition::UpperLeft,
+        };
         chart
             .configure_series_labels()
-            .background_style(&WHITE)
-            .border_style(&BLACK)
+            .position(legend_position)
+            .label_font(theme.text(12))
+            .background_style(theme.background)
+            .border_style(theme.foreground)
             .draw()
             .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+
+        let (width, height) = root.dim_in_pixel();
+        root.draw(&Text::new(
+            catalog.chart_disclaimer,
+            (
+                width as i32 - theme.px(8) as i32,
+                height as i32 - theme.px(6) as i32,
+            ),
+            theme.text(12).pos(Pos::new(HPos::Right, VPos::Bottom)),
+        ))
+        .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+
+        Ok(())
     }
+}
+
+/// Rate of a cell of the cross rate matrix
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrossRate {
+    pub rate: f64,
+    /// Percent change since the previous rates
+    pub change: Option<f64>,
+}
 
-    // Convert the raw buffer into a PNG image buffer
+/// Rate from the currency of every row to the currency of every column, `None`
+/// where `rates` lacks one of them
+pub fn get_cross_rates(
+    rates: &ExchangeRateMap,
+    previous: Option<&ExchangeRateMap>,
+    currencies: &[String],
+) -> Vec<Vec<Option<CrossRate>>> {
+    currencies
+        .iter()
+        .map(|from| {
+            currencies
+                .iter()
+                .map(|to| {
+                    let rate = rates.get_val(from, to)?;
+                    let change = previous
+                        .and_then(|previous| previous.get_val(from, to))
+                        .filter(|previous| *previous != 0.0)
+                        .map(|previous| (rate / previous - 1.0) * 100.0);
+                    Some(CrossRate { rate, change })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Rate with about 5 significant digits
+fn format_rate(rate: f64) -> String {
+    let digits = match rate > 0.0 {
+        true => (4 - rate.log10().floor() as i32).clamp(0, 8) as usize,
+        false => 4,
+    };
+    format!("{:.*}", digits, rate)
+}
+
+/// Color `t` of the way from `from` to `to`
+fn blend(from: RGBColor, to: RGBColor, t: f64) -> RGBColor {
+    let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    RGBColor(
+        channel(from.0, to.0),
+        channel(from.1, to.1),
+        channel(from.2, to.2),
+    )
+}
+
+/// Table of the cross rates of `currencies` on `rates`, every cell colored by its
+/// change since `previous`.
+///
+/// The image grows with the number of currencies instead of taking the size of the theme.
+pub fn get_matrix_graph(
+    rates: &ExchangeRateMap,
+    previous: Option<&ExchangeRateMap>,
+    currencies: &[String],
+    theme: &Theme,
+    locale: Locale,
+    format: ImageFormat,
+) -> Result<Vec<u8>, PlotError> {
+    let cells = currencies.len() as u32 + 1;
+    let theme = Theme {
+        width: MATRIX_CELL_SIZE.0 * cells + 20,
+        height: MATRIX_CELL_SIZE.1 * cells + 60,
+        ..theme.clone()
+    };
+    let chart = MatrixChart {
+        rates,
+        previous,
+        currencies,
+    };
+    render(&chart, &theme, locale, format)
+}
+
+/// Cross rates of `currencies` on `rates`, colored by their change since `previous`
+struct MatrixChart<'a> {
+    rates: &'a ExchangeRateMap,
+    previous: Option<&'a ExchangeRateMap>,
+    currencies: &'a [String],
+}
+
+impl Chart for MatrixChart<'_> {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        theme: &Theme,
+        locale: Locale,
+    ) -> Result<(), PlotError> {
+        let catalog = locale.catalog();
+        let MatrixChart {
+            rates,
+            previous,
+            currencies,
+        } = *self;
+
+        let matrix = get_cross_rates(rates, previous, currencies);
+        if matrix.iter().flatten().all(|cell| cell.is_none()) {
+            return Err(PlotError::NoDataError(
+                rates.base.clone(),
+                currencies.join(", "),
+            ));
+        }
+        // Changes are colored relative to the largest one, small days stay pale
+        let largest_change = matrix
+            .iter()
+            .flatten()
+            .filter_map(|cell| cell.and_then(|cell| cell.change))
+            .fold(MATRIX_MIN_COLORED_CHANGE, |max, change| {
+                max.max(change.abs())
+            });
+
+        root.fill(&theme.background)
+            .map_err(|e| PlotError::FillError(format!("{:?}", e)))?;
+        let date = locale.format_date(rates.get_date());
+        let title = match previous {
+            Some(previous) => fill(
+                catalog.chart_cross_rates_change,
+                &[
+                    ("DATE", &date),
+                    ("PREVIOUS", &locale.format_date(previous.get_date())),
+                ],
+            ),
+            None => fill(catalog.chart_cross_rates, &[("DATE", &date)]),
+        };
+        let margin = theme.px(10);
+        let table = root
+            .margin(margin, margin, margin, margin)
+            .titled(&title, theme.text(20))
+            .map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+
+        let size = currencies.len() + 1;
+        let centered = |size: u32| theme.text(size).pos(Pos::new(HPos::Center, VPos::Center));
+        for (i, cell) in table.split_evenly((size, size)).iter().enumerate() {
+            let (row, column) = (i / size, i % size);
+            let (width, height) = cell.dim_in_pixel();
+            let center = (width as i32 / 2, height as i32 / 2);
+            // Rows are converted from, columns to
+            let (background, lines) = match (row, column) {
+                (0, 0) => (theme.background, vec![]),
+                (0, _) => (theme.background, vec![currencies[column - 1].clone()]),
+                (_, 0) => (theme.background, vec![currencies[row - 1].clone()]),
+                _ if row == column && matrix[row - 1][column - 1].is_some() => (
+                    blend(theme.background, theme.foreground, 0.06),
+                    vec!["1".to_string()],
+                ),
+                _ => match matrix[row - 1][column - 1] {
+                    Some(CrossRate { rate, change }) => {
+                        let background = match change {
+                            Some(change) => blend(
+                                theme.background,
+                                match change >= 0.0 {
+                                    true => theme.rise,
+                                    false => theme.fall,
+                                },
+                                (change.abs() / largest_change).min(1.0) * 0.7,
+                            ),
+                            None => theme.background,
+                        };
+                        let mut lines = vec![format_rate(rate)];
+                        lines.extend(change.map(|change| format!("{:+.2}%", change)));
+                        (background, lines)
+                    }
+                    None => (theme.background, vec!["–".to_string()]),
+                },
+            };
+
+            cell.fill(&background)
+                .map_err(|e| PlotError::FillError(format!("{:?}", e)))?;
+            cell.draw(&Rectangle::new(
+                [(0, 0), (width as i32 - 1, height as i32 - 1)],
+                theme.foreground.mix(0.2),
+            ))
+            .map_err(|e| PlotError::DrawBorderError(format!("{:?}", e)))?;
+
+            let result = match lines.as_slice() {
+                [text] if row == 0 || column == 0 => cell.draw_text(text, &centered(15), center),
+                [text] => cell.draw_text(text, &centered(14), center),
+                [rate, change] => cell
+                    .draw_text(
+                        rate,
+                        &centered(14),
+                        (center.0, center.1 - theme.px(8) as i32),
+                    )
+                    .and_then(|_| {
+                        cell.draw_text(
+                            change,
+                            &centered(11),
+                            (center.0, center.1 + theme.px(10) as i32),
+                        )
+                    }),
+                _ => Ok(()),
+            };
+            result.map_err(|e| PlotError::DrawTextError(format!("{:?}", e)))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Chart drawn the same way by every backend
+trait Chart {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        theme: &Theme,
+        locale: Locale,
+    ) -> Result<(), PlotError>;
+}
+
+/// Image of `chart` drawn in `theme` and labelled in `locale`, encoded as `format`
+fn render(
+    chart: &impl Chart,
+    theme: &Theme,
+    locale: Locale,
+    format: ImageFormat,
+) -> Result<Vec<u8>, PlotError> {
+    let (width, height) = theme.size();
+    match format {
+        ImageFormat::Png | ImageFormat::WebP => {
+            let mut buffer = vec![0; (width * height * 3) as usize];
+            {
+                let root =
+                    BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+                chart.draw(&root, theme, locale)?;
+                root.present()
+                    .map_err(|e| PlotError::PresentError(format!("{:?}", e)))?;
+            }
+            encode_bitmap(buffer, width, height, format)
+        }
+        ImageFormat::Svg => {
+            let mut svg = String::new();
+            {
+                let root = SVGBackend::with_string(&mut svg, (width, height)).into_drawing_area();
+                chart.draw(&root, theme, locale)?;
+                root.present()
+                    .map_err(|e| PlotError::PresentError(format!("{:?}", e)))?;
+            }
+            Ok(svg.into_bytes())
+        }
+    }
+}
+
+/// PNG or WebP of a raw RGB buffer
+fn encode_bitmap(
+    buffer: Vec<u8>,
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+) -> Result<Vec<u8>, PlotError> {
+    // Convert the raw buffer into an image buffer
     let img = RgbImage::from_raw(width, height, buffer).ok_or(PlotError::BufferConversionError)?;
 
-    let mut png_data = Vec::new();
-    {
-        let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
-        encoder
+    let mut data = Vec::new();
+    match format {
+        ImageFormat::WebP => image::codecs::webp::WebPEncoder::new_lossless(&mut data)
+            .write_image(&img, width, height, image::ExtendedColorType::Rgb8)
+            .map_err(PlotError::WebpEncodingError)?,
+        _ => image::codecs::png::PngEncoder::new(&mut data)
             .write_image(&img, width, height, image::ExtendedColorType::Rgb8)
-            .map_err(PlotError::PngEncodingError)?;
+            .map_err(PlotError::PngEncodingError)?,
     }
 
-    Ok(png_data)
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::{get_daily_rates, get_start};
+
+    #[test]
+    fn test_get_precision() {
+        // USD to JPY, EUR to USD and JPY to EUR
+        assert_eq!(get_precision(148.2, 152.9), 2);
+        assert_eq!(get_precision(1.0712, 1.0931), 4);
+        assert_eq!(get_precision(0.00612, 0.00634), 7);
+        // Moves smaller than the usual precision are still visible
+        assert_eq!(get_precision(1.08001, 1.08009), 6);
+        assert_eq!(get_precision(1.08, 1.08), 4);
+    }
+
+    #[test]
+    fn test_rebase() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        assert_eq!(
+            rebase(&[(date(1), 2.0), (date(2), 2.5), (date(3), 1.5)]),
+            [(date(1), 0.0), (date(2), 25.0), (date(3), -25.0)]
+        );
+        assert!(rebase(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_overlays() {
+        assert_eq!(
+            Overlays::parse("sma20, EMA50,bollinger,markers,sma"),
+            Ok(Overlays {
+                sma: vec![20, DEFAULT_OVERLAY_PERIOD],
+                ema: vec![50],
+                bollinger: Some(DEFAULT_OVERLAY_PERIOD),
+                markers: true,
+            })
+        );
+        assert_eq!(Overlays::parse(""), Ok(Overlays::default()));
+        assert!(Overlays::parse("rsi14").is_err());
+        assert!(Overlays::parse("sma1").is_err());
+    }
+
+    #[test]
+    fn test_get_cross_rates() {
+        let rates = |eur: f64, gbp: f64| ExchangeRateMap {
+            datetime: chrono::Utc::now(),
+            base: "USD".to_string(),
+            map: [("EUR".to_string(), eur), ("GBP".to_string(), gbp)].into(),
+        };
+        let currencies: Vec<String> = ["USD", "EUR", "GBP", "XXX"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let matrix = get_cross_rates(&rates(0.9, 0.75), Some(&rates(0.9, 0.8)), &currencies);
+
+        assert_eq!(
+            matrix[0][1],
+            Some(CrossRate {
+                rate: 0.9,
+                change: Some(0.0),
+            })
+        );
+        // One EUR is 0.75 / 0.9 GBP, down from 0.8 / 0.9
+        let eur_gbp = matrix[1][2].unwrap();
+        assert!((eur_gbp.rate - 0.75 / 0.9).abs() < 1e-12);
+        assert!((eur_gbp.change.unwrap() + 6.25).abs() < 1e-9);
+        assert_eq!(matrix[2][2].map(|cell| cell.rate), Some(1.0));
+        assert!(matrix[3].iter().all(Option::is_none));
+        assert_eq!(matrix[0][3], None);
+
+        assert_eq!(
+            get_cross_rates(&rates(0.9, 0.75), None, &currencies)[0][1]
+                .unwrap()
+                .change,
+            None
+        );
+        assert_eq!(format_rate(151.234567), "151.23");
+        assert_eq!(format_rate(0.0066123), "0.0066123");
+    }
+
+    #[test]
+    fn test_render() {
+        let rates = get_daily_rates(&[("USD", &[1.08, 1.09, 1.07, 1.1, 1.12, 1.11])]);
+        let overlays = Overlays::parse("sma3,markers").unwrap();
+        let chart = TrendChart {
+            rates: &rates,
+            from: "EUR",
+            to: "USD",
+            since: get_start(),
+            overlays: &overlays,
+        };
+        let theme = Theme::light();
+
+        let png = render(&chart, &theme, Locale::En, ImageFormat::Png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let webp = render(&chart, &theme, Locale::En, ImageFormat::WebP).unwrap();
+        assert_eq!(&webp[..4], b"RIFF");
+        assert_eq!(&webp[8..12], b"WEBP");
+        let svg = render(&chart, &theme, Locale::En, ImageFormat::Svg).unwrap();
+        assert!(String::from_utf8(svg).unwrap().starts_with("<svg"));
+    }
 }
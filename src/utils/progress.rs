@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use serenity::all::{CommandInteraction, Context, EditInteractionResponse};
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::i18n::Locale;
+use crate::llm::guardrails::{strip_reasoning, Guardrails};
+
+/// Minimum time between two progressive edits of the deferred response.
+/// Discord allows roughly five edits per five seconds on an interaction.
+const STREAM_EDIT_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// Discord rejects message contents longer than 2000 characters.
+const MAX_PREVIEW_CHARS: usize = 1900;
+
+/// Keep editing the deferred response with the latest streamed text until the
+/// sender is dropped.
+///
+/// Previews go through the blocked terms and mass mentions checks. Once one
+/// fails, a placeholder is shown instead and previewing stops, the final
+/// response replaces it after the full guardrails.
+pub async fn stream_to_response(
+    ctx: Context,
+    command: CommandInteraction,
+    mut rx: watch::Receiver<String>,
+) {
+    // Figures and length are only checked on the final response, a preview
+    // may end in the middle of a number
+    let guardrails = Guardrails::new(vec![], &[]);
+    let mut last_edit = Instant::now();
+
+    while rx.changed().await.is_ok() {
+        tokio::time::sleep_until(last_edit + STREAM_EDIT_INTERVAL).await;
+
+        // Reasoning of the model is never shown, not even while streaming
+        let text = strip_reasoning(&rx.borrow_and_update());
+        if text.trim().is_empty() {
+            continue;
+        }
+        if let Err(e) = guardrails.check_terms(&text) {
+            log::info!("Hiding the progressive response: {e}");
+            let placeholder = Locale::from_discord(&command.locale)
+                .catalog()
+                .preview_hidden;
+            if let Err(why) = command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(placeholder),
+                )
+                .await
+            {
+                log::warn!("Cannot send progressive response to slash command: {why}");
+            }
+            return;
+        }
+        let preview: String = match text.char_indices().nth(MAX_PREVIEW_CHARS) {
+            Some((idx, _)) => format!("{}…", &text[..idx]),
+            None => text,
+        };
+
+        if let Err(why) = command
+            .edit_response(&ctx.http, EditInteractionResponse::new().content(preview))
+            .await
+        {
+            log::warn!("Cannot send progressive response to slash command: {why}");
+        }
+        last_edit = Instant::now();
+    }
+}
@@ -0,0 +1,375 @@
+use std::time::Duration;
+
+use crate::database::article::{get_article, save_article};
+
+use super::{
+    relevance::{get_text_relevance, CHARS_PER_TOKEN},
+    SearchResult,
+};
+
+/// Seconds an article may take to download
+const ARTICLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Elements whose text is never part of the article
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "svg", "iframe",
+    "button", "figure", "template",
+];
+
+/// Elements holding the text of the article
+const TEXT_ELEMENTS: &[&str] = &["p", "h2", "h3", "blockquote"];
+
+/// Shorter paragraphs are captions, bylines or share buttons
+const MIN_PARAGRAPH_CHARS: usize = 40;
+
+/// Tokens of the chunks an article is summarized in
+const CHUNK_TOKENS: usize = 300;
+
+/// Replace the snippets of the first `count` results by a summary of their
+/// article in about `tokens` tokens.
+///
+/// Extracted texts are stored, so an article is only downloaded once.
+pub async fn add_article_summaries(
+    results: &mut [SearchResult],
+    keywords: &[(String, f64)],
+    count: usize,
+    tokens: usize,
+) {
+    for result in results.iter_mut().take(count) {
+        let Some(url) = result.url.clone() else {
+            continue;
+        };
+
+        let text = match get_article(&url) {
+            Some(text) => text,
+            None => match fetch_article(&url).await {
+                Some(html) => {
+                    let text = extract_text(&html);
+                    save_article(&url, &text);
+                    text
+                }
+                None => continue,
+            },
+        };
+
+        let summary = summarize(&text, keywords, tokens);
+        if summary.chars().count()
+            > result
+                .content
+                .as_deref()
+                .unwrap_or_default()
+                .chars()
+                .count()
+        {
+            result.content = Some(summary);
+        }
+    }
+}
+
+/// The HTML of `url`, `None` when it cannot be downloaded
+async fn fetch_article(url: &str) -> Option<String> {
+    let client = reqwest::Client::builder()
+        .timeout(ARTICLE_TIMEOUT)
+        .user_agent(concat!("exchange-rate-bot/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .ok()?;
+
+    let res = match client.get(url).send().await {
+        Ok(res) if res.status().is_success() => res,
+        Ok(res) => {
+            log::warn!("Error fetching article '{}': {}", url, res.status());
+            return None;
+        }
+        Err(e) => {
+            log::warn!("Error fetching article '{}': {}", url, e);
+            return None;
+        }
+    };
+
+    let is_html = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.contains("html"));
+    if !is_html {
+        // Still stored as empty so that it is not downloaded again
+        return Some(String::new());
+    }
+
+    match res.text().await {
+        Ok(html) => Some(html),
+        Err(e) => {
+            log::warn!("Error reading article '{}': {}", url, e);
+            None
+        }
+    }
+}
+
+/// The readable text of an HTML page, one paragraph per line.
+///
+/// Only paragraphs and headings are kept, navigation, scripts and other
+/// boilerplate are dropped. The `<article>` element is preferred when there is one.
+pub fn extract_text(html: &str) -> String {
+    // ASCII only, so that the positions are the same in `html`
+    let lowercase = html.to_ascii_lowercase();
+    let html = match (lowercase.find("<article"), lowercase.rfind("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+
+    let mut paragraphs: Vec<String> = vec![];
+    let mut skipping: Option<(String, usize)> = None;
+    let mut paragraph: Option<String> = None;
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        let text = &rest[..start];
+        if skipping.is_none() {
+            if let Some(paragraph) = paragraph.as_mut() {
+                paragraph.push_str(text);
+            }
+        }
+
+        rest = &rest[start..];
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        let self_closing = tag.ends_with('/');
+
+        // Nested elements of the same name have to be closed as well
+        if let Some((skipped, depth)) = skipping.as_mut() {
+            if *skipped == name && !self_closing {
+                match closing {
+                    true => *depth -= 1,
+                    false => *depth += 1,
+                }
+                if *depth == 0 {
+                    skipping = None;
+                }
+            }
+            continue;
+        }
+
+        if SKIPPED_ELEMENTS.contains(&name.as_str()) && !closing && !self_closing {
+            skipping = Some((name, 1));
+        } else if TEXT_ELEMENTS.contains(&name.as_str()) {
+            if let Some(text) = paragraph.take() {
+                push_paragraph(&mut paragraphs, &text);
+            }
+            if !closing {
+                paragraph = Some(String::new());
+            }
+        } else if name == "br" {
+            if let Some(paragraph) = paragraph.as_mut() {
+                paragraph.push(' ');
+            }
+        }
+    }
+
+    if let Some(text) = paragraph {
+        push_paragraph(&mut paragraphs, &text);
+    }
+
+    paragraphs.join("\n")
+}
+
+fn push_paragraph(paragraphs: &mut Vec<String>, text: &str) {
+    let text = clean_text(text);
+    if text.chars().count() >= MIN_PARAGRAPH_CHARS {
+        paragraphs.push(text);
+    }
+}
+
+/// Decode the common HTML entities and collapse whitespace
+fn clean_text(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let char = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" | "#39" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        });
+
+        match (entity, char) {
+            (Some(entity), Some(char)) => {
+                decoded.push(char);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Paragraphs of `text` grouped into chunks of about `tokens` tokens
+fn chunk_text(text: &str, tokens: usize) -> Vec<Vec<&str>> {
+    let max_chars = tokens * CHARS_PER_TOKEN;
+    let mut chunks: Vec<Vec<&str>> = vec![];
+    let mut chars = 0;
+
+    for paragraph in text.lines().filter(|p| !p.trim().is_empty()) {
+        let paragraph_chars = paragraph.chars().count();
+        match chunks.last_mut() {
+            Some(chunk) if chars + paragraph_chars <= max_chars => chunk.push(paragraph),
+            _ => {
+                chunks.push(vec![paragraph]);
+                chars = 0;
+            }
+        }
+        chars += paragraph_chars;
+    }
+
+    chunks
+}
+
+fn split_sentences(paragraph: &str) -> Vec<&str> {
+    let mut sentences = vec![];
+    let mut start = 0;
+    let chars: Vec<(usize, char)> = paragraph.char_indices().collect();
+
+    for (i, (index, c)) in chars.iter().enumerate() {
+        let ends = matches!(c, '.' | '!' | '?' | '。')
+            && chars
+                .get(i + 1)
+                .is_none_or(|(_, next)| next.is_whitespace());
+        if ends {
+            let end = index + c.len_utf8();
+            sentences.push(paragraph[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(paragraph[start..].trim());
+
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Summary of `text` in about `tokens` tokens, built from its most relevant
+/// sentences.
+///
+/// The text is split into chunks and every chunk contributes its best
+/// sentence before any chunk contributes a second one, so the summary covers
+/// the whole article. Sentences keep their order in the article.
+pub fn summarize(text: &str, keywords: &[(String, f64)], tokens: usize) -> String {
+    let max_chars = tokens * CHARS_PER_TOKEN;
+
+    // (rank within the chunk, score, position in the text, sentence)
+    let mut candidates: Vec<(usize, f64, usize, &str)> = vec![];
+    let mut position = 0;
+    for chunk in chunk_text(text, CHUNK_TOKENS) {
+        let mut sentences: Vec<(f64, usize, &str)> = vec![];
+        for (i, sentence) in chunk.iter().flat_map(|p| split_sentences(p)).enumerate() {
+            // Leads of a chunk tend to carry the news
+            let lead = if i == 0 { 0.5 } else { 0.0 };
+            sentences.push((
+                get_text_relevance(sentence, keywords) + lead,
+                position,
+                sentence,
+            ));
+            position += 1;
+        }
+        sentences.sort_by(|(a, _, _), (b, _, _)| b.total_cmp(a));
+        candidates.extend(
+            sentences
+                .into_iter()
+                .enumerate()
+                .map(|(rank, (score, position, sentence))| (rank, score, position, sentence)),
+        );
+    }
+    candidates.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+
+    let mut chars = 0;
+    let mut selected: Vec<(usize, &str)> = vec![];
+    for (_, _, position, sentence) in candidates {
+        let sentence_chars = sentence.chars().count() + 1;
+        if chars + sentence_chars > max_chars {
+            continue;
+        }
+        chars += sentence_chars;
+        selected.push((position, sentence));
+    }
+    selected.sort_by_key(|(position, _)| *position);
+
+    selected
+        .into_iter()
+        .map(|(_, sentence)| sentence)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_text() {
+        let html = r#"<html><head><style>p { color: red; }</style></head><body>
+            <nav><p>Home | Markets | Currencies and everything else you need</p></nav>
+            <article>
+              <h1>Yen surges</h1>
+              <p>The yen strengthened sharply on Monday after the Bank of Japan raised rates.</p>
+              <script>var p = "<p>not text</p>";</script>
+              <p>Share</p>
+              <p>Traders said the move caught markets off guard &amp; wiped out<br>carry trades.</p>
+            </article>
+            <footer><p>Copyright 2024, all rights reserved by the publisher.</p></footer>
+            </body></html>"#;
+
+        assert_eq!(
+            extract_text(html),
+            "The yen strengthened sharply on Monday after the Bank of Japan raised rates.\n\
+             Traders said the move caught markets off guard & wiped out carry trades."
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let keywords = vec![("bank of japan".to_string(), 3.0), ("yen".to_string(), 1.0)];
+        let text = "Markets were busy on Monday. The weather was fine in Tokyo. \
+                    The Bank of Japan raised rates and the yen surged.\n\
+                    Analysts expect more volatility. Some stocks fell.";
+
+        let summary = summarize(text, &keywords, 15);
+        assert_eq!(
+            summary,
+            "The Bank of Japan raised rates and the yen surged."
+        );
+        assert!(summarize(text, &keywords, 1000).starts_with("Markets were busy on Monday."));
+    }
+}
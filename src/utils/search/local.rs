@@ -0,0 +1,76 @@
+use serde_json::Value;
+use serenity::async_trait;
+
+use super::{parse_published, SearchCategory, SearchProvider, SearchResult};
+
+/// Results read from a JSON file, for tests and offline setups.
+///
+/// The file holds an array of `{"title", "content", "url", "published",
+/// "category"}` objects, the category being `news` or `general`. Entries
+/// without a category are returned for both.
+pub struct LocalProvider {
+    path: String,
+}
+
+impl LocalProvider {
+    pub fn new(path: String) -> LocalProvider {
+        LocalProvider { path }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for LocalProvider {
+    fn name(&self) -> &'static str {
+        "Local"
+    }
+
+    /// The query is ignored, every entry of the category is a result
+    async fn search(
+        &self,
+        _query: &str,
+        category: SearchCategory,
+        max: usize,
+    ) -> Vec<SearchResult> {
+        let text = match tokio::fs::read_to_string(&self.path).await {
+            Ok(text) => text,
+            Err(e) => {
+                log::error!("Error reading search results '{}': {}", self.path, e);
+                return vec![];
+            }
+        };
+
+        match parse_results(&text, category) {
+            Ok(results) => results.into_iter().take(max).collect(),
+            Err(e) => {
+                log::error!("Error parsing search results '{}': {}", self.path, e);
+                vec![]
+            }
+        }
+    }
+}
+
+fn parse_results(json: &str, category: SearchCategory) -> Result<Vec<SearchResult>, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| "expected an array of results".to_string())?;
+
+    let category = match category {
+        SearchCategory::General => "general",
+        SearchCategory::News => "news",
+    };
+
+    Ok(entries
+        .iter()
+        .filter(|entry| entry["category"].as_str().is_none_or(|c| c == category))
+        .map(|entry| {
+            let field = |name: &str| entry[name].as_str().map(str::to_owned);
+            SearchResult {
+                url: field("url"),
+                title: field("title"),
+                content: field("content"),
+                published: field("published").as_deref().and_then(parse_published),
+            }
+        })
+        .collect())
+}
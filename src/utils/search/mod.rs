@@ -0,0 +1,303 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serenity::async_trait;
+
+use crate::environment;
+
+pub mod article;
+pub mod local;
+pub mod relevance;
+pub mod rss;
+pub mod searxng;
+
+#[derive(Clone, Copy, Debug)]
+pub enum SearchCategory {
+    General,
+    News,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+}
+
+/// Publication dates as RFC 3339, RFC 2822 or a timestamp without time zone in UTC
+pub fn parse_published(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.trim();
+    DateTime::parse_from_rfc3339(date)
+        .or_else(|_| DateTime::parse_from_rfc2822(date))
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+                .map(|date| date.and_utc())
+        })
+}
+
+/// A source of search results, see [`get_providers`]
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Name of the provider, shown in the logs
+    fn name(&self) -> &'static str;
+
+    /// At most `max` results for `query`, empty when the provider fails
+    async fn search(&self, query: &str, category: SearchCategory, max: usize) -> Vec<SearchResult>;
+}
+
+/// Get the providers that are configured, in the order their results are preferred
+pub fn get_providers() -> Vec<Box<dyn SearchProvider>> {
+    let mut providers: Vec<Box<dyn SearchProvider>> = vec![];
+    if let Some(url) = environment::get_searxng_url() {
+        providers.push(Box::new(searxng::SearxngProvider::new(url)));
+    }
+    let feeds = environment::get_search_rss_feeds();
+    if !feeds.is_empty() {
+        providers.push(Box::new(rss::RssProvider::new(feeds)));
+    }
+    if let Some(path) = environment::get_search_local_file() {
+        providers.push(Box::new(local::LocalProvider::new(path)));
+    }
+    providers
+}
+
+/// Search every configured provider and merge their results
+pub async fn search_all(query: &str, category: SearchCategory, max: usize) -> Vec<SearchResult> {
+    let mut results = vec![];
+    for provider in get_providers() {
+        let found = provider.search(query, category, max).await;
+        log::debug!(
+            "{} found {} results for '{}'",
+            provider.name(),
+            found.len(),
+            query
+        );
+        results.push(found);
+    }
+    merge_results(results, max)
+}
+
+/// Take turns between the providers' results, skipping results whose URL or
+/// title was already taken.
+pub fn merge_results(results: Vec<Vec<SearchResult>>, max: usize) -> Vec<SearchResult> {
+    let mut iters: Vec<_> = results.into_iter().map(|r| r.into_iter()).collect();
+    let mut seen = HashSet::new();
+    let mut merged = vec![];
+
+    while merged.len() < max {
+        let mut any = false;
+        for iter in iters.iter_mut() {
+            let Some(result) = iter.next() else {
+                continue;
+            };
+            any = true;
+
+            let keys: Vec<String> = [
+                result.url.as_deref().map(get_url_key),
+                result.title.as_deref().map(get_title_key),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|key| !key.is_empty())
+            .collect();
+            if keys.iter().any(|key| seen.contains(key)) {
+                continue;
+            }
+            seen.extend(keys);
+
+            merged.push(result);
+            if merged.len() == max {
+                break;
+            }
+        }
+        if !any {
+            break;
+        }
+    }
+
+    merged
+}
+
+/// The same article linked with or without `https://www.`, a trailing slash
+/// or a fragment
+fn get_url_key(url: &str) -> String {
+    let url = url.trim().to_lowercase();
+    let url = url.split('#').next().unwrap_or_default();
+    let url = url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.");
+    format!("url:{}", url.trim_end_matches('/'))
+}
+
+/// Titles differing only in case, punctuation or spacing
+fn get_title_key(title: &str) -> String {
+    let words: Vec<String> = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect();
+    match words.is_empty() {
+        true => String::new(),
+        false => format!("title:{}", words.join(" ")),
+    }
+}
+
+/// Run `queries` and keep the `max` results most relevant to `from` and `to`
+async fn search_pair(
+    queries: &[String],
+    category: SearchCategory,
+    from: &str,
+    to: &str,
+    now: DateTime<Utc>,
+    max: usize,
+) -> Vec<SearchResult> {
+    let mut results = vec![];
+    for query in queries {
+        results.extend(search_all(query, category, max * 2).await);
+    }
+    relevance::select_results(
+        results,
+        &relevance::get_pair_keywords(from, to),
+        now,
+        max,
+        environment::get_search_item_tokens(),
+    )
+}
+
+/// News about `from` and `to`, the currencies, their countries and central banks
+///
+/// The snippets of the top `ARTICLE_FETCH_COUNT` results are replaced by a
+/// summary of their article.
+pub async fn get_news(from: &str, to: &str, datetime: DateTime<Utc>, max: u8) -> Vec<SearchResult> {
+    let mut news = search_pair(
+        &relevance::get_news_queries(from, to),
+        SearchCategory::News,
+        from,
+        to,
+        datetime,
+        usize::from(max),
+    )
+    .await;
+
+    let count = environment::get_article_fetch_count();
+    if count > 0 {
+        article::add_article_summaries(
+            &mut news,
+            &relevance::get_pair_keywords(from, to),
+            count,
+            environment::get_article_summary_tokens(),
+        )
+        .await;
+    }
+    news
+}
+
+/// What happens on the day of `datetime` in the countries of `from` and `to`
+pub async fn search_date(
+    from: &str,
+    to: &str,
+    datetime: DateTime<Utc>,
+    max: u8,
+) -> Vec<SearchResult> {
+    search_pair(
+        &[relevance::get_date_query(from, to, datetime)],
+        SearchCategory::General,
+        from,
+        to,
+        datetime,
+        usize::from(max),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::test;
+
+    // Initialize the logger for the tests
+    fn init_logger() {
+        let _ = env_logger::builder()
+            .is_test(true) // Ensures logs are only displayed during tests
+            .filter_level(log::LevelFilter::Debug) // Set the log level to debug
+            .try_init();
+    }
+
+    #[tokio::test]
+    async fn test_get_news() {
+        // Initialize the logger
+        init_logger();
+        // Mock the SearxNG URL environment variable
+        std::env::set_var("SEARXNG_URL", "https://searxng.nannoda.com");
+        // Mock a datetime value
+        let datetime = Utc::now();
+
+        // Call the function
+        let results = get_news("EUR", "USD", datetime, 5).await;
+
+        println!("result: {:?}", results);
+
+        // Assert the results
+        assert!(
+            !results.is_empty(),
+            "get_news should return results (mocked case)"
+        );
+    }
+
+    fn result(url: &str, title: &str) -> SearchResult {
+        SearchResult {
+            url: Some(url.to_string()),
+            title: Some(title.to_string()),
+            content: None,
+            published: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_results() {
+        let merged = merge_results(
+            vec![
+                vec![
+                    result("https://www.example.com/ecb/", "ECB holds rates"),
+                    result("https://example.com/fed", "Fed cuts rates"),
+                ],
+                vec![
+                    result("http://example.com/ecb#top", "Euro steadies"),
+                    result("https://other.com/ecb", "ECB Holds Rates!"),
+                    result("https://other.com/yen", "Yen surges"),
+                ],
+            ],
+            5,
+        );
+        let titles: Vec<_> = merged.iter().filter_map(|r| r.title.as_deref()).collect();
+        assert_eq!(titles, ["ECB holds rates", "Fed cuts rates", "Yen surges"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_date() {
+        // Initialize the logger
+        init_logger();
+        // Mock the SearxNG URL environment variable
+        std::env::set_var("SEARXNG_URL", "https://searxng.nannoda.com");
+        // Mock a datetime value
+        let datetime = Utc::now();
+
+        // Call the function
+        let results = search_date("EUR", "USD", datetime, 5).await;
+
+        println!("result: {:?}", results);
+
+        // Assert the results
+        assert!(
+            !results.is_empty(),
+            "search_date should return results (mocked case)"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use crate::utils::currencies::get_currency_info;
+
+use super::SearchResult;
+
+/// Words of any exchange rate story, worth less than the pair's own keywords
+const GENERIC_KEYWORDS: &[&str] = &[
+    "exchange rate",
+    "currency",
+    "forex",
+    "interest rate",
+    "inflation",
+    "central bank",
+];
+
+/// Titles sharing this share of their words are the same story
+const NEAR_DUPLICATE_SIMILARITY: f64 = 0.6;
+
+/// Rough number of characters per token of the LLM
+pub(super) const CHARS_PER_TOKEN: usize = 4;
+
+/// Lowercase keywords of `from` and `to` with their weight
+pub fn get_pair_keywords(from: &str, to: &str) -> Vec<(String, f64)> {
+    let mut keywords: Vec<(String, f64)> = vec![];
+    for code in [from, to] {
+        keywords.push((code.to_lowercase(), 2.0));
+        if let Some(info) = get_currency_info(code) {
+            keywords.push((info.name.to_string(), 1.0));
+            keywords.push((info.region.to_lowercase(), 2.0));
+            keywords.push((info.central_bank.to_lowercase(), 3.0));
+        }
+    }
+    keywords.extend(GENERIC_KEYWORDS.iter().map(|k| (k.to_string(), 1.0)));
+
+    let mut seen = HashSet::new();
+    keywords.retain(|(keyword, _)| seen.insert(keyword.clone()));
+    keywords
+}
+
+/// News queries about `from` and `to`, e.g. `USD CAD exchange rate` and
+/// `Federal Reserve Bank of Canada`
+pub fn get_news_queries(from: &str, to: &str) -> Vec<String> {
+    let mut queries = vec![format!("{} {} exchange rate", from, to)];
+    if let (Some(from), Some(to)) = (get_currency_info(from), get_currency_info(to)) {
+        queries.push(format!("{} {}", from.central_bank, to.central_bank));
+    }
+    queries
+}
+
+/// Query about what happens on `date` in the regions of `from` and `to`
+pub fn get_date_query(from: &str, to: &str, date: DateTime<Utc>) -> String {
+    let region =
+        |code: &str| get_currency_info(code).map_or(code.to_string(), |i| i.region.to_string());
+    format!("{} {} {}", region(from), region(to), date.format("%B %-d"))
+}
+
+/// Sum of the weights of the `keywords` in `text`
+pub(super) fn get_text_relevance(text: &str, keywords: &[(String, f64)]) -> f64 {
+    let text = text.to_lowercase();
+    keywords
+        .iter()
+        .filter(|(keyword, _)| text.contains(keyword.as_str()))
+        .map(|(_, weight)| weight)
+        .sum()
+}
+
+/// Keywords in the title count twice
+fn get_relevance(result: &SearchResult, keywords: &[(String, f64)]) -> f64 {
+    2.0 * get_text_relevance(result.title.as_deref().unwrap_or_default(), keywords)
+        + get_text_relevance(result.content.as_deref().unwrap_or_default(), keywords)
+}
+
+/// Up to 2 for a result published today, fading over the following days
+fn get_recency(result: &SearchResult, now: DateTime<Utc>) -> f64 {
+    result.published.map_or(0.0, |published| {
+        let days = (now - published).num_hours().max(0) as f64 / 24.0;
+        2.0 / (1.0 + days)
+    })
+}
+
+fn get_title_words(result: &SearchResult) -> HashSet<String> {
+    result
+        .title
+        .as_deref()
+        .unwrap_or_default()
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether two results tell the same story, judged by their titles
+fn is_near_duplicate(a: &HashSet<String>, b: &HashSet<String>) -> bool {
+    let union = a.union(b).count();
+    union > 0 && a.intersection(b).count() as f64 / union as f64 >= NEAR_DUPLICATE_SIMILARITY
+}
+
+/// Cut `text` to about `tokens` tokens at a word boundary
+pub fn truncate_to_tokens(text: &str, tokens: usize) -> String {
+    let max_chars = tokens * CHARS_PER_TOKEN;
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    for word in text.split_whitespace() {
+        if truncated.chars().count() + word.chars().count() + 1 > max_chars {
+            break;
+        }
+        if !truncated.is_empty() {
+            truncated.push(' ');
+        }
+        truncated.push_str(word);
+    }
+    truncated + "…"
+}
+
+/// The `max` most relevant and recent results, without near-duplicates and
+/// with their content cut to `item_tokens` tokens
+pub fn select_results(
+    results: Vec<SearchResult>,
+    keywords: &[(String, f64)],
+    now: DateTime<Utc>,
+    max: usize,
+    item_tokens: usize,
+) -> Vec<SearchResult> {
+    let mut scored: Vec<(f64, SearchResult)> = results
+        .into_iter()
+        .map(|result| {
+            let score = get_relevance(&result, keywords) + get_recency(&result, now);
+            (score, result)
+        })
+        .collect();
+    // Stable, so results of the same score keep the order of the providers
+    scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    let mut selected: Vec<(HashSet<String>, SearchResult)> = vec![];
+    for (_, mut result) in scored {
+        if selected.len() == max {
+            break;
+        }
+        let words = get_title_words(&result);
+        let duplicate = selected.iter().any(|(other_words, other)| {
+            is_near_duplicate(&words, other_words)
+                || (result.url.is_some() && result.url == other.url)
+        });
+        if duplicate {
+            continue;
+        }
+
+        result.content = result
+            .content
+            .map(|content| truncate_to_tokens(&content, item_tokens));
+        selected.push((words, result));
+    }
+
+    selected.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn result(title: &str, content: &str, age_days: i64) -> SearchResult {
+        SearchResult {
+            url: None,
+            title: Some(title.to_string()),
+            content: Some(content.to_string()),
+            published: Some(Utc::now() - Duration::days(age_days)),
+        }
+    }
+
+    #[test]
+    fn test_pair_queries() {
+        assert_eq!(
+            get_news_queries("USD", "CAD"),
+            ["USD CAD exchange rate", "Federal Reserve Bank of Canada"]
+        );
+        let keywords = get_pair_keywords("USD", "CAD");
+        assert!(keywords.iter().any(|(k, _)| k == "bank of canada"));
+        // Both are dollars, the name is only listed once
+        assert_eq!(keywords.iter().filter(|(k, _)| k == "dollar").count(), 1);
+    }
+
+    #[test]
+    fn test_select_results() {
+        let keywords = get_pair_keywords("EUR", "JPY");
+        let selected = select_results(
+            vec![
+                result("Celebrity wedding", "Photos of the day.", 0),
+                result("Bank of Japan raises rates", "The yen surged.", 1),
+                result("Bank of Japan raises interest rates", "Yen jumps.", 0),
+                result("ECB holds rates", "The euro is steady.", 3),
+                result("Bank of Japan hikes", &"word ".repeat(100), 10),
+            ],
+            &keywords,
+            Utc::now(),
+            3,
+            10,
+        );
+
+        let titles: Vec<_> = selected.iter().filter_map(|r| r.title.as_deref()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Bank of Japan raises interest rates",
+                "Bank of Japan hikes",
+                "ECB holds rates"
+            ]
+        );
+        assert!(selected[1].content.as_ref().unwrap().chars().count() <= 41);
+    }
+}
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use serenity::async_trait;
+
+use crate::database::search_result::save_search_result;
+
+use super::{parse_published, SearchCategory, SearchProvider, SearchResult};
+
+/// Seconds a feed may take to download
+const FEED_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// RSS and Atom feeds aggregated into news results
+pub struct RssProvider {
+    feeds: Vec<String>,
+}
+
+impl RssProvider {
+    pub fn new(feeds: Vec<String>) -> RssProvider {
+        RssProvider { feeds }
+    }
+
+    async fn fetch(&self, client: &reqwest::Client, feed: &str) -> Vec<SearchResult> {
+        let text = match client.get(feed).send().await {
+            Ok(res) if res.status().is_success() => match res.text().await {
+                Ok(text) => text,
+                Err(e) => {
+                    log::error!("Error reading feed '{}': {}", feed, e);
+                    return vec![];
+                }
+            },
+            Ok(res) => {
+                log::error!("Error fetching feed '{}': {}", feed, res.status());
+                return vec![];
+            }
+            Err(e) => {
+                log::error!("Error fetching feed '{}': {}", feed, e);
+                return vec![];
+            }
+        };
+
+        save_search_result(feed, &text);
+
+        match parse_feed(&text) {
+            Ok(items) => items,
+            Err(e) => {
+                log::error!("Error parsing feed '{}': {}", feed, e);
+                vec![]
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for RssProvider {
+    fn name(&self) -> &'static str {
+        "RSS"
+    }
+
+    /// Feeds only carry news. Items mentioning a word of `query` are
+    /// preferred, the newest items are returned when none does.
+    async fn search(&self, query: &str, category: SearchCategory, max: usize) -> Vec<SearchResult> {
+        if !matches!(category, SearchCategory::News) {
+            return vec![];
+        }
+
+        let client = match reqwest::Client::builder().timeout(FEED_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Cannot build feed client: {}", e);
+                return vec![];
+            }
+        };
+
+        let mut items = vec![];
+        for feed in &self.feeds {
+            items.extend(self.fetch(&client, feed).await);
+        }
+        items.sort_by_key(|item| std::cmp::Reverse(item.published));
+
+        let words = get_query_words(query);
+        let (matching, others): (Vec<SearchResult>, Vec<SearchResult>) = items
+            .into_iter()
+            .partition(|item| mentions_any(item, &words));
+        let items = if matching.is_empty() {
+            others
+        } else {
+            matching
+        };
+
+        items.into_iter().take(max).collect()
+    }
+}
+
+/// Words of `query` worth matching, dates and filler are left out
+fn get_query_words(query: &str) -> Vec<String> {
+    query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3)
+        .filter(|word| !word.chars().all(|c| c.is_ascii_digit()))
+        .filter(|word| *word != "news")
+        .map(str::to_string)
+        .collect()
+}
+
+fn mentions_any(result: &SearchResult, words: &[String]) -> bool {
+    let text = format!(
+        "{} {}",
+        result.title.as_deref().unwrap_or_default(),
+        result.content.as_deref().unwrap_or_default()
+    )
+    .to_lowercase();
+    words.iter().any(|word| text.contains(word.as_str()))
+}
+
+/// Items of an RSS 2.0 `<item>` or Atom `<entry>` feed
+fn parse_feed(xml: &str) -> Result<Vec<SearchResult>, roxmltree::Error> {
+    let document = roxmltree::Document::parse(xml)?;
+
+    let items = document
+        .descendants()
+        .filter(|node| node.has_tag_name("item") || node.has_tag_name("entry"))
+        .map(|node| {
+            let text = |names: &[&str]| {
+                names.iter().find_map(|name| {
+                    node.children()
+                        .find(|child| child.has_tag_name(*name))
+                        .and_then(|child| child.text())
+                        .map(|text| strip_html(text.trim()))
+                        .filter(|text| !text.is_empty())
+                })
+            };
+            // Atom links are attributes, RSS links are text
+            let url = node
+                .children()
+                .find(|child| child.has_tag_name("link"))
+                .and_then(|link| link.attribute("href").or(link.text()))
+                .map(|url| url.trim().to_string());
+            SearchResult {
+                url,
+                title: text(&["title"]),
+                content: text(&["description", "summary", "content"]),
+                published: text(&["pubDate", "published", "updated", "date"])
+                    .as_deref()
+                    .and_then(parse_published),
+            }
+        })
+        .collect();
+
+    Ok(items)
+}
+
+/// Feed descriptions often carry markup, only the text is kept
+fn strip_html(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feed() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel>
+              <item>
+                <title>ECB holds rates</title>
+                <link>https://example.com/ecb</link>
+                <description>&lt;p&gt;The euro &lt;b&gt;steadied&lt;/b&gt;.&lt;/p&gt;</description>
+                <pubDate>Thu, 07 Mar 2024 13:15:00 GMT</pubDate>
+              </item>
+            </channel></rss>"#;
+        let items = parse_feed(rss).unwrap();
+        assert_eq!(items[0].url.as_deref(), Some("https://example.com/ecb"));
+        assert_eq!(items[0].content.as_deref(), Some("The euro steadied."));
+        assert!(items[0].published.is_some());
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+              <entry>
+                <title>Yen surges</title>
+                <link href="https://example.com/boj"/>
+                <summary>The yen strengthened.</summary>
+                <updated>2024-08-05T09:00:00Z</updated>
+              </entry>
+            </feed>"#;
+        let items = parse_feed(atom).unwrap();
+        assert_eq!(items[0].title.as_deref(), Some("Yen surges"));
+        assert_eq!(items[0].url.as_deref(), Some("https://example.com/boj"));
+    }
+}
@@ -0,0 +1,135 @@
+use serde_json::Value;
+use serenity::async_trait;
+
+use crate::database::search_result::save_search_result;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+use super::{parse_published, SearchCategory, SearchProvider, SearchResult};
+
+/// A SearxNG instance queried through its JSON API
+pub struct SearxngProvider {
+    url: String,
+}
+
+impl SearxngProvider {
+    pub fn new(url: String) -> SearxngProvider {
+        SearxngProvider { url }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for SearxngProvider {
+    fn name(&self) -> &'static str {
+        "SearxNG"
+    }
+
+    async fn search(&self, query: &str, category: SearchCategory, max: usize) -> Vec<SearchResult> {
+        let v = match query_searxng(&self.url, query, category).await {
+            Some(v) => v,
+            None => return vec![],
+        };
+
+        let results = match v.get("results").and_then(|v| v.as_array()) {
+            Some(r) => r,
+            None => {
+                log::error!("No result");
+                return vec![];
+            }
+        };
+
+        results
+            .iter()
+            .take(max)
+            .map(|item| {
+                let field = |name: &str| item.get(name).and_then(|v| v.as_str()).map(str::to_owned);
+                SearchResult {
+                    url: field("url"),
+                    title: field("title"),
+                    content: field("content"),
+                    published: field("publishedDate").as_deref().and_then(parse_published),
+                }
+            })
+            .collect()
+    }
+}
+
+async fn query_searxng(url: &str, keyword: &str, category: SearchCategory) -> Option<Value> {
+    // URL-encode the keyword to make it web-safe
+    let encoded_keyword = utf8_percent_encode(keyword, NON_ALPHANUMERIC).to_string();
+
+    let query_url = format!(
+        "{}/search?q={}&format=json&safesearch=0&categories={}",
+        url,
+        encoded_keyword,
+        match category {
+            SearchCategory::General => "general",
+            SearchCategory::News => "news",
+        }
+    );
+
+    log::debug!("Search Query: {query_url}");
+
+    let res = match reqwest::get(&query_url).await {
+        Ok(res) => res,
+        Err(err) => {
+            log::error!("Error fetching '{}': {}", query_url, err);
+            return None;
+        }
+    };
+
+    if !res.status().is_success() {
+        log::error!("Error fetching '{}': {}", query_url, res.status());
+        return None;
+    }
+
+    // Safely attempt to read the response body
+    let text = match res.text().await {
+        Ok(txt) => txt,
+        Err(e) => {
+            log::error!("Error getting text {e}");
+            return None;
+        }
+    };
+
+    save_search_result(url, &text);
+
+    match serde_json::from_str(&text) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log::error!("Error parse json {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Initialize the logger for the tests
+    fn init_logger() {
+        let _ = env_logger::builder()
+            .is_test(true) // Ensures logs are only displayed during tests
+            .filter_level(log::LevelFilter::Debug) // Set the log level to debug
+            .try_init();
+    }
+
+    #[tokio::test]
+    async fn test_search_general() {
+        // Initialize the logger
+        init_logger();
+        // Mock the SearxNG URL environment variable
+        std::env::set_var("SEARXNG_URL", "https://searxng.nannoda.com");
+
+        // Call the function
+        let provider = SearxngProvider::new(crate::environment::get_searxng_url().unwrap());
+        let result = provider
+            .search("example keyword", SearchCategory::General, 5)
+            .await;
+
+        println!("result: {:?}", result);
+
+        // Assert the result
+        assert!(!result.is_empty(), "Search should return a result");
+    }
+}
@@ -0,0 +1,356 @@
+use chrono::NaiveDate;
+
+use crate::exchange_rate::ExchangeRateMap;
+
+/// Trading days in a year, used to annualize the daily volatility
+pub const TRADING_DAYS: f64 = 252.0;
+
+/// Statistics of the daily values of a currency pair
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub first: (NaiveDate, f64),
+    pub last: (NaiveDate, f64),
+    pub high: f64,
+    pub low: f64,
+    pub average: f64,
+    /// Percent change from the first to the last value
+    pub change: f64,
+    /// Standard deviation of the percent changes between trading days
+    pub volatility: f64,
+    /// Daily volatility scaled to a year of trading days
+    pub annualized_volatility: f64,
+    pub max_drawdown: Option<Drawdown>,
+    /// Length and direction of the latest streak, see [`get_streak`]
+    pub streak: (usize, f64),
+    pub longest_gains: usize,
+    pub longest_losses: usize,
+    /// Latest change in standard deviations of the changes before it
+    pub z_score: Option<f64>,
+}
+
+/// Largest fall from a high to a later low
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drawdown {
+    /// Fall in percent of the high, positive
+    pub percent: f64,
+    pub peak: (NaiveDate, f64),
+    pub trough: (NaiveDate, f64),
+}
+
+/// Daily values of `from` to `to`, sorted by date
+pub fn get_series(rates: &[ExchangeRateMap], from: &str, to: &str) -> Vec<(NaiveDate, f64)> {
+    let mut data: Vec<(NaiveDate, f64)> = rates
+        .iter()
+        .filter_map(|r| r.get_val(from, to).map(|v| (r.get_date(), v)))
+        .collect();
+    data.sort_by_key(|(date, _)| *date);
+    data
+}
+
+/// Values of sorted `data` after `since`
+pub fn get_since(data: &[(NaiveDate, f64)], since: NaiveDate) -> &[(NaiveDate, f64)] {
+    &data[data.partition_point(|(date, _)| *date <= since)..]
+}
+
+/// Values of sorted `data` on trading days.
+///
+/// Weekends and holidays carry the rates of the day before forward, days
+/// repeating the value before them are left out like in
+/// [`get_outliers`](super::anomaly::get_outliers).
+pub fn get_trading_days(data: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, f64)> {
+    let mut days = data.to_vec();
+    days.dedup_by(|day, previous| day.1 == previous.1);
+    days
+}
+
+/// Percent change of every value from the one before, dated by the later value
+fn get_changes(data: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, f64)> {
+    data.windows(2)
+        .map(|pair| (pair[1].0, (pair[1].1 - pair[0].1) / pair[0].1 * 100.0))
+        .collect()
+}
+
+/// Percent change of every trading day of `data` from the one before, dated by
+/// the later day
+pub fn get_returns(data: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, f64)> {
+    get_changes(&get_trading_days(data))
+}
+
+fn get_mean(values: &[f64]) -> Option<f64> {
+    match values.len() {
+        0 => None,
+        len => Some(values.iter().sum::<f64>() / len as f64),
+    }
+}
+
+fn get_deviation(values: &[f64]) -> Option<f64> {
+    let mean = get_mean(values)?;
+    Some((values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt())
+}
+
+/// Standard deviation of the daily percent changes `returns`, 0 without changes
+pub fn get_volatility(returns: &[f64]) -> f64 {
+    get_deviation(returns).unwrap_or_default()
+}
+
+/// Volatility of `returns` scaled to a year of trading days
+pub fn get_annualized_volatility(returns: &[f64]) -> f64 {
+    get_volatility(returns) * TRADING_DAYS.sqrt()
+}
+
+/// Largest fall of `data` from a high to a later low, `None` when it never fell
+pub fn get_max_drawdown(data: &[(NaiveDate, f64)]) -> Option<Drawdown> {
+    let mut peak = *data.first()?;
+    let mut max_drawdown: Option<Drawdown> = None;
+    for &(date, value) in data {
+        if value > peak.1 {
+            peak = (date, value);
+            continue;
+        }
+        let percent = (peak.1 - value) / peak.1 * 100.0;
+        if percent > max_drawdown.map_or(0.0, |drawdown| drawdown.percent) {
+            max_drawdown = Some(Drawdown {
+                percent,
+                peak,
+                trough: (date, value),
+            });
+        }
+    }
+    max_drawdown
+}
+
+/// Directions of the moves of `data`, skipping moves smaller than `threshold`
+fn get_moves(data: &[(NaiveDate, f64)], threshold: f64) -> impl Iterator<Item = f64> + '_ {
+    data.windows(2)
+        .map(|pair| pair[1].1 - pair[0].1)
+        .filter(move |diff| diff.abs() >= threshold)
+        .map(f64::signum)
+}
+
+/// Length and direction of the latest run of moves in the same direction.
+///
+/// Days moving less than `threshold` (weekends, holidays) neither extend nor
+/// break the streak.
+pub fn get_streak(data: &[(NaiveDate, f64)], threshold: f64) -> (usize, f64) {
+    let moves: Vec<f64> = get_moves(data, threshold).collect();
+    match moves.last() {
+        Some(&direction) => (
+            moves.iter().rev().take_while(|d| **d == direction).count(),
+            direction,
+        ),
+        None => (0, 0.0),
+    }
+}
+
+/// Longest runs of gains and of losses, skipping moves like [`get_streak`]
+pub fn get_longest_streaks(data: &[(NaiveDate, f64)], threshold: f64) -> (usize, usize) {
+    let (mut gains, mut losses) = (0, 0);
+    let mut run = (0, 0.0);
+    for direction in get_moves(data, threshold) {
+        run = match run {
+            (length, d) if d == direction => (length + 1, d),
+            _ => (1, direction),
+        };
+        if direction > 0.0 {
+            gains = gains.max(run.0);
+        } else {
+            losses = losses.max(run.0);
+        }
+    }
+    (gains, losses)
+}
+
+/// Latest of `returns` in standard deviations from the mean of the returns
+/// before it, `None` with fewer than two of them or when they did not vary
+pub fn get_z_score(returns: &[f64]) -> Option<f64> {
+    let (latest, before) = returns.split_last()?;
+    if before.len() < 2 {
+        return None;
+    }
+    let deviation = get_deviation(before).filter(|deviation| *deviation > 0.0)?;
+    Some((latest - get_mean(before)?) / deviation)
+}
+
+/// Pearson correlation of `a` and `b`, `None` when either did not vary
+pub fn get_correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let (mean_a, mean_b) = (get_mean(a)?, get_mean(b)?);
+    let covariance: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| (a - mean_a) * (b - mean_b))
+        .sum();
+    let variance_a: f64 = a.iter().map(|a| (a - mean_a).powi(2)).sum();
+    let variance_b: f64 = b.iter().map(|b| (b - mean_b).powi(2)).sum();
+    match variance_a * variance_b {
+        product if product > 0.0 => Some(covariance / product.sqrt()),
+        _ => None,
+    }
+}
+
+/// Percent changes of `a` and `b` between the dates both have a value, except
+/// for the days both carried forward
+fn get_paired_returns(
+    a: &[(NaiveDate, f64)],
+    b: &[(NaiveDate, f64)],
+) -> Vec<(NaiveDate, f64, f64)> {
+    let (a, b): (Vec<_>, Vec<_>) = a
+        .iter()
+        .filter_map(|&(date, a)| {
+            b.binary_search_by_key(&date, |(date, _)| *date)
+                .ok()
+                .map(|i| ((date, a), b[i]))
+        })
+        .unzip();
+    get_changes(&a)
+        .into_iter()
+        .zip(get_changes(&b))
+        .map(|((date, a), (_, b))| (date, a, b))
+        .filter(|(_, a, b)| *a != 0.0 || *b != 0.0)
+        .collect()
+}
+
+/// Correlation of the daily changes of `a` and `b` over the last `window` days
+/// both changed, dated by the last day of every window
+pub fn get_rolling_correlation(
+    a: &[(NaiveDate, f64)],
+    b: &[(NaiveDate, f64)],
+    window: usize,
+) -> Vec<(NaiveDate, f64)> {
+    if window < 2 {
+        return vec![];
+    }
+    get_paired_returns(a, b)
+        .windows(window)
+        .filter_map(|pairs| {
+            let a: Vec<f64> = pairs.iter().map(|(_, a, _)| *a).collect();
+            let b: Vec<f64> = pairs.iter().map(|(_, _, b)| *b).collect();
+            get_correlation(&a, &b).map(|correlation| (pairs[window - 1].0, correlation))
+        })
+        .collect()
+}
+
+/// Correlation of the daily changes of `a` and `b` over all days both changed
+pub fn get_pair_correlation(a: &[(NaiveDate, f64)], b: &[(NaiveDate, f64)]) -> Option<f64> {
+    let pairs = get_paired_returns(a, b);
+    let a: Vec<f64> = pairs.iter().map(|(_, a, _)| *a).collect();
+    let b: Vec<f64> = pairs.iter().map(|(_, _, b)| *b).collect();
+    get_correlation(&a, &b)
+}
+
+/// Statistics of sorted `data`, `None` when it is empty.
+///
+/// Moves smaller than `threshold` do not count for the streaks.
+pub fn get_stats(data: &[(NaiveDate, f64)], threshold: f64) -> Option<Stats> {
+    let first = *data.first()?;
+    let last = *data.last()?;
+    let values: Vec<f64> = data.iter().map(|(_, v)| *v).collect();
+    let returns: Vec<f64> = get_returns(data).into_iter().map(|(_, r)| r).collect();
+    let (longest_gains, longest_losses) = get_longest_streaks(data, threshold);
+
+    Some(Stats {
+        first,
+        last,
+        high: values.iter().cloned().fold(f64::MIN, f64::max),
+        low: values.iter().cloned().fold(f64::MAX, f64::min),
+        average: get_mean(&values)?,
+        change: (last.1 - first.1) / first.1 * 100.0,
+        volatility: get_volatility(&returns),
+        annualized_volatility: get_annualized_volatility(&returns),
+        max_drawdown: get_max_drawdown(data),
+        streak: get_streak(data, threshold),
+        longest_gains,
+        longest_losses,
+        z_score: get_z_score(&returns),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fixtures::get_daily_series as get_data;
+    use chrono::{Datelike, Weekday};
+
+    #[test]
+    fn test_streaks() {
+        let data = get_data(&[1.00, 1.01, 1.02, 1.02, 1.03]);
+        assert_eq!(get_streak(&data, 0.001), (3, 1.0));
+        assert_eq!(get_streak(&data[..1], 0.001), (0, 0.0));
+
+        let data = get_data(&[1.00, 0.99, 0.98, 0.97, 0.98, 0.98, 0.99, 0.98]);
+        assert_eq!(get_streak(&data, 0.001), (1, -1.0));
+        assert_eq!(get_longest_streaks(&data, 0.001), (2, 3));
+    }
+
+    #[test]
+    fn test_stats() {
+        let data = get_data(&[100.0, 110.0, 99.0, 104.0, 88.0, 120.0]);
+        let drawdown = get_max_drawdown(&data).unwrap();
+        assert_eq!(drawdown.peak, data[1]);
+        assert_eq!(drawdown.trough, data[4]);
+        assert!((drawdown.percent - 20.0).abs() < 1e-9);
+        assert_eq!(get_max_drawdown(&get_data(&[1.0, 2.0, 3.0])), None);
+
+        let stats = get_stats(&data, 0.001).unwrap();
+        assert_eq!(stats.high, 120.0);
+        assert!((stats.change - 20.0).abs() < 1e-9);
+        assert!((stats.annualized_volatility - stats.volatility * 252f64.sqrt()).abs() < 1e-9);
+        assert_eq!(get_stats(&[], 0.001), None);
+
+        assert_eq!(get_z_score(&[1.0, -1.0, 1.0, -1.0, 3.0]), Some(3.0));
+        assert_eq!(get_z_score(&[1.0, 1.0, 3.0]), None);
+        assert_eq!(get_volatility(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_returns_skip_weekends() {
+        // Seven working days over nine calendar days
+        let data = get_data(&[100.0, 101.0, 100.0, 102.0, 101.0, 103.0, 102.0]);
+        assert_eq!(data.len(), 9);
+
+        let returns = get_returns(&data);
+        assert_eq!(returns.len(), 6);
+        // Monday changes from Friday
+        assert_eq!(returns[4].0, NaiveDate::from_ymd_opt(2024, 1, 8).unwrap());
+        assert!((returns[4].1 - 2.0 / 101.0 * 100.0).abs() < 1e-9);
+
+        // The carried forward weekend changes neither the volatility nor the z-score
+        let working: Vec<_> = data
+            .iter()
+            .filter(|(date, _)| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+            .copied()
+            .collect();
+        let (stats, working) = (
+            get_stats(&data, 0.001).unwrap(),
+            get_stats(&working, 0.001).unwrap(),
+        );
+        assert_eq!(stats.volatility, working.volatility);
+        assert_eq!(stats.z_score, working.z_score);
+        // Windows of three changes between trading days
+        assert_eq!(get_rolling_correlation(&data, &data, 3).len(), 4);
+    }
+
+    #[test]
+    fn test_correlation() {
+        assert_eq!(
+            get_correlation(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]),
+            Some(1.0)
+        );
+        assert_eq!(
+            get_correlation(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]),
+            Some(-1.0)
+        );
+        assert_eq!(get_correlation(&[1.0, 1.0], &[1.0, 2.0]), None);
+
+        // `b` misses the 4th day, `a` then changes from the 3rd to the 5th day
+        let a = get_data(&[1.0, 1.1, 1.0, 0.5, 1.2, 1.1]);
+        let mut b = get_data(&[2.0, 2.2, 2.0, 2.2, 2.4, 2.2]);
+        b.remove(3);
+        let rolling = get_rolling_correlation(&a, &b, 3);
+        assert_eq!(rolling.len(), 2);
+        assert_eq!(rolling[1].0, a.last().unwrap().0);
+        assert!(rolling.iter().all(|(_, c)| (c - 1.0).abs() < 1e-9));
+        assert!((get_pair_correlation(&a, &b).unwrap() - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,264 @@
+use std::{env, fs, io, path::Path};
+
+use plotters::style::{IntoFont, RGBColor, TextStyle, BLACK, BLUE, GREEN, RED, WHITE};
+
+use crate::{database::guild_setting::get_guild_setting, environment};
+
+/// Font compiled into the binary, so that charts have text on systems without fonts
+const BUNDLED_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// Family of the bundled font
+pub const BUNDLED_FONT_FAMILY: &str = "DejaVu Sans";
+
+/// Guild setting holding the name of the theme of the guild
+pub const THEME_SETTING: &str = "chart_theme";
+
+/// Resolution of the configured chart sizes
+const BASE_DPI: u32 = 96;
+
+/// Colors, size and font of the charts
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub background: RGBColor,
+    /// Text, axes, grid and reference lines
+    pub foreground: RGBColor,
+    /// Rate of the trend chart and its last value
+    pub line: RGBColor,
+    /// Rising candles and highest rate
+    pub rise: RGBColor,
+    /// Falling candles and lowest rate
+    pub fall: RGBColor,
+    /// Moving averages and the currencies of comparisons
+    pub palette: Vec<RGBColor>,
+    /// Width at 96 DPI
+    pub width: u32,
+    /// Height at 96 DPI
+    pub height: u32,
+    pub dpi: u32,
+    /// Font family of all text
+    pub font: String,
+}
+
+impl Theme {
+    pub const NAMES: &'static [&'static str] = &["light", "dark", "custom"];
+
+    pub fn light() -> Theme {
+        Theme {
+            background: WHITE,
+            foreground: BLACK,
+            line: BLUE,
+            rise: GREEN,
+            fall: RED,
+            palette: vec![
+                RGBColor(31, 119, 180),
+                RGBColor(255, 127, 14),
+                RGBColor(44, 160, 44),
+                RGBColor(214, 39, 40),
+                RGBColor(148, 103, 189),
+                RGBColor(140, 86, 75),
+                RGBColor(227, 119, 194),
+                RGBColor(23, 190, 207),
+            ],
+            width: 800,
+            height: 400,
+            dpi: BASE_DPI,
+            font: BUNDLED_FONT_FAMILY.to_string(),
+        }
+    }
+
+    pub fn dark() -> Theme {
+        Theme {
+            background: RGBColor(30, 31, 34),
+            foreground: RGBColor(219, 222, 225),
+            line: RGBColor(88, 166, 255),
+            rise: RGBColor(63, 185, 80),
+            fall: RGBColor(248, 81, 73),
+            palette: vec![
+                RGBColor(88, 166, 255),
+                RGBColor(255, 166, 87),
+                RGBColor(126, 231, 135),
+                RGBColor(255, 123, 114),
+                RGBColor(210, 168, 255),
+                RGBColor(227, 179, 65),
+                RGBColor(247, 120, 186),
+                RGBColor(86, 212, 221),
+            ],
+            ..Theme::light()
+        }
+    }
+
+    /// Theme with the colors of a list such as
+    /// `background=#1e1f22,line=#58a6ff,palette=#ffa657;#7ee787` replaced
+    pub fn with_colors(mut self, list: &str) -> Result<Theme, String> {
+        for item in list
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let (name, value) = item
+                .split_once('=')
+                .ok_or(format!("`{}` is not a color such as line=#58a6ff", item))?;
+            let value = value.trim();
+            match name.trim().to_lowercase().as_str() {
+                "background" => self.background = parse_color(value)?,
+                "foreground" => self.foreground = parse_color(value)?,
+                "line" => self.line = parse_color(value)?,
+                "rise" => self.rise = parse_color(value)?,
+                "fall" => self.fall = parse_color(value)?,
+                "palette" => {
+                    self.palette = value
+                        .split(';')
+                        .map(|color| parse_color(color.trim()))
+                        .collect::<Result<_, _>>()?
+                }
+                name => return Err(format!("Unknown color `{}`", name)),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Pixels of `size` pixels at 96 DPI
+    pub fn px(&self, size: u32) -> u32 {
+        (size * self.dpi).div_ceil(BASE_DPI)
+    }
+
+    /// Pixel offset of an offset at 96 DPI
+    pub fn offset(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let scale = |v: i32| v * self.dpi as i32 / BASE_DPI as i32;
+        (scale(x), scale(y))
+    }
+
+    /// Width and height of the image in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.px(self.width), self.px(self.height))
+    }
+
+    /// Text of `size` pixels at 96 DPI in the font and color of the theme
+    pub fn text(&self, size: u32) -> TextStyle<'_> {
+        (self.font.as_str(), self.px(size))
+            .into_font()
+            .color(&self.foreground)
+    }
+
+    /// Color of line `i`, the palette repeating when there are more lines
+    pub fn color(&self, i: usize) -> RGBColor {
+        self.palette[i % self.palette.len()]
+    }
+}
+
+/// Color of a hex code such as `#58a6ff`
+fn parse_color(code: &str) -> Result<RGBColor, String> {
+    let hex = code.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(RGBColor(r, g, b)),
+        _ => Err(format!("`{}` is not a color such as #58a6ff", code)),
+    }
+}
+
+/// Theme `name` in the configured size, resolution and font
+pub fn get_theme(name: &str) -> Option<Theme> {
+    let theme = match name.to_lowercase().as_str() {
+        "light" => Theme::light(),
+        "dark" => Theme::dark(),
+        "custom" => Theme::light()
+            .with_colors(&environment::get_chart_colors())
+            .unwrap_or_else(|e| panic!("Invalid CHART_COLORS: {}", e)),
+        _ => return None,
+    };
+    Some(Theme {
+        width: environment::get_chart_width(),
+        height: environment::get_chart_height(),
+        dpi: environment::get_chart_dpi(),
+        font: environment::get_chart_font_family(),
+        ..theme
+    })
+}
+
+/// Theme chosen by the guild, `CHART_THEME` outside of guilds or when it did not choose one
+pub fn get_guild_theme(guild_id: Option<u64>) -> Theme {
+    guild_id
+        .and_then(|id| get_guild_setting(id, THEME_SETTING))
+        .and_then(|name| get_theme(&name))
+        .unwrap_or_else(environment::get_chart_theme)
+}
+
+/// Make the bundled font and `CHART_FONT` available to the charts.
+///
+/// The fonts are written to a directory which a generated fontconfig configuration adds to the fonts of the system,
+/// so that the charts have text even where no font is installed.
+///
+/// Sets `FONTCONFIG_FILE`, call it before any other thread is started.
+pub fn install_fonts() -> io::Result<()> {
+    let dir = env::temp_dir().join("exchange-rate-bot-fonts");
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("DejaVuSans.ttf"), BUNDLED_FONT)?;
+    if let Some(font) = environment::get_chart_font() {
+        let name = Path::new(&font).file_name().ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("CHART_FONT `{}` is not a file", font),
+        ))?;
+        fs::copy(&font, dir.join(name))?;
+    }
+
+    // Keeps the fonts of the system, or of the configuration given to the bot
+    let config_file = dir.join("fonts.conf");
+    let system_config = env::var("FONTCONFIG_FILE")
+        .ok()
+        .filter(|file| Path::new(file) != config_file)
+        .unwrap_or("/etc/fonts/fonts.conf".to_string());
+    let config = format!(
+        r#"<?xml version="1.0"?>
+<!DOCTYPE fontconfig SYSTEM "fonts.dtd">
+<fontconfig>
+    <dir>{}</dir>
+    <cachedir>{}</cachedir>
+    <include ignore_missing="yes">{}</include>
+</fontconfig>
+"#,
+        dir.display(),
+        dir.join("cache").display(),
+        system_config
+    );
+    fs::write(&config_file, config)?;
+    env::set_var("FONTCONFIG_FILE", &config_file);
+    log::debug!("Installed the chart fonts in {}", dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_colors() {
+        let theme = Theme::light()
+            .with_colors("background=#1e1f22, line=58A6FF, palette=#ff0000;#00ff00")
+            .unwrap();
+        assert_eq!(theme.background, RGBColor(30, 31, 34));
+        assert_eq!(theme.line, RGBColor(88, 166, 255));
+        assert_eq!(theme.palette, [RED, GREEN]);
+        assert_eq!(theme.color(3), GREEN);
+        assert_eq!(theme.foreground, BLACK);
+
+        assert!(Theme::light().with_colors("line=#58a6f").is_err());
+        assert!(Theme::light().with_colors("line=#58a6fg").is_err());
+        assert!(Theme::light().with_colors("border=#000000").is_err());
+        assert!(Theme::light().with_colors("palette=").is_err());
+    }
+
+    #[test]
+    fn test_scaling() {
+        let theme = Theme {
+            dpi: 144,
+            ..Theme::light()
+        };
+        assert_eq!(theme.size(), (1200, 600));
+        assert_eq!(theme.px(1), 2);
+        assert_eq!(theme.offset((-70, 16)), (-105, 24));
+        assert_eq!(Theme::light().size(), (800, 400));
+    }
+}
@@ -0,0 +1,68 @@
+import json
+import re
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+
+class StubOllamaServer(BaseHTTPRequestHandler):
+    """Answers Ollama's /api/chat with a canned report, for the evaluation."""
+
+    CHUNK_DELAY = 0.05  # Seconds between streamed chunks
+
+    def do_POST(self):
+        if self.path != "/api/chat":
+            self.send_error(404, "Only /api/chat is available.")
+            return
+
+        length = int(self.headers.get("Content-Length", 0))
+        request = json.loads(self.rfile.read(length))
+        model = request.get("model", "stub")
+        prompt = next(
+            (m["content"] for m in reversed(request["messages"]) if m["role"] == "user"),
+            "",
+        )
+
+        # Quote the first rate of the prompt, so the number check has something to verify
+        rates = re.findall(r"\d+\.\d{4}", prompt)
+        currencies = re.findall(r"\b[A-Z]{3}\b", prompt)
+        if rates and len(currencies) >= 2:
+            answer = f"The exchange rate from {currencies[0]} to {currencies[1]} is now {rates[0]} {currencies[1]}."
+        else:
+            answer = "The exchange rate is unchanged."
+
+        self.send_response(200)
+        self.send_header("Content-type", "application/x-ndjson")
+        self.end_headers()
+
+        words = answer.split(" ")
+        for i, word in enumerate(words):
+            delta = word if i == 0 else " " + word
+            self.write_chunk({"model": model, "message": {"role": "assistant", "content": delta}, "done": False})
+            time.sleep(self.CHUNK_DELAY)
+
+        self.write_chunk({
+            "model": model,
+            "message": {"role": "assistant", "content": ""},
+            "done": True,
+            "prompt_eval_count": len(prompt.split()),
+            "eval_count": len(words),
+        })
+
+    def write_chunk(self, chunk):
+        self.wfile.write((json.dumps(chunk) + "\n").encode("utf-8"))
+        self.wfile.flush()
+
+    def log_message(self, format, *args):
+        # Override to prevent default logging to stderr
+        pass
+
+
+def run(server_class=HTTPServer, handler_class=StubOllamaServer, port=11435):
+    server_address = ("", port)
+    httpd = server_class(server_address, handler_class)
+    print(f"Starting stub LLM server on port {port}...")
+    httpd.serve_forever()
+
+
+if __name__ == "__main__":
+    run()